@@ -0,0 +1,11444 @@
+/*  ========================================================================  *
+ *
+ *    lib.rs
+ *    ~~~~~~
+ *
+ *    Simulation of Ernö Rubik’s Cube
+ *
+ *    Project:            cubus
+ *
+ *    Target language:    Rust 1.82.0
+ *
+ *    Text encoding:      UTF-8
+ *
+ *    Created 2013-04-19: Ulrich Singer
+ */
+
+#![crate_name = "cubus"]
+
+// Without the `std` feature, only the state, move and search core remains,
+// which needs no more than an allocator.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#![allow(unused_parens)]
+#![allow(unused_must_use)]
+
+#![allow(non_snake_case)]
+
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use core::cmp::Reverse;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::ops::{Deref, Mul};
+use core::str::FromStr;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+#[cfg(feature = "std")]
+use std::env;
+#[cfg(feature = "std")]
+use std::io::{self, Write};
+#[cfg(feature = "std")]
+use std::fs::{self, File, OpenOptions};
+#[cfg(feature = "std")]
+use std::path::Path;
+#[cfg(feature = "std")]
+use std::sync::OnceLock;
+#[cfg(feature = "std")]
+use std::thread;
+#[cfg(feature = "std")]
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+
+/*  ––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––  */
+
+
+/// A short unsigned integer type for cube-local coordinate values.
+/// Since the maximum cube size is 10, 4 bits would actually suffice.
+pub type Coord = u8;
+
+
+/// The layer value of a Move that turns all layers about its axis, i.e.
+/// the whole cube.  Notated `X*` for the X axis, see Move::rotation().
+pub const ALL_LAYERS: Coord = Coord::MAX;
+
+
+/// A type that designates a coordinate axis and a rotation direction.
+pub type Axis = char;
+
+
+/// The ways in which parsing moves, applying them, and searching can fail.
+/// More may come, so matches need a catch-all arm.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum CubusError
+{
+    InvalidAxis(Axis),          // Not one of X, Y, Z, x, y, z.
+    InvalidCoord(char),         // Not a layer digit in a move string.
+    Unexpected(usize, Option<char>, String),    // Where a move string or JSON went wrong, and what was due.
+    InvalidLayer(Axis, Coord),  // A move turns no layer of the cube.
+    OffCube(usize, Axis, Coord, Coord), // Where a move string names a layer beyond the highest.
+    LockedLayer(Axis, Coord),   // A move turns a locked layer.
+    UnbalancedParens,
+    SizeMismatch(Coord, Coord), // Cubes that must agree in size do not.
+    InvalidFacelet(char),       // Not one of U, R, F, D, L, B.
+    FaceletCount(usize),        // Not 6·N² facelets for any cube size N.
+    ImpossibleFacelets,         // No arrangement of bricks shows the facelets.
+    UnknownPiece(String),       // No piece has this name, e.g. UFR.
+    InvalidBricks,              // Bricks that no moves can leave so.
+    Unreachable(Vec<String>),   // Why moves cannot connect two states.
+    UnknownPattern(String, Coord),  // No pattern of this name for this size.
+    CenterMove(Axis, Coord),    // A middle-layer move, which cubies cannot follow.
+    InvalidCycle(String),       // Not a cycle of distinct corners or edges.
+    QrOverflow(usize),          // Too many bytes for the largest QR code made.
+    UnknownCase(String),        // No case of this name, e.g. T-perm.
+    InvalidDatabase(String),    // A file that holds no pattern databases.
+    InvalidOutline(usize),      // A line of a move tree indented below no parent.
+    InvalidPacked(String),      // Hex digits that encode no state of the cube.
+    PhaseFailed(String),        // A phase finds no moves to its goal from the state.
+    InvalidSize(Coord),         // Not a cube size from 1 to 10.
+    NoRandomState(Coord),       // A size whose random states Cube::random() cannot draw.
+    InvalidProtobuf(String),    // Bytes that decode to no message of proto/cubus.proto.
+    #[cfg(feature = "std")]
+    Io(io::Error)
+
+}   /* CubusError */
+
+impl fmt::Display for CubusError
+{
+    fn fmt (&self, fmt: &mut fmt::Formatter)
+    -> fmt::Result
+    {
+        match self
+        {
+            CubusError::InvalidAxis(axdir)
+                    =>  write!(fmt, "Invalid axis designator {}", axdir),
+            CubusError::InvalidCoord(chr)
+                    =>  write!(fmt, "Invalid coordinate value {}", chr),
+            CubusError::Unexpected(position, Some(chr), expected)
+                    =>  write!(fmt, "Unexpected '{}' at position {}, expected {}", chr, position, expected),
+            CubusError::Unexpected(position, None, expected)
+                    =>  write!(fmt, "Unexpected end at position {}, expected {}", position, expected),
+            CubusError::InvalidLayer(axdir, axval)
+                    =>  write!(fmt, "Move {} turns no layer of the cube", Move::new(*axdir, *axval)),
+            CubusError::OffCube(position, axdir, axval, axmax)
+                    =>  write!(fmt, "Move {}{} at position {} turns no layer of the {}x{}x{} cube, whose layers are 0 to {}; did you mean {}?",
+                               axdir, axval, position, axmax + 1, axmax + 1, axmax + 1, axmax,
+                               move_suggested(*axdir, *axval, *axmax)),
+            CubusError::LockedLayer(axdir, axval)
+                    =>  write!(fmt, "Move {} turns a locked layer", Move::new(*axdir, *axval)),
+            CubusError::UnbalancedParens
+                    =>  write!(fmt, "Unbalanced parenthesis"),
+            CubusError::SizeMismatch(lhs, rhs)
+                    =>  write!(fmt, "Cubes are of different size: {} and {}", lhs, rhs),
+            CubusError::InvalidFacelet(chr)
+                    =>  write!(fmt, "Invalid facelet {}", chr),
+            CubusError::FaceletCount(count)
+                    =>  write!(fmt, "{} facelets cover no cube", count),
+            CubusError::ImpossibleFacelets
+                    =>  write!(fmt, "No arrangement of bricks shows these facelets"),
+            CubusError::UnknownPiece(name)
+                    =>  write!(fmt, "No piece is named {}", name),
+            CubusError::InvalidBricks
+                    =>  write!(fmt, "The bricks do not fill the cube as moves leave them"),
+            CubusError::Unreachable(faults)
+                    =>  write!(fmt, "No sequence connects the states: {}", faults.join(" ")),
+            CubusError::UnknownPattern(name, size)
+                    =>  write!(fmt, "No pattern {} for the {}x{}x{} cube", name, size, size, size),
+            CubusError::CenterMove(axdir, axval)
+                    =>  write!(fmt, "Move {} turns centers, which cubies keep in place", Move::new(*axdir, *axval)),
+            CubusError::InvalidCycle(text)
+                    =>  write!(fmt, "Not a cycle of distinct corners or edges: {}", text),
+            CubusError::UnknownCase(name)
+                    =>  write!(fmt, "No case {} among the bundled algorithms", name),
+            CubusError::InvalidDatabase(path)
+                    =>  write!(fmt, "{} holds no pattern databases of this cubus", path),
+            CubusError::InvalidOutline(line)
+                    =>  write!(fmt, "Line {} of the move tree is indented deeper than the line above allows", line),
+            CubusError::InvalidPacked(hex)
+                    =>  write!(fmt, "Invalid packed state {}", hex),
+            CubusError::PhaseFailed(name)
+                    =>  write!(fmt, "The phase {} finds no moves to its goal from this state", name),
+            CubusError::InvalidSize(size)
+                    =>  write!(fmt, "No cube of size {}; sizes run from 1 to 10", size),
+            CubusError::NoRandomState(size)
+                    =>  write!(fmt, "No uniformly random states of the {}x{}x{} cube, only up to 3x3x3", size, size, size),
+            CubusError::InvalidProtobuf(reason)
+                    =>  write!(fmt, "Invalid protobuf message: {}", reason),
+            CubusError::QrOverflow(count)
+                    =>  write!(fmt, "{} bytes do not fit in a QR code of version 10 or less", count),
+            #[cfg(feature = "std")]
+            CubusError::Io(error)
+                    =>  write!(fmt, "{}", error)
+        }
+    }
+
+}   /* impl fmt::Display for CubusError */
+
+impl core::error::Error for CubusError {}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for CubusError
+{
+    fn from (error: io::Error)
+    -> CubusError
+    {
+        CubusError::Io(error)
+    }
+
+}   /* impl From<io::Error> for CubusError */
+
+
+/// A brick location in a cube-local coordinate system.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Eq, PartialEq, Hash, Copy, Clone)]
+pub struct Loc
+{
+    pub x: Coord,
+    pub y: Coord,
+    pub z: Coord
+
+}   /* Loc */
+
+/// Component accessors.
+fn get_x (loc: &Loc) -> Coord { loc.x }
+fn get_y (loc: &Loc) -> Coord { loc.y }
+fn get_z (loc: &Loc) -> Coord { loc.z }
+
+
+/// Symbolic names for cube face colors.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Eq, PartialEq, Hash, Copy, Clone)]
+pub enum Huename
+{
+    RD = 0x01,
+    OR = 0x02,
+    WT = 0x03,
+    YL = 0x04,
+    GN = 0x05,
+    BL = 0x06
+
+}   /* Huename */
+
+impl Huename
+{
+    /// Maps cube face color symbols to VT100 color control sequences.
+    pub fn vt100_attrs (&self)
+    -> &'static str
+    {
+        // "\e[2;30;40m" : Black
+        // "\e[2;31;41m" : Red
+        // "\e[2;32;42m" : Green
+        // "\e[2;33;43m" : Yellow
+        // "\e[2;34;44m" : Blue
+        // "\e[2;35;45m" : Magenta
+        // "\e[2;36;46m" : Cyan
+        // "\e[1;37;47m" : White
+        match *self
+        {
+            Huename::RD  => "\x1B[2;31;41m",
+            Huename::OR  => "\x1B[2;36;46m",    // Using Cyan for Orange.
+            Huename::WT  => "\x1B[1;37;47m",
+            Huename::YL  => "\x1B[2;33;43m",
+            Huename::GN  => "\x1B[2;32;42m",
+            Huename::BL  => "\x1B[2;34;44m"
+        }
+
+    } /* .vt100_attrs() */
+
+    /// Returns the color's initial letter.
+    pub fn letter (&self)
+    -> char
+    {
+        match *self
+        {
+            Huename::RD  => 'R',
+            Huename::OR  => 'O',
+            Huename::WT  => 'W',
+            Huename::YL  => 'Y',
+            Huename::GN  => 'G',
+            Huename::BL  => 'B'
+        }
+
+    } /* .letter() */
+
+    /// Returns the color's red, green and blue components.
+    pub fn rgb (&self)
+    -> (u8, u8, u8)
+    {
+        match *self
+        {
+            Huename::RD  => (183,  18,  52),
+            Huename::OR  => (255,  88,   0),
+            Huename::WT  => (255, 255, 255),
+            Huename::YL  => (255, 213,   0),
+            Huename::GN  => (  0, 155,  72),
+            Huename::BL  => (  0,  70, 173)
+        }
+
+    } /* .rgb() */
+
+    /// Returns the color with the given initial letter, see letter().
+    pub fn of_letter (chr: char)
+    -> Option<Huename>
+    {
+        hue_faces(&Brick::new(0, 0, 0).curHue).into_iter().find(|name| name.letter() == chr.to_ascii_uppercase())
+
+    } /* ::of_letter() */
+
+}   /* impl Huename */
+
+
+/// The faces of a cube, as seen with Front as the reference.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub enum Face
+{
+    Right,  // +X
+    Left,   // -X
+    Up,     // +Y
+    Down,   // -Y
+    Front,  // +Z
+    Back    // -Z
+
+}   /* Face */
+
+impl Face
+{
+    /// All faces, in the order of their axes and directions.
+    pub const ALL: [Face; 6] = [Face::Right, Face::Left, Face::Up, Face::Down, Face::Front, Face::Back];
+
+}   /* impl Face */
+
+
+/// Which color each face shows in the ordered state.  Cubes are modelled
+/// in the western scheme of Brick::new(); recolored() repaints a cube in
+/// another scheme for display, since moves only carry bricks around.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Eq, PartialEq, Copy, Clone)]
+pub struct ColorScheme
+{
+    pub faces: [Huename; 6]     // By Face, in the order of Face::ALL.
+
+}   /* ColorScheme */
+
+impl ColorScheme
+{
+    /// The western scheme: white opposite yellow, green opposite blue,
+    /// red opposite orange, with white up, green front and red right.
+    pub fn western ()
+    -> ColorScheme
+    {
+        ColorScheme { faces: hue_faces(&Brick::new(0, 0, 0).curHue) }
+
+    }   /* ::western() */
+
+    /// The Japanese scheme, which swaps the western blue and yellow, so
+    /// that white is opposite blue.
+    pub fn japanese ()
+    -> ColorScheme
+    {
+        let mut faces = ColorScheme::western().faces;
+        faces[Face::Down as usize] = Huename::BL;
+        faces[Face::Back as usize] = Huename::YL;
+
+        ColorScheme { faces }
+
+    }   /* ::japanese() */
+
+    /// Returns the scheme named ‘western’ or ‘japanese’, or the one given
+    /// by six different color letters R, O, W, Y, G, B for the faces Up,
+    /// Right, Front, Down, Left and Back, as in facelets().  E.g. the
+    /// western scheme is WRGYOB.
+    pub fn of_name (name: &str)
+    -> Option<ColorScheme>
+    {
+        match name
+        {
+            "western"   =>  return Some(ColorScheme::western()),
+            "japanese"  =>  return Some(ColorScheme::japanese()),
+            _           =>  ()
+        }
+
+        let letters: Vec<char> = name.chars().collect();
+        if letters.len() != 6
+        {
+            return None;
+        }
+
+        let mut faces = ColorScheme::western().faces;
+        for (&face, &chr) in [Face::Up, Face::Right, Face::Front, Face::Down, Face::Left, Face::Back].iter().zip(letters.iter())
+        {
+            faces[face as usize] = Huename::of_letter(chr)?;
+        }
+        if (0 .. 6).any(|face| faces[face + 1 ..].contains(&faces[face]))
+        {
+            return None;
+        }
+
+        Some(ColorScheme { faces })
+
+    }   /* ::of_name() */
+
+    /// Returns this scheme's color for the face that has the given color in
+    /// the western scheme.
+    pub fn paint (&self, name: Huename)
+    -> Huename
+    {
+        let western = ColorScheme::western().faces;
+
+        self.faces[western.iter().position(|&known| known == name).unwrap()]
+
+    } /* .paint() */
+
+    /// Returns the western color of the face that has the given color in
+    /// this scheme, which undoes paint().
+    pub fn unpaint (&self, name: Huename)
+    -> Huename
+    {
+        let western = ColorScheme::western().faces;
+
+        western[self.faces.iter().position(|&known| known == name).unwrap()]
+
+    } /* .unpaint() */
+
+    /// Returns a copy of the cube with every sticker painted in this scheme's
+    /// color for the face that it belongs to.
+    pub fn recolored (&self, cube: &Cube)
+    -> Cube
+    {
+        let mut recolored = cube.clone();
+        for brick in recolored.bricks.iter_mut()
+        {
+            let faces = hue_faces(&brick.curHue);
+            brick.curHue = hue_of_faces(&faces.map(|name| self.paint(name)));
+        }
+
+        recolored
+
+    } /* .recolored() */
+
+}   /* impl ColorScheme */
+
+impl Default for ColorScheme
+{
+    fn default ()
+    -> ColorScheme
+    {
+        ColorScheme::western()
+    }
+
+}   /* impl Default for ColorScheme */
+
+
+/// Face color distributions for a cube or a brick.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Eq, Copy, Clone)]
+pub struct Hue
+{
+    pub xpos: Huename,
+    pub xneg: Huename,
+    pub ypos: Huename,
+    pub yneg: Huename,
+    pub zpos: Huename,
+    pub zneg: Huename
+
+}   /* Hue */
+
+impl PartialEq for Hue
+{
+    fn eq (&self, other: &Hue)
+    -> bool
+    {
+        self.xpos == other.xpos
+     && self.ypos == other.ypos
+     && self.zpos == other.zpos
+    }
+
+}   /* impl PartialEq for Hue */
+
+/// Hashes the faces that PartialEq compares, which determine the others.
+impl Hash for Hue
+{
+    fn hash<H: Hasher> (&self, state: &mut H)
+    {
+        self.xpos.hash(state);
+        self.ypos.hash(state);
+        self.zpos.hash(state);
+    }
+
+}   /* impl Hash for Hue */
+
+
+/// Smallest movable cube fragment.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "BrickFields"))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Eq, PartialEq, Hash, Copy, Clone)]
+pub struct Brick
+{
+    pub curLoc: Loc,
+    pub curHue: Hue
+
+}   /* Brick */
+
+impl Brick
+{
+    /// Brick constructor.
+    pub fn new (x: Coord, y: Coord, z: Coord)
+    -> Brick
+    {
+        Brick {
+            curLoc: Loc { x, y, z },
+            curHue: Hue {
+                xpos: Huename::RD, xneg: Huename::OR,
+                ypos: Huename::WT, yneg: Huename::YL,
+                zpos: Huename::GN, zneg: Huename::BL
+            }
+        }
+
+    } /* ::new() */
+
+}   /* impl Brick */
+
+/// The serialized fields of a Brick, which deserialization accepts for
+/// places on a cube of up to 10x10x10 and colors turned as a whole.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct BrickFields
+{
+    curLoc: Loc,
+    curHue: Hue
+
+}   /* BrickFields */
+
+#[cfg(feature = "serde")]
+impl TryFrom<BrickFields> for Brick
+{
+    type Error = CubusError;
+
+    fn try_from (fields: BrickFields)
+    -> Result<Brick, CubusError>
+    {
+        let loc = &fields.curLoc;
+        if 9 < loc.x || 9 < loc.y || 9 < loc.z
+        || !hue_orientations().iter().any(|hue| hue_faces(hue) == hue_faces(&fields.curHue))
+        {
+            return Err(CubusError::InvalidBricks);
+        }
+
+        Ok(Brick { curLoc: fields.curLoc, curHue: fields.curHue })
+    }
+
+}   /* impl TryFrom<BrickFields> for Brick */
+
+
+/// Rotates a brick counter-clockwise by 90° about the cube's X axis.
+fn brick_rotated_x_pos (brick: &Brick, axmax: Coord)
+-> Brick
+{
+    let srcLoc = &brick.curLoc;
+    let srcHue = &brick.curHue;
+    Brick {
+        curLoc: Loc {
+            x: srcLoc.x,
+            y: axmax - srcLoc.z,
+            z: srcLoc.y
+        },
+        curHue: Hue {
+            xpos: srcHue.xpos,
+            xneg: srcHue.xneg,
+            ypos: srcHue.zneg,
+            yneg: srcHue.zpos,
+            zpos: srcHue.ypos,
+            zneg: srcHue.yneg
+        }
+    }
+
+}   /* brick_rotated_x_pos() */
+
+
+/// Rotates a brick clockwise by 90° about the cube's X axis.
+fn brick_rotated_x_neg (brick: &Brick, axmax: Coord)
+-> Brick
+{
+    let srcLoc = &brick.curLoc;
+    let srcHue = &brick.curHue;
+    Brick {
+        curLoc: Loc {
+            x: srcLoc.x,
+            y: srcLoc.z,
+            z: axmax - srcLoc.y
+        },
+        curHue: Hue {
+            xpos: srcHue.xpos,
+            xneg: srcHue.xneg,
+            ypos: srcHue.zpos,
+            yneg: srcHue.zneg,
+            zpos: srcHue.yneg,
+            zneg: srcHue.ypos
+        }
+    }
+
+}   /* brick_rotated_x_neg() */
+
+
+/// Rotates a brick counter-clockwise by 90° about the cube's Y axis.
+fn brick_rotated_y_pos (brick: &Brick, axmax: Coord)
+-> Brick
+{
+    let srcLoc = &brick.curLoc;
+    let srcHue = &brick.curHue;
+    Brick {
+        curLoc: Loc {
+            x: srcLoc.z,
+            y: srcLoc.y,
+            z: axmax - srcLoc.x
+        },
+        curHue: Hue {
+            xpos: srcHue.zpos,
+            xneg: srcHue.zneg,
+            ypos: srcHue.ypos,
+            yneg: srcHue.yneg,
+            zpos: srcHue.xneg,
+            zneg: srcHue.xpos
+        }
+    }
+
+}   /* brick_rotated_y_pos() */
+
+
+/// Rotates a brick clockwise by 90° about the cube's Y axis.
+fn brick_rotated_y_neg (brick: &Brick, axmax: Coord)
+-> Brick
+{
+    let srcLoc = &brick.curLoc;
+    let srcHue = &brick.curHue;
+    Brick {
+        curLoc: Loc {
+            x: axmax - srcLoc.z,
+            y: srcLoc.y,
+            z: srcLoc.x
+        },
+        curHue: Hue {
+            xpos: srcHue.zneg,
+            xneg: srcHue.zpos,
+            ypos: srcHue.ypos,
+            yneg: srcHue.yneg,
+            zpos: srcHue.xpos,
+            zneg: srcHue.xneg
+        }
+    }
+
+}   /* brick_rotated_y_neg() */
+
+
+/// Rotates a brick counter-clockwise by 90° about the cube's Z axis.
+fn brick_rotated_z_pos (brick: &Brick, axmax: Coord)
+-> Brick
+{
+    let srcLoc = &brick.curLoc;
+    let srcHue = &brick.curHue;
+    Brick {
+        curLoc: Loc {
+            x: axmax - srcLoc.y,
+            y: srcLoc.x,
+            z: srcLoc.z
+        },
+        curHue: Hue {
+            xpos: srcHue.yneg,
+            xneg: srcHue.ypos,
+            ypos: srcHue.xpos,
+            yneg: srcHue.xneg,
+            zpos: srcHue.zpos,
+            zneg: srcHue.zneg
+        }
+    }
+
+}   /* brick_rotated_z_pos() */
+
+
+/// Rotates a brick clockwise by 90° about the cube's Z axis.
+fn brick_rotated_z_neg (brick: &Brick, axmax: Coord)
+-> Brick
+{
+    let srcLoc = &brick.curLoc;
+    let srcHue = &brick.curHue;
+    Brick {
+        curLoc: Loc {
+            x: srcLoc.y,
+            y: axmax - srcLoc.x,
+            z: srcLoc.z
+        },
+        curHue: Hue {
+            xpos: srcHue.ypos,
+            xneg: srcHue.yneg,
+            ypos: srcHue.xneg,
+            yneg: srcHue.xpos,
+            zpos: srcHue.zpos,
+            zneg: srcHue.zneg
+        }
+    }
+
+}   /* brick_rotated_z_neg() */
+
+
+/// Checks that the indicated move turns a layer of a cube whose
+/// coordinates range up to axmax.
+fn move_check (axdir: Axis, axval: Coord, axmax: Coord)
+-> Result<(), CubusError>
+{
+    if !"XxYyZz".contains(axdir)
+    {
+        return Err(CubusError::InvalidAxis(axdir));
+    }
+    if axval > axmax && axval != ALL_LAYERS
+    {
+        return Err(CubusError::InvalidLayer(axdir, axval));
+    }
+
+    Ok(())
+
+}   /* move_check() */
+
+
+/// Returns the move likely meant by one that names a layer beyond axmax:
+/// that of the layer below if counted from 1, as faces are, or else the
+/// outermost layer on that side.
+fn move_suggested (axdir: Axis, axval: Coord, axmax: Coord)
+-> Move
+{
+    Move::new(axdir, if axval - 1 <= axmax { axval - 1 } else { axmax })
+
+}   /* move_suggested() */
+
+
+/// Checks every move of the given vector with move_check().
+fn movevec_check (moves: &[Move], axmax: Coord)
+-> Result<(), CubusError>
+{
+    moves.iter().try_for_each(|mov| move_check(mov.axdir, mov.axval, axmax))
+
+}   /* movevec_check() */
+
+
+/// Performs the indicated move on the given Brick vector
+/// and returns a new vector in the resulting state.
+pub fn brickvec_move (bricks: &[Brick], axdir: Axis, axval: Coord, axmax: Coord)
+-> Result<Vec<Brick>, CubusError>
+{
+    move_check(axdir, axval, axmax)?;
+
+    Ok(brickvec_turned(bricks, axdir, axval, axmax))
+
+}   /* brickvec_move() */
+
+
+/// Like brickvec_move(), for a move that has been checked already.
+fn brickvec_turned (bricks: &[Brick], axdir: Axis, axval: Coord, axmax: Coord)
+-> Vec<Brick>
+{
+    let mut newBricks = bricks.to_vec();
+    brickvec_turn(&mut newBricks, axdir, axval, axmax);
+
+    newBricks
+
+}   /* brickvec_turned() */
+
+
+/// Performs a checked move on the given bricks in place.
+fn brickvec_turn (bricks: &mut [Brick], axdir: Axis, axval: Coord, axmax: Coord)
+{
+    // A function that returns a fixed coordinate component of a Loc.
+    let selFun: fn (&Loc) -> Coord =
+    match axdir
+    {
+        'X' | 'x' =>  get_x,
+        'Y' | 'y' =>  get_y,
+        'Z' | 'z' =>  get_z,
+        _         =>  unreachable!()
+    };
+
+    // A function that rotates a brick ±90° at a time around a fixed cube axis.
+    let rotFun: fn (&Brick, Coord) -> Brick =
+    match axdir
+    {
+        'X' =>  brick_rotated_x_pos,
+        'x' =>  brick_rotated_x_neg,
+        'Y' =>  brick_rotated_y_pos,
+        'y' =>  brick_rotated_y_neg,
+        'Z' =>  brick_rotated_z_pos,
+        'z' =>  brick_rotated_z_neg,
+        _   =>  unreachable!()
+    };
+
+    // Only bricks in the affected layer are rotated, or all of them.
+    for brick in bricks.iter_mut()
+    {
+        if axval == ALL_LAYERS || selFun(&brick.curLoc) == axval
+        {
+            *brick = rotFun(brick, axmax);
+        }
+    }
+
+}   /* brickvec_turn() */
+
+
+/// Tells whether the location is in the layer that a move along `axdir`
+/// at coordinate `axval` turns.
+#[cfg(feature = "std")]
+fn loc_in_layer (loc: &Loc, axdir: Axis, axval: Coord)
+-> bool
+{
+    if axval == ALL_LAYERS
+    {
+        return true;
+    }
+    match axdir
+    {
+        'X' | 'x' =>  loc.x == axval,
+        'Y' | 'y' =>  loc.y == axval,
+        _         =>  loc.z == axval
+    }
+
+}   /* loc_in_layer() */
+
+
+/// Casts a move's identity as an integer, for fast equality tests.
+fn ident_of_move (axdir: Axis, axval: Coord)
+-> u16
+{
+    (((axdir as u16) & 0x00FF) << 8) | (axval as u16)
+
+}   /* ident_of_move() */
+
+
+/// A move on a cube, which is the rotation of a layer of bricks
+/// around the selected cube axis by 90° at a time.  Affected bricks
+/// are identified by their coordinate value on the rotation axis, or
+/// all of them by ALL_LAYERS in a rotation of the whole cube.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "MoveFields"))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Eq, PartialEq, Copy, Clone)]
+pub struct Move
+{
+    pub axdir:  Axis,
+    pub axval:  Coord,
+    #[cfg_attr(any(feature = "serde", feature = "schemars"), serde(skip))]
+    pub(crate) ident:  u16
+
+}   /* Move */
+
+impl Move
+{
+    /// Move constructor.
+    pub fn new (axdir: Axis, axval: Coord)
+    -> Move
+    {
+        Move { axdir, axval, ident: ident_of_move(axdir, axval) }
+
+    } /* ::new() */
+
+    /// Returns the rotation of the whole cube about the given axis, which
+    /// counts as one move but turns all layers, e.g. `x` in Singmaster
+    /// notation for `x*`.
+    pub fn rotation (axdir: Axis)
+    -> Move
+    {
+        Move::new(axdir, ALL_LAYERS)
+
+    } /* ::rotation() */
+
+    /// Tells whether this move rotates the whole cube.
+    pub fn is_rotation (&self)
+    -> bool
+    {
+        self.axval == ALL_LAYERS
+
+    } /* .is_rotation() */
+
+    /// Returns the move that undoes this one: the same layer turned the
+    /// other way.
+    pub fn inverse (&self)
+    -> Move
+    {
+        Move::new(invert_axis(self.axdir), self.axval)
+
+    } /* .inverse() */
+
+}   /* impl Move */
+
+/// The serialized fields of a Move, from which deserialization restores
+/// the identity that the search compares, for the axes and the layers 0
+/// to 9 that move strings name.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct MoveFields
+{
+    axdir:  Axis,
+    axval:  Coord
+
+}   /* MoveFields */
+
+#[cfg(feature = "serde")]
+impl TryFrom<MoveFields> for Move
+{
+    type Error = CubusError;
+
+    fn try_from (fields: MoveFields)
+    -> Result<Move, CubusError>
+    {
+        move_check(fields.axdir, fields.axval, 9)?;
+
+        Ok(Move::new(fields.axdir, fields.axval))
+    }
+
+}   /* impl TryFrom<MoveFields> for Move */
+
+/// Formats a move in the notation of movevec_of_string(), e.g. `X0`, or
+/// `X*` for a rotation.
+impl fmt::Display for Move
+{
+    fn fmt (&self, fmt: &mut fmt::Formatter)
+    -> fmt::Result
+    {
+        if self.is_rotation()
+        {
+            write!(fmt, "{}*", self.axdir)
+        }
+        else
+        {
+            write!(fmt, "{}{}", self.axdir, self.axval)
+        }
+    }
+
+}   /* impl fmt::Display for Move */
+
+
+/// Returns a vector of Moves that were parsed from the given string.
+/// Moves may be grouped in parentheses, and a group followed by `^n` is
+/// repeated n times, as in MoveSequence::from_str(), so that the output of
+/// repeated_string_of_movevec() reads back unchanged.
+/// A `*` in place of the layer digit rotates the whole cube, e.g. `Y*`.
+/// In brackets, `[A, B]` is the commutator A B A' B', and `[A: B]`, or
+/// `A: B` in any group, the conjugate A B A', e.g. `[X0: [Y2, Z0]]`.
+/// Text from `#` to the end of the line is a comment.  Any other stray
+/// character fails with CubusError::Unexpected, which tells where it is
+/// and what was expected there instead.
+pub fn movevec_of_string (string: &str, axmax: Coord)
+-> Result<Vec<Move>, CubusError>
+{
+    Ok(movevec_of_string_with_lints(string, axmax)?.0)
+
+}   /* movevec_of_string() */
+
+
+/// Like movevec_of_string(), but also returns a message for each move in
+/// the string that is likely a mistake, such as `4X0`, which turns a layer
+/// four times and so not at all.  Callers may show these as warnings.
+pub fn movevec_of_string_with_lints (string: &str, axmax: Coord)
+-> Result<(Vec<Move>, Vec<String>), CubusError>
+{
+    let mut lints: Vec<String> = vec![];
+    let moves = movevec_parsed(string, axmax, false, &mut lints)?;
+
+    Ok((moves, lints))
+
+}   /* movevec_of_string_with_lints() */
+
+
+/// Returns a vector of Moves that were parsed from the given string in the
+/// Singmaster notation of speedcubers, e.g. `R U2 F'`.  R, L, U, D, F and
+/// B turn the outer layers clockwise as seen from outside, a digit before
+/// them the layer that far in instead, e.g. `2R`.  Followed by `w`, or
+/// in lowercase, they turn the two outer layers, or with a digit before
+/// them as many, e.g. `Rw`, `r` or `3Rw`, or with two digits joined by a
+/// dash the layers between them as in SiGN, e.g. `2-3Rw`.  M, E and S
+/// turn the middle layer of an odd-sized cube the way L, D and F do.  x, y
+/// and z turn the whole cube the way R, U and F do.  A following `'` turns
+/// the other way and a following digit as often.  Groups are as in
+/// movevec_of_string(), but need no `^` before their power.
+pub fn movevec_of_singmaster (string: &str, axmax: Coord)
+-> Result<Vec<Move>, CubusError>
+{
+    movevec_parsed(string, axmax, true, &mut vec![])
+
+}   /* movevec_of_singmaster() */
+
+
+/// Returns the moves of a string with the switch markers of NISS, normal
+/// inverse scramble switch, as in fewest-moves solving, e.g. `R U (F D)`.
+/// Moves in parentheses at the outermost level go on the inverse, so the
+/// result is the other moves followed by the inverse of those, here
+/// `R U D' F'`.  Each part is read with `parse`, where inner parentheses
+/// group as usual.
+pub fn movevec_of_niss (string: &str, parse: &dyn Fn (&str) -> Result<Vec<Move>, CubusError>)
+-> Result<Vec<Move>, CubusError>
+{
+    let mut normal = String::new();
+    let mut inverse = String::new();
+    let mut depth = 0;
+    let mut isInComment = false;
+    for chr in string.chars()
+    {
+        isInComment = isInComment && chr != '\n' || chr == '#';
+        if !isInComment && chr == ')'
+        {
+            if depth == 0
+            {
+                return Err(CubusError::UnbalancedParens);
+            }
+            depth -= 1;
+        }
+        match (depth, isInComment)
+        {
+            (0, false) if chr == '(' || chr == ')'
+                    =>  { normal.push(' '); inverse.push(' '); },
+            (0, _)  =>  normal.push(chr),
+            _       =>  inverse.push(chr)
+        }
+        if !isInComment && chr == '('
+        {
+            depth += 1;
+        }
+    }
+    if depth != 0
+    {
+        return Err(CubusError::UnbalancedParens);
+    }
+
+    let mut moves = parse(&normal)?;
+    moves.extend(movevec_inverted(&parse(&inverse)?));
+
+    Ok(moves)
+
+}   /* movevec_of_niss() */
+
+
+/// Returns the moves of one clockwise turn in Singmaster notation, see
+/// movevec_of_singmaster(), of the layers `first` to `last` counted from 1,
+/// e.g. 1 to 2 for `Rw` or 2 to 3 for `2-3Rw`.
+fn movevec_of_face (letter: char, first: Coord, last: Coord, axmax: Coord)
+-> Result<Vec<Move>, CubusError>
+{
+    if axmax + 1 < last
+    {
+        return Err(CubusError::InvalidCoord((b'0' + last) as char));
+    }
+    if first < last
+    {
+        let mut moves: Vec<Move> = vec![];
+        for inner in first ..= last
+        {
+            moves.extend(movevec_of_face(letter, inner, inner, axmax)?);
+        }
+        return Ok(moves);
+    }
+    let layer = last;
+    let (near, far) = (layer - 1, axmax + 1 - layer);
+    let mid = axmax / 2;
+    if "MES".contains(letter) && axmax % 2 != 0
+    {
+        return Err(CubusError::InvalidCoord(letter));
+    }
+
+    Ok(match letter
+    {
+        'R' =>  vec![Move::new('x', far)],
+        'L' =>  vec![Move::new('X', near)],
+        'U' =>  vec![Move::new('y', far)],
+        'D' =>  vec![Move::new('Y', near)],
+        'F' =>  vec![Move::new('z', far)],
+        'B' =>  vec![Move::new('Z', near)],
+        'M' =>  vec![Move::new('X', mid)],
+        'E' =>  vec![Move::new('Y', mid)],
+        'S' =>  vec![Move::new('z', mid)],
+        _   =>  vec![Move::rotation(letter)]
+    })
+
+}   /* movevec_of_face() */
+
+
+/// Does the work of movevec_of_string(), or with `isSingmaster` of
+/// movevec_of_singmaster(), and adds the messages of any moves that are
+/// likely mistakes to `lints`.
+fn movevec_parsed (string: &str, axmax: Coord, isSingmaster: bool, lints: &mut Vec<String>)
+-> Result<Vec<Move>, CubusError>
+{
+    let maxChr = (b'0' + axmax) as char;
+
+    // The outermost move vector and those of any open groups, with the
+    // bracket that opened each and where a `:` or `,` split it if at all.
+    let mut groups: Vec<Vec<Move>> = vec![vec![]];
+    let mut marks: Vec<(char, Option<(usize, char)>)> = vec![('_', None)];
+
+    let mut count: u8 = 1;
+    let mut rangeStart: Coord = 0;
+    let mut axdir: Axis = '_';
+    let mut expectsAxis = true;
+    let mut isInComment = false;
+    let mut chars = string.chars().peekable();
+    // Errors tell the 1-based position of a character from what follows it.
+    let total = string.chars().count();
+    let unexpected = |rest: core::iter::Peekable<core::str::Chars>, chr: Option<char>, expected: String|
+                     CubusError::Unexpected(total - rest.count(), chr, expected);
+    while let Some(chr) = chars.next()
+    {
+        let rest = chars.clone();
+
+        if isInComment
+        {
+            // Ignore until end.
+            if chr == '\n'
+            {
+                isInComment = false;
+            }
+        }
+        else if expectsAxis
+        {
+            if isSingmaster && "RLUDFBMESxyzrludfb".contains(chr)
+            {
+                // A prefixed digit picks the layer, or the layers up to it
+                // of a wide move, which are two by default, and two digits
+                // joined by a dash the layers of a wide move between them.
+                // Following digits repeat.
+                let isWide = chr.is_ascii_lowercase() && !"xyz".contains(chr)
+                          || "RLUDFB".contains(chr) && chars.next_if_eq(&'w').is_some();
+                let (first, last) = if rangeStart != 0   { (rangeStart, count) }
+                                    else if isWide      { (1, if count == 1 {2} else {count}) }
+                                    else                { (count, count) };
+                if rangeStart != 0 && !isWide
+                {
+                    return Err(unexpected(rest, Some(chr), "a wide move after a range of layers".to_string()));
+                }
+                if first > last
+                {
+                    return Err(unexpected(rest, Some(chr), format!("a range of layers from low to high, not {}-{}", first, last)));
+                }
+                let mut moves = movevec_of_face(if isWide { chr.to_ascii_uppercase() } else { chr }, first, last, axmax)?;
+                let mut turns = 1;
+                if let Some(digit) = chars.peek().and_then(|chr| chr.to_digit(10))
+                {
+                    chars.next();
+                    turns = digit;
+                }
+                if chars.peek() == Some(&'\'')
+                {
+                    chars.next();
+                    moves = movevec_inverted(&moves);
+                }
+                for _ in 0 .. turns
+                {
+                    groups.last_mut().unwrap().extend_from_slice(&moves);
+                }
+                count = 1;
+                rangeStart = 0;
+            }
+            else if !isSingmaster
+            && (chr == 'X' || chr == 'x'
+             || chr == 'Y' || chr == 'y'
+             || chr == 'Z' || chr == 'z')
+            {
+                // Consume move axis.
+                axdir = chr;
+
+                expectsAxis = false;
+            }
+            else if ('1' ..= '9').contains(&chr) && (isSingmaster || chr != '1')
+            {
+                // A prefixed digit acts as a repeat count.
+                count = chr as u8 - b'0';
+            }
+            else if isSingmaster && chr == '-' && rangeStart == 0 && chars.peek().is_some_and(|chr| chr.is_ascii_digit())
+            {
+                // A dash after a digit starts a range of layers.
+                rangeStart = count;
+            }
+            else if chr == '(' || chr == '['
+            {
+                groups.push(vec![]);
+                marks.push((chr, None));
+            }
+            else if chr == ':' || chr == ',' && marks.last().unwrap().0 == '['
+            {
+                // A conjugate or commutator splits its group once.
+                let (_, split) = marks.last_mut().unwrap();
+                if split.is_some()
+                {
+                    return Err(unexpected(rest, Some(chr), "a single : or , per group".to_string()));
+                }
+                *split = Some((groups.last().unwrap().len(), chr));
+            }
+            else if chr == ')' || chr == ']'
+            {
+                if groups.len() < 2 || marks.last().unwrap().0 != if chr == ')' {'('} else {'['}
+                {
+                    return Err(CubusError::UnbalancedParens);
+                }
+                let group = movevec_of_split_group(groups.pop().unwrap(), marks.pop().unwrap().1);
+
+                // A group may be raised to a decimal power, in Singmaster
+                // notation also without the caret.
+                let mut power: usize = 1;
+                if chars.peek() == Some(&'^')
+                || isSingmaster && chars.peek().is_some_and(|chr| chr.is_ascii_digit())
+                {
+                    chars.next_if_eq(&'^');
+                    power = 0;
+                    while let Some(digit) = chars.peek().and_then(|chr| chr.to_digit(10))
+                    {
+                        chars.next();
+                        power = power * 10 + digit as usize;
+                    }
+                }
+
+                groups.last_mut().unwrap().extend(movevec_repeat(&group, power));
+            }
+            else if chr == '#'
+            {
+                isInComment = true;
+            }
+            else if !chr.is_whitespace()
+            {
+                let expected = if isSingmaster { "a face letter such as R, a layer digit, or a bracket" }
+                               else            { "an axis X, Y, Z, x, y or z, a count, or a bracket" };
+                return Err(unexpected(rest, Some(chr), expected.to_string()));
+            }
+        }
+        else
+        {
+            // Expecting a coordinate digit, or a star for all layers.
+            if '0' <= chr && chr <= maxChr || chr == '*'
+            {
+                let newMove = if chr == '*' { Move::rotation(axdir) }
+                              else          { Move::new(axdir, (chr as u8 - b'0') as Coord) };
+
+                if count >= 4
+                {
+                    lints.push(format!("{}{} turns a layer {} times, which equals {} turn{}",
+                                       count, newMove, count, count % 4, if count % 4 != 1 {"s"} else {""}));
+                }
+
+                while count != 0
+                {
+                    groups.last_mut().unwrap().push(newMove);
+                    count -= 1;
+                }
+
+                expectsAxis = true;
+                count = 1;
+            }
+            else if chr.is_ascii_digit()
+            {
+                let position = total - rest.count();
+                return Err(CubusError::OffCube(position, axdir, (chr as u8 - b'0') as Coord, axmax));
+            }
+            else
+            {
+                return Err(unexpected(rest, Some(chr), format!("a layer digit 0 to {} or *", axmax)));
+            }
+        }
+    }
+
+    if !expectsAxis
+    {
+        return Err(CubusError::Unexpected(total + 1, None, format!("a layer digit 0 to {} or *", axmax)));
+    }
+
+    if groups.len() != 1
+    {
+        return Err(CubusError::UnbalancedParens);
+    }
+
+    Ok(movevec_of_split_group(groups.pop().unwrap(), marks.pop().unwrap().1))
+
+}   /* movevec_parsed() */
+
+
+/// Returns the moves of a group that `split` divides into A and B, if at
+/// all: A B A' for the `:` of a conjugate, or A B A' B' for the `,` of a
+/// commutator.
+fn movevec_of_split_group (moves: Vec<Move>, split: Option<(usize, char)>)
+-> Vec<Move>
+{
+    let Some((at, sep)) = split
+    else
+    {
+        return moves;
+    };
+    let (lhs, rhs) = moves.split_at(at);
+
+    let mut expanded = moves.clone();
+    expanded.extend(movevec_inverted(lhs));
+    if sep == ','
+    {
+        expanded.extend(movevec_inverted(rhs));
+    }
+
+    expanded
+
+}   /* movevec_of_split_group() */
+
+
+/// Returns the given moves with consecutive turns of the same layer merged,
+/// leaving at most two quarter turns of it, or one in the negative sense.
+pub fn movevec_simplified (moves: &[Move])
+-> Vec<Move>
+{
+    let mut simple: Vec<Move> = Vec::with_capacity(moves.len());
+    for mov in moves.iter()
+    {
+        let upper = mov.axdir.to_ascii_uppercase();
+
+        // Net quarter turns of the trailing run on this layer, in positive sense.
+        let mut turns: i32 = if mov.axdir == upper {1} else {-1};
+        while let Some(last) = simple.last()
+        {
+            if last.axval != mov.axval || last.axdir.to_ascii_uppercase() != upper
+            {
+                break;
+            }
+            turns += if last.axdir == upper {1} else {-1};
+            simple.pop();
+        }
+
+        match turns.rem_euclid(4)
+        {
+            1   =>  simple.push(Move::new(upper, mov.axval)),
+            2   =>  {
+                simple.push(Move::new(upper, mov.axval));
+                simple.push(Move::new(upper, mov.axval));
+            },
+            3   =>  simple.push(Move::new(invert_axis(upper), mov.axval)),
+            _   =>  ()
+        }
+    }
+
+    simple
+
+}   /* movevec_simplified() */
+
+
+/// Returns the given parts of a solution joined, with the moves that cancel
+/// where each part meets the next taken out, and for each such seam how
+/// many moves cancel there.  Moves about one axis in a row commute, so
+/// those on either side of a seam cancel whatever their order, and where
+/// they cancel altogether the moves around them meet in turn.  Moves
+/// within a part stay as they are.
+pub fn movevec_joined (parts: &[Vec<Move>])
+-> (Vec<Move>, Vec<usize>)
+{
+    let mut joined: Vec<Move> = parts.first().cloned().unwrap_or_default();
+    let mut savings: Vec<usize> = vec![];
+    for part in parts.iter().skip(1)
+    {
+        let mut rest = part.as_slice();
+        let mut saved = 0;
+        while let (Some(last), Some(first)) = (joined.last(), rest.first())
+        {
+            let upper = last.axdir.to_ascii_uppercase();
+            if first.axdir.to_ascii_uppercase() != upper
+            {
+                break;
+            }
+
+            // The runs about the axis on either side, by layer.
+            let tailStart = joined.iter().rposition(|mov| mov.axdir.to_ascii_uppercase() != upper).map_or(0, |ind| ind + 1);
+            let headEnd = rest.iter().position(|mov| mov.axdir.to_ascii_uppercase() != upper).unwrap_or(rest.len());
+            let mut run = [&joined[tailStart ..], &rest[.. headEnd]].concat();
+            let runLen = run.len();
+            run.sort_by_key(|mov| mov.axval);
+            let merged = movevec_simplified(&run);
+            if merged.len() == runLen
+            {
+                break;
+            }
+
+            saved += runLen - merged.len();
+            joined.truncate(tailStart);
+            joined.extend_from_slice(&merged);
+            rest = &rest[headEnd ..];
+            if !merged.is_empty()
+            {
+                break;
+            }
+        }
+        joined.extend_from_slice(rest);
+        savings.push(saved);
+    }
+
+    (joined, savings)
+
+}   /* movevec_joined() */
+
+
+/// Returns the given moves repeated `count` times.
+pub fn movevec_repeat (moves: &[Move], count: usize)
+-> Vec<Move>
+{
+    let mut repeated: Vec<Move> = Vec::with_capacity(moves.len() * count);
+    for _ in 0 .. count
+    {
+        repeated.extend_from_slice(moves);
+    }
+
+    repeated
+
+}   /* movevec_repeat() */
+
+
+/// Returns the moves that undo the given ones: the same layers turned the
+/// other way, in reverse order.
+pub fn movevec_inverted (moves: &[Move])
+-> Vec<Move>
+{
+    moves.iter().rev().map(Move::inverse).collect()
+
+}   /* movevec_inverted() */
+
+
+/// Returns the commutator [A, B] of two move sequences, which is A, then
+/// B, then A undone, then B undone.
+pub fn movevec_commutator (lhs: &[Move], rhs: &[Move])
+-> Vec<Move>
+{
+    let mut moves: Vec<Move> = Vec::with_capacity(2 * (lhs.len() + rhs.len()));
+    moves.extend_from_slice(lhs);
+    moves.extend_from_slice(rhs);
+    moves.extend(movevec_inverted(lhs));
+    moves.extend(movevec_inverted(rhs));
+
+    moves
+
+}   /* movevec_commutator() */
+
+
+/// The names of the patterns that pattern_movevec() knows.
+pub const PATTERN_NAMES: [&str; 3] = ["checkerboard", "superflip", "cube-in-cube"];
+
+/// Returns the moves that turn the ordered cube of the given size into the
+/// pattern of the given name, one of PATTERN_NAMES: the checkerboard, for
+/// any size, turns every other layer twice; the superflip, which flips
+/// all edges, and the cube-in-cube are known for the 3x3x3 cube only.
+pub fn pattern_movevec (name: &str, size: Coord)
+-> Result<Vec<Move>, CubusError>
+{
+    let axmax = size - 1;
+    match (name, size)
+    {
+        ("checkerboard", 2 ..)
+                =>  Ok(['X', 'Y', 'Z'].iter()
+                                      .flat_map(|&axdir| (0 ..= axmax).step_by(2).flat_map(move |axval| [Move::new(axdir, axval); 2]))
+                                      .collect()),
+        ("superflip", 3)
+                =>  movevec_of_singmaster("U R2 F B R B2 R U2 L B2 R U' D' R2 F R' L B2 U2 F2", axmax),
+        ("cube-in-cube", 3)
+                =>  movevec_of_singmaster("F L F U' R U F2 L2 U' L' B D' B' L2 U", axmax),
+        _       =>  Err(CubusError::UnknownPattern(name.to_string(), size))
+    }
+
+}   /* pattern_movevec() */
+
+
+/// An algorithm of the database that cubus bundles, see ALGORITHMS.
+pub struct Algorithm
+{
+    pub set:   &'static str,        // OLL, PLL, F2L or parity.
+    pub name:  &'static str,        // Name of the case it solves, e.g. T-perm.
+    pub size:  Coord,               // Edge length of the cubes it is for.
+    pub note:  &'static str,        // What the case looks like or what it does.
+    pub moves: &'static str         // The algorithm in Singmaster notation.
+
+}   /* Algorithm */
+
+impl Algorithm
+{
+    /// Returns the moves of the algorithm for a cube of its size.
+    pub fn movevec (&self)
+    -> Result<Vec<Move>, CubusError>
+    {
+        movevec_of_singmaster(self.moves, self.size - 1)
+
+    } /* .movevec() */
+
+}   /* impl Algorithm */
+
+
+/// The bundled algorithm database: the 3x3x3 last-layer permutation (PLL)
+/// cases, the last-layer orientation (OLL) cases most often learned first,
+/// the basic inserts into the front right slot of the first two layers
+/// (F2L), and the two 4x4x4 parity algorithms.  Each is written with the
+/// unsolved part on top and solves its case up to a turn of the U layer.
+pub const ALGORITHMS: &[Algorithm] = &[
+    Algorithm { set: "F2L", name: "F2L-1", size: 3, note: "Corner above the slot, edge at UR", moves: "U R U' R'" },
+    Algorithm { set: "F2L", name: "F2L-2", size: 3, note: "Corner above the slot, edge at UF", moves: "U' F' U F" },
+    Algorithm { set: "F2L", name: "F2L-3", size: 3, note: "Corner above the slot, edge at UL", moves: "F' U' F" },
+    Algorithm { set: "F2L", name: "F2L-4", size: 3, note: "Corner above the slot, edge at UB", moves: "R U R'" },
+    Algorithm { set: "OLL", name: "OLL-1", size: 3, note: "Dot, nothing oriented", moves: "R U2 R2 F R F' U2 R' F R F'" },
+    Algorithm { set: "OLL", name: "OLL-2", size: 3, note: "Dot, nothing oriented", moves: "F R U R' U' F' f R U R' U' f'" },
+    Algorithm { set: "OLL", name: "OLL-21", size: 3, note: "H, cross with corners facing sideways in pairs", moves: "R U2 R' U' R U R' U' R U' R'" },
+    Algorithm { set: "OLL", name: "OLL-22", size: 3, note: "Pi, cross with one pair of corners facing sideways", moves: "R U2 R2 U' R2 U' R2 U2 R" },
+    Algorithm { set: "OLL", name: "OLL-23", size: 3, note: "Headlights, cross with two corners oriented side by side", moves: "R2 D' R U2 R' D R U2 R" },
+    Algorithm { set: "OLL", name: "OLL-24", size: 3, note: "Chameleon, cross with two corners oriented side by side", moves: "r U R' U' r' F R F'" },
+    Algorithm { set: "OLL", name: "OLL-25", size: 3, note: "Bowtie, cross with two corners oriented diagonally", moves: "F' r U R' U' r' F R" },
+    Algorithm { set: "OLL", name: "OLL-26", size: 3, note: "Antisune, cross with one corner oriented", moves: "R U2 R' U' R U' R'" },
+    Algorithm { set: "OLL", name: "OLL-27", size: 3, note: "Sune, cross with one corner oriented", moves: "R U R' U R U2 R'" },
+    Algorithm { set: "OLL", name: "OLL-28", size: 3, note: "Corners oriented, two edges flipped", moves: "r U R' U' M U R U' R'" },
+    Algorithm { set: "OLL", name: "OLL-33", size: 3, note: "Shoelaces", moves: "R U R' U' R' F R F'" },
+    Algorithm { set: "OLL", name: "OLL-44", size: 3, note: "Square P shape", moves: "f R U R' U' f'" },
+    Algorithm { set: "OLL", name: "OLL-45", size: 3, note: "T shape", moves: "F R U R' U' F'" },
+    Algorithm { set: "OLL", name: "OLL-51", size: 3, note: "Line, no corner oriented", moves: "f R U R' U' R U R' U' f'" },
+    Algorithm { set: "OLL", name: "OLL-57", size: 3, note: "Corners oriented, two opposite edges flipped", moves: "R U R' U' M' U R U' r'" },
+    Algorithm { set: "PLL", name: "Aa-perm", size: 3, note: "Three corners cycle", moves: "R' F R' B2 R F' R' B2 R2" },
+    Algorithm { set: "PLL", name: "Ab-perm", size: 3, note: "Three corners cycle", moves: "R2 B2 R F R' B2 R F' R" },
+    Algorithm { set: "PLL", name: "E-perm", size: 3, note: "Corners swap in two pairs", moves: "R B' R' F R B R' F' R B R' F R B' R' F'" },
+    Algorithm { set: "PLL", name: "F-perm", size: 3, note: "Two corners and two edges swap, one bar", moves: "R' U' F' R U R' U' R' F R2 U' R' U' R U R' U R" },
+    Algorithm { set: "PLL", name: "Ga-perm", size: 3, note: "Three corners and three edges cycle", moves: "R2 U R' U R' U' R U' R2 U' D R' U R D'" },
+    Algorithm { set: "PLL", name: "Gb-perm", size: 3, note: "Three corners and three edges cycle", moves: "R' U' R U D' R2 U R' U R U' R U' R2 D" },
+    Algorithm { set: "PLL", name: "Gc-perm", size: 3, note: "Three corners and three edges cycle", moves: "R2 U' R U' R U R' U R2 U D' R U' R' D" },
+    Algorithm { set: "PLL", name: "Gd-perm", size: 3, note: "Three corners and three edges cycle", moves: "R U R' U' D R2 U' R U' R' U R' U R2 D'" },
+    Algorithm { set: "PLL", name: "H-perm", size: 3, note: "Opposite edges swap", moves: "M2 U M2 U2 M2 U M2" },
+    Algorithm { set: "PLL", name: "Ja-perm", size: 3, note: "Two corners and two edges swap, two bars", moves: "R' U L' U2 R U' R' U2 R L U'" },
+    Algorithm { set: "PLL", name: "Jb-perm", size: 3, note: "Two corners and two edges swap, two bars", moves: "R U R' F' R U R' U' R' F R2 U' R' U'" },
+    Algorithm { set: "PLL", name: "Na-perm", size: 3, note: "Diagonal corners and opposite edges swap", moves: "R U R' U R U R' F' R U R' U' R' F R2 U' R' U2 R U' R'" },
+    Algorithm { set: "PLL", name: "Nb-perm", size: 3, note: "Diagonal corners and opposite edges swap", moves: "R' U R U' R' F' U' F R U R' F R' F' R U' R" },
+    Algorithm { set: "PLL", name: "Ra-perm", size: 3, note: "Two corners and two edges swap, one bar", moves: "R U' R' U' R U R D R' U' R D' R' U2 R' U'" },
+    Algorithm { set: "PLL", name: "Rb-perm", size: 3, note: "Two corners and two edges swap, one bar", moves: "R2 F R U R U' R' F' R U2 R' U2 R U" },
+    Algorithm { set: "PLL", name: "T-perm", size: 3, note: "Two corners and two edges swap, one bar", moves: "R U R' U' R' F R2 U' R' U' R U R' F'" },
+    Algorithm { set: "PLL", name: "Ua-perm", size: 3, note: "Three edges cycle", moves: "M2 U M U2 M' U M2" },
+    Algorithm { set: "PLL", name: "Ub-perm", size: 3, note: "Three edges cycle", moves: "M2 U' M U2 M' U' M2" },
+    Algorithm { set: "PLL", name: "V-perm", size: 3, note: "Diagonal corners and two edges swap", moves: "R' U R' U' B' R' B2 U' B' U B' R B R" },
+    Algorithm { set: "PLL", name: "Y-perm", size: 3, note: "Diagonal corners and two edges swap", moves: "F R U' R' U' R U R' F' R U R' U' R' F R F'" },
+    Algorithm { set: "PLL", name: "Z-perm", size: 3, note: "Edges swap in two pairs", moves: "M' U M2 U M2 U M' U2 M2 U'" },
+    Algorithm { set: "parity", name: "OLL-parity", size: 4, note: "One last-layer edge flipped", moves: "Rw U2 x Rw U2 Rw U2 Rw' U2 Lw U2 Rw' U2 Rw U2 Rw' U2 Rw'" },
+    Algorithm { set: "parity", name: "PLL-parity", size: 4, note: "Two opposite last-layer edges swap", moves: "2R2 U2 2R2 Uw2 2R2 Uw2" }
+];
+
+
+/// Returns the algorithm of the given name in ALGORITHMS, in any case.
+pub fn algorithm_named (name: &str)
+-> Result<&'static Algorithm, CubusError>
+{
+    ALGORITHMS.iter()
+              .find(|algorithm| algorithm.name.eq_ignore_ascii_case(name))
+              .ok_or_else(|| CubusError::UnknownCase(name.to_string()))
+
+}   /* algorithm_named() */
+
+
+/// Returns the algorithms in ALGORITHMS whose set, name, note or moves
+/// contain the given text, in any case.
+pub fn algorithms_matching (text: &str)
+-> Vec<&'static Algorithm>
+{
+    let text = text.to_lowercase();
+    ALGORITHMS.iter()
+              .filter(|algorithm| [algorithm.set, algorithm.name, algorithm.note, algorithm.moves].iter()
+                                        .any(|field| field.to_lowercase().contains(&text)))
+              .collect()
+
+}   /* algorithms_matching() */
+
+
+/// Returns which stickers of a cube of the given size a mask covers, one
+/// flag per sticker in the order of Cube::facelets().  The mask lists,
+/// separated by commas, pieces named by the faces of their home places,
+/// e.g. UFR or UF, and regions: F2L, the layers below the top, OLL, those
+/// and the top face, and LL, the whole cube.
+pub fn sticker_mask (spec: &str, size: Coord)
+-> Result<Vec<bool>, CubusError>
+{
+    let axmax = size - 1;
+    let places: Vec<(usize, Loc)> = [2, 0, 4, 3, 1, 5].iter()
+        .flat_map(|&face| (0 ..= axmax).flat_map(move |row| (0 ..= axmax).map(move |col| (face, face_loc(face, row, col, axmax)))))
+        .collect();
+
+    let mut mask = vec![false; places.len()];
+    for item in spec.split(',')
+    {
+        let region = item.trim().to_ascii_uppercase();
+        let name = match region.as_str()
+        {
+            "F2L" | "OLL" | "LL"    =>  String::new(),
+            _                       =>  piece_name_checked(&region, size)?
+        };
+        for (isCovered, (face, loc)) in mask.iter_mut().zip(places.iter())
+        {
+            *isCovered |= match region.as_str()
+            {
+                "F2L"   =>  loc.y < axmax,
+                "OLL"   =>  loc.y < axmax || *face == 2,
+                "LL"    =>  true,
+                _       =>  piece_name(loc, axmax) == name
+            };
+        }
+    }
+
+    Ok(mask)
+
+}   /* sticker_mask() */
+
+
+/// Returns the algorithms in ALGORITHMS for the cube's size that solve the
+/// stickers of the mask on it, see sticker_mask(), each with the turns of
+/// the U layer that it needs before and after, shortest first.  Their
+/// moves are ranked by their length in STM, then in ETM, which counts
+/// whole-cube turns, and then by how few layers they turn, which spares
+/// regrips.  The cube must face as an ordered one does.
+pub fn algorithms_solving (cube: &Cube, mask: &[bool])
+-> Vec<(&'static Algorithm, Vec<Move>)>
+{
+    let goal: Vec<char> = Cube::new(cube.size).facelets().chars().collect();
+    let isSolved = |cube: &Cube| cube.facelets().chars().zip(goal.iter()).zip(mask.iter())
+                                     .all(|((chr, &goalChr), &isCovered)| !isCovered || chr == goalChr);
+
+    let turnU = Move::new('y', cube.size - 1);
+    let mut found: Vec<(&'static Algorithm, Vec<Move>)> = vec![];
+    for algorithm in ALGORITHMS.iter().filter(|algorithm| algorithm.size == cube.size)
+    {
+        let Ok(moves) = algorithm.movevec() else { continue };
+        'turns: for before in 0 .. 4
+        {
+            let mut after = cube.copy_with_checked_moves(&vec![turnU; before]);
+            after.apply_checked_moves(&moves);
+            for turns in 0 .. 4
+            {
+                if isSolved(&after)
+                {
+                    let mut all = vec![turnU; before];
+                    all.extend_from_slice(&moves);
+                    all.extend(vec![turnU; turns]);
+                    found.push((algorithm, movevec_simplified(&all)));
+                    break 'turns;
+                }
+                after.apply_checked_moves(&[turnU]);
+            }
+        }
+    }
+
+    found.sort_by_key(|(_, moves)| {
+        let metrics = MoveMetrics::of_movevec(moves, cube.size);
+        let mut layers: Vec<(Axis, Coord)> = moves.iter().map(|mov| (mov.axdir.to_ascii_uppercase(), mov.axval)).collect();
+        layers.sort();
+        layers.dedup();
+        (metrics.stm, metrics.etm, layers.len())
+    });
+
+    found
+
+}   /* algorithms_solving() */
+
+
+/// Returns the moves of the 3x3x3 case of the given name in ALGORITHMS,
+/// which solve the case up to a turn of the U layer.
+pub fn case_movevec (name: &str)
+-> Result<Vec<Move>, CubusError>
+{
+    match algorithm_named(name)?
+    {
+        algorithm if algorithm.size == 3
+                =>  algorithm.movevec(),
+        _       =>  Err(CubusError::UnknownCase(name.to_string()))
+    }
+
+}   /* case_movevec() */
+
+
+/// Returns a setup of the named case for practice, see case_movevec(): the
+/// inverse of its algorithm between random turns of the U layer, so that
+/// the case shows from any side and needs any final turn to solve.
+pub fn drill_movevec (name: &str, rng: &mut Rng)
+-> Result<Vec<Move>, CubusError>
+{
+    let auf = |rng: &mut Rng| vec![Move::new('y', 2); rng.below(4)];
+    let mut moves = auf(rng);
+    moves.extend(movevec_inverted(&case_movevec(name)?));
+    moves.extend(auf(rng));
+
+    Ok(movevec_simplified(&moves))
+
+}   /* drill_movevec() */
+
+
+/// Returns the moves as a string in the notation of string_of_movevec(),
+/// but with the stretches that take the form of a conjugate A B A⁻¹ or a
+/// commutator A B A⁻¹ B⁻¹ written as `[A: B]` or `[A, B]`, e.g.
+/// `[Y2: [X0, Z2]]`.  Of the ways to write the moves so, it picks one that
+/// leaves the fewest moves outside brackets.  Only the moves as given are
+/// matched, after merging turns of the same layer, not their effect.
+pub fn bracketed_string_of_movevec (moves: &[Move])
+-> String
+{
+    let moves = movevec_simplified(moves);
+    let mut best: BTreeMap<(usize, usize), (usize, String)> = BTreeMap::new();
+
+    bracketed_best(&moves, 0, moves.len(), &string_of_movevec, "", &mut best).1
+
+}   /* bracketed_string_of_movevec() */
+
+
+/// Returns the moves as bracketed_string_of_movevec() does, but in the
+/// notation of singmaster_string_of_movevec(), e.g. `[R U R': D]`.
+pub fn bracketed_singmaster_string_of_movevec (moves: &[Move], axmax: Coord)
+-> String
+{
+    let moves = movevec_simplified(moves);
+    let mut best: BTreeMap<(usize, usize), (usize, String)> = BTreeMap::new();
+    let format = |span: &[Move]| singmaster_string_of_movevec(span, axmax);
+
+    bracketed_best(&moves, 0, moves.len(), &format, " ", &mut best).1
+
+}   /* bracketed_singmaster_string_of_movevec() */
+
+
+/// Returns the number of moves left outside brackets, and the string, of
+/// the best bracketed form of moves[start .. end], memoized in `best`.
+/// Moves are written by `format`, and stretches joined by `sep`.
+fn bracketed_best (moves: &[Move], start: usize, end: usize, format: &dyn Fn (&[Move]) -> String, sep: &str,
+                   best: &mut BTreeMap<(usize, usize), (usize, String)>)
+-> (usize, String)
+{
+    if let Some(found) = best.get(&(start, end))
+    {
+        return found.clone();
+    }
+
+    let isInverse = |lhs: &[Move], rhs: &[Move]| movevec_simplified(&movevec_inverted(lhs)) == rhs;
+    let span = &moves[start .. end];
+    let len  = span.len();
+    let mut found = (len, format(span));
+
+    // A conjugate, with the longest outer part that fits.
+    if let Some(outer) = (1 .. len.div_ceil(2)).rev().find(|&outer| isInverse(&span[.. outer], &span[len - outer ..]))
+    {
+        let inner = bracketed_best(moves, start + outer, end - outer, format, sep, best);
+        if inner.0 < found.0
+        {
+            found = (inner.0, format!("[{}: {}]", format(&span[.. outer]), inner.1));
+        }
+    }
+
+    // A commutator, which leaves no move outside.
+    if len % 2 == 0 && 0 < found.0
+    {
+        for lhsLen in 1 .. len / 2
+        {
+            let (lhs, rhs) = (&span[.. lhsLen], &span[lhsLen .. len / 2]);
+            if isInverse(lhs, &span[len / 2 .. len / 2 + lhsLen]) && isInverse(rhs, &span[len / 2 + lhsLen ..])
+            {
+                found = (0, format!("[{}, {}]", format(lhs), format(rhs)));
+                break;
+            }
+        }
+    }
+
+    // Two stretches, one after the other.
+    for split in start + 1 .. end
+    {
+        if found.0 == 0
+        {
+            break;
+        }
+        let head = bracketed_best(moves, start, split, format, sep, best);
+        let tail = bracketed_best(moves, split, end, format, sep, best);
+        if head.0 + tail.0 < found.0
+        {
+            let sep = if head.1.ends_with(']') || tail.1.starts_with('[') {" "} else {sep};
+            found = (head.0 + tail.0, format!("{}{}{}", head.1, sep, tail.1));
+        }
+    }
+
+    best.insert((start, end), found.clone());
+
+    found
+
+}   /* bracketed_best() */
+
+
+/// Returns the moves as a string in the notation of string_of_movevec(),
+/// but with each stretch that repeats a block of two or more moves written
+/// as a power of the block, e.g. `X2(X0Y1)^6`.
+pub fn repeated_string_of_movevec (moves: &[Move])
+-> String
+{
+    repeated_powers(moves, &string_of_movevec, "", "^")
+
+}   /* repeated_string_of_movevec() */
+
+
+/// Returns the moves, on a cube with the given maximum coordinate, like
+/// repeated_string_of_movevec() but in the notation of
+/// singmaster_string_of_movevec(), e.g. `R (R U R' U')6`.
+pub fn repeated_singmaster_string_of_movevec (moves: &[Move], axmax: Coord)
+-> String
+{
+    repeated_powers(moves, &|span: &[Move]| singmaster_string_of_movevec(span, axmax), " ", "")
+
+}   /* repeated_singmaster_string_of_movevec() */
+
+
+/// Writes the moves from left to right, taking at each one the block that
+/// repeats over the most moves from there, the shortest such, as a group
+/// followed by `caret` and its power.  Blocks that are powers themselves,
+/// e.g. of a single move, are passed over.  Moves are written by `format`, and
+/// stretches joined by `sep`.
+fn repeated_powers (moves: &[Move], format: &dyn Fn (&[Move]) -> String, sep: &str, caret: &str)
+-> String
+{
+    let mut parts: Vec<String> = vec![];
+    let mut plain = 0;
+    let mut ind = 0;
+    while ind < moves.len()
+    {
+        let rest = &moves[ind ..];
+        let isPower = |block: &[Move]| (1 .. block.len()).any(|len| {
+            block.len() % len == 0 && block.chunks(len).all(|chunk| chunk == &block[.. len])
+        });
+        let repeat = (2 ..= rest.len() / 2).filter(|&len| !isPower(&rest[.. len])).filter_map(|len| {
+            let count = rest.chunks_exact(len).take_while(|&chunk| chunk == &rest[.. len]).count();
+            (count >= 2).then_some((len * count, len, count))
+        }).max_by_key(|&(covered, len, _)| (covered, Reverse(len)));
+
+        let Some((covered, len, count)) = repeat
+        else
+        {
+            ind += 1;
+            continue;
+        };
+        if plain < ind
+        {
+            parts.push(format(&moves[plain .. ind]));
+        }
+        parts.push(format!("({}){}{}", format(&rest[.. len]), caret, count));
+        ind += covered;
+        plain = ind;
+    }
+    if plain < moves.len()
+    {
+        parts.push(format(&moves[plain ..]));
+    }
+
+    parts.join(sep)
+
+}   /* repeated_powers() */
+
+
+/// Returns the order of a move sequence on a cube of the given size, which
+/// is how often it must be applied to an ordered cube to restore it, see
+/// Cube::order().
+pub fn movevec_order (moves: &[Move], size: Coord)
+-> Result<usize, CubusError>
+{
+    Ok(Cube::new(size).copy_with_moves(moves)?.order())
+
+}   /* movevec_order() */
+
+
+/// Returns the given moves repeated `count` times, leaving out whole cycles
+/// of repetitions that restore the state on a cube of the given size.
+pub fn movevec_pow_mod_identity (moves: &[Move], count: usize, size: Coord)
+-> Result<Vec<Move>, CubusError>
+{
+    Ok(movevec_repeat(moves, count % movevec_order(moves, size)?))
+
+}   /* movevec_pow_mod_identity() */
+
+
+/// Returns the string representation of a vector of Moves.
+pub fn string_of_movevec (moves: &[Move])
+-> String
+{
+    moves.iter().map(|mov| mov.to_string()).collect()
+
+}   /* string_of_movevec() */
+
+
+/// Returns the moves, on a cube with the given maximum coordinate, in the
+/// notation of movevec_of_singmaster(), separated by spaces.  Rotations and
+/// turns of all layers about one axis become x, y or z, turns of the outer
+/// layers from a face wide moves, e.g. `Rw` or `3Rw`, and turns of adjacent
+/// inner layers wide moves with a range, e.g. `2-3Rw`.  A layer turned
+/// twice in a row becomes e.g. `R2`.
+pub fn singmaster_string_of_movevec (moves: &[Move], axmax: Coord)
+-> String
+{
+    let size = axmax + 1;
+    let mut tokens: Vec<String> = vec![];
+    let mut ind = 0;
+    while ind < moves.len()
+    {
+        let mov = &moves[ind];
+        let isUpper = mov.axdir.is_ascii_uppercase();
+        let axis = (mov.axdir.to_ascii_uppercase() as u8 - b'X') as usize;
+
+        // Whether the next moves turn the `count` layers from `first` alike.
+        let isRun = |count: Coord, first: Coord| {
+            let span = &moves[ind .. moves.len().min(ind + count as usize)];
+            span.len() == count as usize
+         && span.iter().all(|other| other.axdir == mov.axdir)
+         && (first .. first + count).all(|axval| span.iter().any(|other| other.axval == axval))
+        };
+        let isTurn = mov.is_rotation() || isRun(size, 0);
+        let wide = (2 .. size).rev().find(|&count| !isTurn && (isRun(count, 0) || isRun(count, size - count)));
+        let isLow = wide.is_some_and(|count| isRun(count, 0));
+
+        // Or the inner layers `first` to `first + count - 1`, from the
+        // nearer face, on a tie R, U or F.
+        let range = (2 .. size.saturating_sub(1)).rev().filter(|_| !isTurn && wide.is_none()).find_map(|count| {
+            let first = moves[ind ..].iter().take(count as usize).map(|other| other.axval).min()?;
+            (0 < first && first + count <= axmax && isRun(count, first)).then_some((first, count))
+        });
+
+        // Each letter with the number of layers it turns, or the layer
+        // that it turns, and the sense in which it turns clockwise.
+        let (letter, layer, isClockwise) =
+            if isTurn                            { (b"xyz"[axis] as char, 1, !isUpper) }
+            else if let Some(count) = wide       { if isLow { (b"LDB"[axis] as char, count, isUpper) }
+                                                   else     { (b"RUF"[axis] as char, count, !isUpper) } }
+            else if let Some((first, count)) = range
+                                                 { if 2 * first + count - 1 < axmax
+                                                            { (b"LDB"[axis] as char, first + count, isUpper) }
+                                                   else     { (b"RUF"[axis] as char, axmax + 1 - first, !isUpper) } }
+            else if 2 * mov.axval == axmax       { (b"MES"[axis] as char, 1, isUpper != (axis == 2)) }
+            else if axmax < 2 * mov.axval        { (b"RUF"[axis] as char, axmax + 1 - mov.axval, !isUpper) }
+            else                                 { (b"LDB"[axis] as char, mov.axval + 1, isUpper) };
+        ind += if mov.is_rotation() { 1 } else if isTurn { size as usize }
+               else if let Some((_, count)) = range { count as usize } else { wide.unwrap_or(1) as usize };
+
+        let mut token = match (wide.is_some(), range, layer)
+        {
+            (_, Some((_, count)), _)
+                            =>  format!("{}-{}{}w", layer + 1 - count, layer, letter),
+            (true, _, 2)    =>  format!("{}w", letter),
+            (true, _, _)    =>  format!("{}{}w", layer, letter),
+            (false, _, 1)   =>  letter.to_string(),
+            (false, _, _)   =>  format!("{}{}", layer, letter)
+        };
+        if !isClockwise
+        {
+            token.push('\'');
+        }
+        if tokens.last() == Some(&token)
+        {
+            tokens.pop();
+            token = format!("{}2", token.trim_end_matches('\''));
+        }
+        tokens.push(token);
+    }
+
+    tokens.join(" ")
+
+}   /* singmaster_string_of_movevec() */
+
+
+/// A sequence of moves that parses from and prints to the notation of
+/// movevec_of_string(), so that `seq.to_string().parse()` returns `seq`.
+/// Parsing accepts layers 0 to 9 and repeats `(...)^n` groups n times;
+/// whether the moves fit a given cube is checked when they are applied.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(any(feature = "serde", feature = "schemars"), serde(transparent))]
+#[derive(Eq, PartialEq, Clone, Default)]
+pub struct MoveSequence
+{
+    pub moves: Vec<Move>
+
+}   /* MoveSequence */
+
+impl MoveSequence
+{
+    /// MoveSequence constructor.
+    pub fn new (moves: Vec<Move>)
+    -> MoveSequence
+    {
+        MoveSequence { moves }
+
+    } /* ::new() */
+
+    /// Returns the sequence that undoes this one, e.g. the solution that
+    /// reverts a scramble.
+    pub fn inverse (&self)
+    -> MoveSequence
+    {
+        movevec_inverted(&self.moves).into()
+
+    } /* .inverse() */
+
+    /// Returns this sequence followed by the `next` one.
+    pub fn then (&self, next: &MoveSequence)
+    -> MoveSequence
+    {
+        let mut moves = self.moves.clone();
+        moves.extend_from_slice(&next.moves);
+
+        moves.into()
+
+    } /* .then() */
+
+    /// Returns this sequence repeated `count` times, with turns of the same
+    /// layer merged where the repetitions meet, see movevec_simplified().
+    pub fn repeat (&self, count: usize)
+    -> MoveSequence
+    {
+        movevec_simplified(&movevec_repeat(&self.moves, count)).into()
+
+    } /* .repeat() */
+
+    /// Like repeat(), but leaves out whole cycles of repetitions that
+    /// restore the state on a cube of the given size, see
+    /// movevec_pow_mod_identity().
+    pub fn pow_mod_identity (&self, count: usize, size: Coord)
+    -> Result<MoveSequence, CubusError>
+    {
+        Ok(movevec_simplified(&movevec_pow_mod_identity(&self.moves, count, size)?).into())
+
+    } /* .pow_mod_identity() */
+
+    /// Returns a copy of the cube with this sequence applied, see
+    /// Cube::copy_with_moves().
+    pub fn applied_to (&self, cube: &Cube)
+    -> Result<Cube, CubusError>
+    {
+        cube.copy_with_moves(&self.moves)
+
+    } /* .applied_to() */
+
+    /// Returns a scramble of `length` random moves for a cube of the given
+    /// size, see random_movevec().  Equal seeds yield equal scrambles.
+    pub fn scramble (size: Coord, length: usize, seed: u64)
+    -> MoveSequence
+    {
+        MoveSequence::new(random_movevec(size, length, &mut Rng::new(seed)))
+
+    } /* ::scramble() */
+
+    /// Encodes the sequence as a MoveSequence message of proto/cubus.proto.
+    #[cfg(feature = "protobuf")]
+    pub fn to_protobuf (&self)
+    -> Vec<u8>
+    {
+        let moves = self.moves.iter().map(|mv| layer_message(mv.axdir, mv.axval)).collect();
+        prost::Message::encode_to_vec(&MoveSequenceMessage { moves })
+
+    } /* .to_protobuf() */
+
+    /// Decodes a MoveSequence message of proto/cubus.proto.  Fails with
+    /// InvalidProtobuf on malformed bytes, and as move parsing does on moves
+    /// that no cube of size 10 or less can turn.
+    #[cfg(feature = "protobuf")]
+    pub fn from_protobuf (bytes: &[u8])
+    -> Result<MoveSequence, CubusError>
+    {
+        let message: MoveSequenceMessage = prost::Message::decode(bytes)
+            .map_err(|error| CubusError::InvalidProtobuf(error.to_string()))?;
+        let mut moves = vec![];
+        for layer in message.moves.iter()
+        {
+            let (axdir, axval) = layer_of_message(layer)?;
+            move_check(axdir, axval, 9)?;
+            moves.push(Move::new(axdir, axval));
+        }
+
+        Ok(MoveSequence::new(moves))
+
+    } /* ::from_protobuf() */
+
+}   /* impl MoveSequence */
+
+impl FromStr for MoveSequence
+{
+    type Err = CubusError;
+
+    fn from_str (string: &str)
+    -> Result<MoveSequence, CubusError>
+    {
+        Ok(MoveSequence::new(movevec_parsed(string, 9, false, &mut vec![])?))
+    }
+
+}   /* impl FromStr for MoveSequence */
+
+impl fmt::Display for MoveSequence
+{
+    fn fmt (&self, fmt: &mut fmt::Formatter)
+    -> fmt::Result
+    {
+        self.moves.iter().try_for_each(|mov| write!(fmt, "{}", mov))
+    }
+
+}   /* impl fmt::Display for MoveSequence */
+
+impl From<Vec<Move>> for MoveSequence
+{
+    fn from (moves: Vec<Move>)
+    -> MoveSequence
+    {
+        MoveSequence::new(moves)
+    }
+
+}   /* impl From<Vec<Move>> for MoveSequence */
+
+impl Deref for MoveSequence
+{
+    type Target = [Move];
+
+    fn deref (&self)
+    -> &[Move]
+    {
+        &self.moves
+    }
+
+}   /* impl Deref for MoveSequence */
+
+
+/// A node of a MoveTree.
+#[derive(Clone)]
+struct MoveTreeNode
+{
+    parent:   Option<usize>,
+    moves:    Vec<Move>,            // What leads here from the parent.
+    children: Vec<usize>
+
+}   /* MoveTreeNode */
+
+
+/// The moves tried from one state, as a tree like the analysis board of a
+/// chess program: each node holds the moves that lead to it from its
+/// parent, so that going back and playing other moves starts a branch
+/// beside the old line, which stays to return to.  Nodes are numbered in
+/// the order they were added, the root, the state itself, 0.
+#[derive(Clone)]
+pub struct MoveTree
+{
+    nodes:   Vec<MoveTreeNode>,
+    current: usize
+
+}   /* MoveTree */
+
+impl MoveTree
+{
+    /// MoveTree constructor, of the root alone.
+    pub fn new ()
+    -> MoveTree
+    {
+        MoveTree {
+            nodes: vec![MoveTreeNode { parent: None, moves: vec![], children: vec![] }],
+            current: 0
+        }
+
+    } /* ::new() */
+
+    /// Returns the number of the current node.
+    pub fn current (&self)
+    -> usize
+    {
+        self.current
+
+    } /* .current() */
+
+    /// Plays the given moves from the current node, going to the child that
+    /// they lead to, a new one unless the same moves were played before.
+    /// Returns the number of the child.
+    pub fn play (&mut self, moves: Vec<Move>)
+    -> usize
+    {
+        let known = self.nodes[self.current].children.iter().find(|&&child| self.nodes[child].moves == moves);
+        self.current = match known
+        {
+            Some(&child)    =>  child,
+            None            =>  {
+                self.nodes.push(MoveTreeNode { parent: Some(self.current), moves, children: vec![] });
+                let child = self.nodes.len() - 1;
+                self.nodes[self.current].children.push(child);
+                child
+            }
+        };
+
+        self.current
+
+    } /* .play() */
+
+    /// Goes back to the parent of the current node, keeping the line left.
+    /// Returns false at the root.
+    pub fn back (&mut self)
+    -> bool
+    {
+        match self.nodes[self.current].parent
+        {
+            Some(parent)    =>  { self.current = parent; true },
+            None            =>  false
+        }
+
+    } /* .back() */
+
+    /// Goes to the node of the given number.  Returns false if there is none.
+    pub fn goto (&mut self, node: usize)
+    -> bool
+    {
+        if node >= self.nodes.len()
+        {
+            return false;
+        }
+        self.current = node;
+
+        true
+
+    } /* .goto() */
+
+    /// Returns the moves that the given node holds.
+    pub fn moves_of (&self, node: usize)
+    -> &[Move]
+    {
+        &self.nodes[node].moves
+
+    } /* .moves_of() */
+
+    /// Returns all moves from the root to the current node.
+    pub fn path (&self)
+    -> Vec<Move>
+    {
+        let mut lines: Vec<&[Move]> = vec![];
+        let mut node = self.current;
+        while let Some(parent) = self.nodes[node].parent
+        {
+            lines.push(&self.nodes[node].moves);
+            node = parent;
+        }
+
+        lines.into_iter().rev().flatten().copied().collect()
+
+    } /* .path() */
+
+    /// Tells whether some node has more than one child.
+    pub fn has_branches (&self)
+    -> bool
+    {
+        self.nodes.iter().any(|node| node.children.len() > 1)
+
+    } /* .has_branches() */
+
+    /// Returns the nodes below the root, depth first in the order they
+    /// were played, each with its depth, 0 for the children of the root.
+    pub fn outline (&self)
+    -> Vec<(usize, usize)>
+    {
+        let mut outline: Vec<(usize, usize)> = vec![];
+        let mut pending: Vec<(usize, usize)> = self.nodes[0].children.iter().rev().map(|&child| (child, 0)).collect();
+        while let Some((node, depth)) = pending.pop()
+        {
+            outline.push((node, depth));
+            pending.extend(self.nodes[node].children.iter().rev().map(|&child| (child, depth + 1)));
+        }
+
+        outline
+
+    } /* .outline() */
+
+    /// Returns the tree as text, one node per line, indented by two spaces
+    /// per depth, with its moves as `format` writes them.
+    pub fn as_text (&self, format: &dyn Fn(&[Move]) -> String)
+    -> String
+    {
+        self.outline().into_iter().map(|(node, depth)| format!("{}{}\n", "  ".repeat(depth), format(&self.nodes[node].moves)))
+                                  .collect()
+
+    } /* .as_text() */
+
+    /// Returns the tree of text as as_text() writes it, with the moves of
+    /// each line as `parse` reads them, at its root.  Blank lines are
+    /// skipped.
+    pub fn of_text (text: &str, parse: &dyn Fn(&str) -> Result<Vec<Move>, CubusError>)
+    -> Result<MoveTree, CubusError>
+    {
+        let mut tree = MoveTree::new();
+        let mut line: Vec<usize> = vec![0];    // The nodes down to the last line.
+        for (num, text) in text.lines().enumerate().filter(|(_, text)| !text.trim().is_empty())
+        {
+            let depth = (text.len() - text.trim_start().len()) / 2;
+            if depth + 1 > line.len()
+            {
+                return Err(CubusError::InvalidOutline(num + 1));
+            }
+            line.truncate(depth + 1);
+            tree.current = line[depth];
+            line.push(tree.play(parse(text.trim())?));
+        }
+        tree.current = 0;
+
+        Ok(tree)
+
+    } /* ::of_text() */
+
+}   /* impl MoveTree */
+
+impl Default for MoveTree
+{
+    fn default ()
+    -> MoveTree
+    {
+        MoveTree::new()
+    }
+
+}   /* impl Default for MoveTree */
+
+
+/// How the axes of a move notation lie in the cube: for each of the
+/// notation's axes X, Y and Z, the cube axis that it stands for, and
+/// whether it points the other way.  The cube's own axes point right, up
+/// and to the front.  Moves turn about the notation's axes as the cube's
+/// moves turn about its own, so a reversed axis also reverses the turns.
+#[derive(Eq, PartialEq, Copy, Clone)]
+pub struct AxisConvention
+{
+    pub axes:     [Axis; 3],    // Uppercase cube axis for notation X, Y, Z.
+    pub reversed: [bool; 3]     // Whether the notation axis points the other way.
+
+}   /* AxisConvention */
+
+impl AxisConvention
+{
+    /// The cube's own convention, in which notation and cube axes agree.
+    pub fn native ()
+    -> AxisConvention
+    {
+        AxisConvention { axes: ['X', 'Y', 'Z'], reversed: [false; 3] }
+
+    }   /* ::native() */
+
+    /// Returns the convention named by the cube axes that the notation's X,
+    /// Y and Z stand for, each preceded by `-` if it points the other way,
+    /// e.g. `X-ZY` for a notation whose Z axis points up.
+    pub fn of_name (name: &str)
+    -> Option<AxisConvention>
+    {
+        let mut convention = AxisConvention::native();
+        let mut chars = name.chars();
+        for ind in 0 .. 3
+        {
+            let mut chr = chars.next()?;
+            if chr == '-'
+            {
+                convention.reversed[ind] = true;
+                chr = chars.next()?;
+            }
+            convention.axes[ind] = chr.to_ascii_uppercase();
+        }
+
+        let mut axes = convention.axes;
+        axes.sort();
+        if chars.next().is_some() || axes != ['X', 'Y', 'Z']
+        {
+            return None;
+        }
+
+        Some(convention)
+
+    }   /* ::of_name() */
+
+    /// Returns the given moves in the notation as moves of a cube with the
+    /// given maximum coordinate.
+    pub fn to_cube (&self, moves: &[Move], axmax: Coord)
+    -> Vec<Move>
+    {
+        moves.iter().map(|mov| {
+            let ind = (mov.axdir.to_ascii_uppercase() as u8 - b'X') as usize;
+            AxisConvention::moved(mov, self.axes[ind], mov.axdir.is_ascii_uppercase(), self.reversed[ind], axmax)
+        }).collect()
+
+    }   /* .to_cube() */
+
+    /// Returns the given moves of a cube with the given maximum coordinate
+    /// in the notation, which undoes to_cube().
+    pub fn to_notation (&self, moves: &[Move], axmax: Coord)
+    -> Vec<Move>
+    {
+        moves.iter().map(|mov| {
+            let ind = self.axes.iter().position(|&axis| axis == mov.axdir.to_ascii_uppercase()).unwrap();
+            AxisConvention::moved(mov, (b'X' + ind as u8) as Axis, mov.axdir.is_ascii_uppercase(), self.reversed[ind], axmax)
+        }).collect()
+
+    }   /* .to_notation() */
+
+    /// Returns the move about the given axis that turns the layer of `mov`,
+    /// counted from the other side and turning the other way if reversed.
+    fn moved (mov: &Move, axis: Axis, isUpper: bool, isReversed: bool, axmax: Coord)
+    -> Move
+    {
+        let axdir = if isUpper != isReversed { axis } else { axis.to_ascii_lowercase() };
+        let axval = if isReversed && !mov.is_rotation() { axmax - mov.axval } else { mov.axval };
+
+        Move::new(axdir, axval)
+
+    }   /* .moved() */
+
+}   /* impl AxisConvention */
+
+impl Default for AxisConvention
+{
+    fn default ()
+    -> AxisConvention
+    {
+        AxisConvention::native()
+    }
+
+}   /* impl Default for AxisConvention */
+
+
+/// Returns the moves with each rotation replaced by the turns of all layers
+/// about its axis on a cube with the given maximum coordinate.
+fn movevec_rotations_expanded (moves: &[Move], axmax: Coord)
+-> Vec<Move>
+{
+    moves.iter().flat_map(|mov| {
+        if mov.is_rotation() { (0 ..= axmax).map(|axval| Move::new(mov.axdir, axval)).collect() }
+        else                 { vec![*mov] }
+    }).collect()
+
+}   /* movevec_rotations_expanded() */
+
+
+/// Returns the moves without the whole-cube turns, i.e. rotations or moves
+/// of all layers about one axis, that they start or end with.  The moves after a leading
+/// turn are rewritten to turn the same bricks without it, so that the
+/// result plays from the original orientation; it reaches the same state
+/// but for the orientation of the cube as a whole.
+pub fn movevec_turns_trimmed (moves: &[Move], size: Coord)
+-> Vec<Move>
+{
+    let axmax = size - 1;
+    let len = size as usize;
+    let isTurn = |part: &[Move]| {
+        part.iter().all(|mov| mov.axdir == part[0].axdir)
+     && (0 ..= axmax).all(|axval| part.iter().any(|mov| mov.axval == axval))
+    };
+
+    let home = Cube::new(size);
+    let mut moves = movevec_rotations_expanded(moves, axmax);
+    while len <= moves.len() && isTurn(&moves[.. len])
+    {
+        // Each axis's face move after the turn is some face move before it.
+        let turn: Vec<Move> = moves.drain(.. len).collect();
+        let mut convention = AxisConvention::native();
+        for ind in 0 .. 3
+        {
+            let axis = (b'X' + ind as u8) as Axis;
+            let mut after = turn.clone();
+            after.push(Move::new(axis, axmax));
+            let turned = home.copy_with_checked_moves(&after);
+            for (image, isReversed) in ['X', 'Y', 'Z'].into_iter().flat_map(|image| [(image, false), (image, true)])
+            {
+                let mut before = vec![if isReversed { Move::new(image.to_ascii_lowercase(), 0) } else { Move::new(image, axmax) }];
+                before.extend_from_slice(&turn);
+                if home.copy_with_checked_moves(&before) == turned
+                {
+                    convention.axes[ind] = image;
+                    convention.reversed[ind] = isReversed;
+                    break;
+                }
+            }
+        }
+        moves = convention.to_cube(&moves, axmax);
+    }
+    while len <= moves.len() && isTurn(&moves[moves.len() - len ..])
+    {
+        moves.truncate(moves.len() - len);
+    }
+
+    moves
+
+}   /* movevec_turns_trimmed() */
+
+
+/// Returns a Hue's face colors in the order xpos, xneg, ypos, yneg, zpos, zneg.
+fn hue_faces (hue: &Hue)
+-> [Huename; 6]
+{
+    [hue.xpos, hue.xneg, hue.ypos, hue.yneg, hue.zpos, hue.zneg]
+
+}   /* hue_faces() */
+
+
+/// Returns the Hue with the given face colors, in the order of `hue_faces()`.
+fn hue_of_faces (faces: &[Huename; 6])
+-> Hue
+{
+    Hue {
+        xpos: faces[0], xneg: faces[1],
+        ypos: faces[2], yneg: faces[3],
+        zpos: faces[4], zneg: faces[5]
+    }
+
+}   /* hue_of_faces() */
+
+
+/// Returns the Hue of a brick with Hue `first` after it has been turned the
+/// way that a brick in initial orientation is turned to reach Hue `then`.
+fn hue_composed (first: &Hue, then: &Hue)
+-> Hue
+{
+    let homeFaces  = hue_faces(&Brick::new(0, 0, 0).curHue);
+    let firstFaces = hue_faces(first);
+    let thenFaces  = hue_faces(then);
+
+    let mut faces = firstFaces;
+    for face in 0 .. 6
+    {
+        // The face that `then` moves here, and what `first` shows there.
+        let slot = homeFaces.iter().position(|name| *name == thenFaces[face]).unwrap();
+        faces[face] = firstFaces[slot];
+    }
+
+    hue_of_faces(&faces)
+
+}   /* hue_composed() */
+
+
+/// Tells which faces of a brick at the given location are part of the cube's
+/// surface, in the same order as `hue_faces()`.
+fn loc_outward (loc: &Loc, axmax: Coord)
+-> [bool; 6]
+{
+    [loc.x == axmax, loc.x == 0,
+     loc.y == axmax, loc.y == 0,
+     loc.z == axmax, loc.z == 0]
+
+}   /* loc_outward() */
+
+
+/// Returns a unique index for a location in a cube of the given size.
+fn loc_index (loc: &Loc, size: Coord)
+-> usize
+{
+    let size = size as usize;
+
+    (loc.z as usize * size + loc.y as usize) * size + loc.x as usize
+
+}   /* loc_index() */
+
+
+/// Returns the location of the sticker at `row`, `col` of a face, in the
+/// order of `hue_faces()`, as seen from outside the cube.  The side faces
+/// have Up above them, Up has Back above it and Down has Front above it.
+fn face_loc (face: usize, row: Coord, col: Coord, axmax: Coord)
+-> Loc
+{
+    match face
+    {
+        0   =>  Loc { x: axmax,       y: axmax - row, z: axmax - col },
+        1   =>  Loc { x: 0,           y: axmax - row, z: col },
+        2   =>  Loc { x: col,         y: axmax,       z: row },
+        3   =>  Loc { x: col,         y: 0,           z: axmax - row },
+        4   =>  Loc { x: col,         y: axmax - row, z: axmax },
+        _   =>  Loc { x: axmax - col, y: axmax - row, z: 0 }
+    }
+
+}   /* face_loc() */
+
+
+/// Returns a location's distances from the nearest cube surface along each
+/// axis, sorted.  Moves only ever carry a brick between locations that have
+/// the same distances, which makes these locations an orbit.
+fn loc_orbit (loc: &Loc, axmax: Coord)
+-> [Coord; 3]
+{
+    let mut dist = [loc.x.min(axmax - loc.x),
+                    loc.y.min(axmax - loc.y),
+                    loc.z.min(axmax - loc.z)];
+    dist.sort();
+
+    dist
+
+}   /* loc_orbit() */
+
+
+/// Returns the 24 orientations of a brick as Hues, in a fixed order.
+fn hue_orientations ()
+-> Vec<Hue>
+{
+    let rotFuns: [fn (&Brick, Coord) -> Brick; 3] =
+        [brick_rotated_x_pos, brick_rotated_y_pos, brick_rotated_z_pos];
+
+    let mut bricks: Vec<Brick> = vec![Brick::new(0, 0, 0)];
+    let mut ind = 0;
+    while ind < bricks.len()
+    {
+        for rotFun in rotFuns.iter()
+        {
+            let brick = rotFun(&bricks[ind], 0);
+            if !bricks.iter().any(|known| known.curHue == brick.curHue)
+            {
+                bricks.push(brick);
+            }
+        }
+        ind += 1;
+    }
+
+    bricks.iter().map(|brick| brick.curHue).collect()
+
+}   /* hue_orientations() */
+
+
+/// Returns the orientations that a brick which starts out at location `home`
+/// can assume at location `loc`, i.e. those that keep its outer faces outside.
+fn hue_orientations_at (home: &Loc, loc: &Loc, axmax: Coord)
+-> Vec<Hue>
+{
+    let homeFaces = hue_faces(&Brick::new(0, 0, 0).curHue);
+    let homeOut = loc_outward(home, axmax);
+    let locOut  = loc_outward(loc, axmax);
+
+    hue_orientations().into_iter().filter(|hue| {
+        let faces = hue_faces(hue);
+        (0 .. 6).all(|face| {
+            let slot = homeFaces.iter().position(|name| *name == faces[face]).unwrap();
+            !locOut[face] || homeOut[slot]
+        })
+    }).collect()
+
+}   /* hue_orientations_at() */
+
+
+/// Groups the indices of the given bricks by the orbits of their locations.
+fn brickvec_orbits (bricks: &[Brick], axmax: Coord)
+-> Vec<Vec<usize>>
+{
+    let mut keys: Vec<[Coord; 3]> = vec![];
+    let mut orbits: Vec<Vec<usize>> = vec![];
+    for (ind, brick) in bricks.iter().enumerate()
+    {
+        let key = loc_orbit(&brick.curLoc, axmax);
+        match keys.iter().position(|known| *known == key)
+        {
+            Some(pos) =>  orbits[pos].push(ind),
+            None      =>  {
+                keys.push(key);
+                orbits.push(vec![ind]);
+            }
+        }
+    }
+
+    orbits
+
+}   /* brickvec_orbits() */
+
+
+/// Multiplies a little-endian base-256 number by `factor` and adds `addend`.
+fn bignum_mul_add (num: &mut Vec<u8>, factor: u32, addend: u32)
+{
+    let mut carry = addend;
+    for byte in num.iter_mut()
+    {
+        let value = (*byte as u32) * factor + carry;
+        *byte = (value & 0xFF) as u8;
+        carry = value >> 8;
+    }
+    while carry != 0
+    {
+        num.push((carry & 0xFF) as u8);
+        carry >>= 8;
+    }
+
+}   /* bignum_mul_add() */
+
+
+/// Divides a little-endian base-256 number by `divisor` and returns the remainder.
+fn bignum_div_rem (num: &mut [u8], divisor: u32)
+-> u32
+{
+    let mut rem: u32 = 0;
+    for byte in num.iter_mut().rev()
+    {
+        let value = (rem << 8) | (*byte as u32);
+        *byte = (value / divisor) as u8;
+        rem = value % divisor;
+    }
+
+    rem
+
+}   /* bignum_div_rem() */
+
+
+/// A Rubik's cube with a given edge length.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "CubeFields"))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Eq, Clone)]
+pub struct Cube
+{
+    pub size:   Coord,
+    pub bricks: Vec<Brick>,
+    #[cfg_attr(any(feature = "serde", feature = "schemars"), serde(default))]
+    pub locks:  Vec<(Axis, Coord)>  // Layers that moves may not turn, by uppercase axis.
+
+}   /* Cube */
+
+/// Cubes are equal when their bricks are, whatever their locks.
+impl PartialEq for Cube
+{
+    fn eq (&self, other: &Cube)
+    -> bool
+    {
+        self.size == other.size
+     && self.bricks == other.bricks
+    }
+
+}   /* impl PartialEq for Cube */
+
+/// Hashes what PartialEq compares, so that cube states can key hash tables.
+impl Hash for Cube
+{
+    fn hash<H: Hasher> (&self, state: &mut H)
+    {
+        self.size.hash(state);
+        self.bricks.hash(state);
+    }
+
+}   /* impl Hash for Cube */
+
+/// Returns the cube of the given fields, as decoders read them, if
+/// check_bricks() accepts it and each lock names a layer of the cube.
+#[cfg(any(feature = "serde", feature = "protobuf"))]
+fn cube_of_fields (size: Coord, bricks: Vec<Brick>, locks: Vec<(Axis, Coord)>)
+-> Result<Cube, CubusError>
+{
+    if !(1 ..= 10).contains(&size)
+    {
+        return Err(CubusError::InvalidSize(size));
+    }
+    let cube = Cube { size, bricks, locks };
+    cube.check_bricks()?;
+    for &(axis, axval) in cube.locks.iter()
+    {
+        if !"XYZ".contains(axis)
+        {
+            return Err(CubusError::InvalidAxis(axis));
+        }
+        if cube.size <= axval
+        {
+            return Err(CubusError::InvalidLayer(axis, axval));
+        }
+    }
+
+    Ok(cube)
+
+}   /* cube_of_fields() */
+
+/// The serialized fields of a Cube, which deserialization accepts as
+/// cube_of_fields() does.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct CubeFields
+{
+    size:   Coord,
+    bricks: Vec<Brick>,
+    #[serde(default)]
+    locks:  Vec<(Axis, Coord)>
+
+}   /* CubeFields */
+
+#[cfg(feature = "serde")]
+impl TryFrom<CubeFields> for Cube
+{
+    type Error = CubusError;
+
+    fn try_from (fields: CubeFields)
+    -> Result<Cube, CubusError>
+    {
+        cube_of_fields(fields.size, fields.bricks, fields.locks)
+    }
+
+}   /* impl TryFrom<CubeFields> for Cube */
+
+/// The messages of proto/cubus.proto, which Cube::to_protobuf() and
+/// MoveSequence::to_protobuf() encode, named as the schema names them with
+/// a Message suffix.  Colors are Huename values.
+#[cfg(feature = "protobuf")]
+#[derive(Clone, PartialEq, prost::Message)]
+struct LocMessage
+{
+    #[prost(uint32, tag = "1")]
+    x: u32,
+    #[prost(uint32, tag = "2")]
+    y: u32,
+    #[prost(uint32, tag = "3")]
+    z: u32
+
+}   /* LocMessage */
+
+#[cfg(feature = "protobuf")]
+#[derive(Clone, PartialEq, prost::Message)]
+struct HueMessage
+{
+    #[prost(int32, tag = "1")]
+    xpos: i32,
+    #[prost(int32, tag = "2")]
+    xneg: i32,
+    #[prost(int32, tag = "3")]
+    ypos: i32,
+    #[prost(int32, tag = "4")]
+    yneg: i32,
+    #[prost(int32, tag = "5")]
+    zpos: i32,
+    #[prost(int32, tag = "6")]
+    zneg: i32
+
+}   /* HueMessage */
+
+#[cfg(feature = "protobuf")]
+#[derive(Clone, PartialEq, prost::Message)]
+struct BrickMessage
+{
+    #[prost(message, optional, tag = "1")]
+    cur_loc: Option<LocMessage>,
+    #[prost(message, optional, tag = "2")]
+    cur_hue: Option<HueMessage>
+
+}   /* BrickMessage */
+
+/// A move, with ALL_LAYERS for a rotation, or a locked layer by its
+/// uppercase axis.
+#[cfg(feature = "protobuf")]
+#[derive(Clone, PartialEq, prost::Message)]
+struct LayerMessage
+{
+    #[prost(string, tag = "1")]
+    axis: String,
+    #[prost(uint32, tag = "2")]
+    layer: u32
+
+}   /* LayerMessage */
+
+#[cfg(feature = "protobuf")]
+#[derive(Clone, PartialEq, prost::Message)]
+struct CubeMessage
+{
+    #[prost(uint32, tag = "1")]
+    size: u32,
+    #[prost(message, repeated, tag = "2")]
+    bricks: Vec<BrickMessage>,
+    #[prost(message, repeated, tag = "3")]
+    locks: Vec<LayerMessage>
+
+}   /* CubeMessage */
+
+#[cfg(feature = "protobuf")]
+#[derive(Clone, PartialEq, prost::Message)]
+struct MoveSequenceMessage
+{
+    #[prost(message, repeated, tag = "1")]
+    moves: Vec<LayerMessage>
+
+}   /* MoveSequenceMessage */
+
+/// Returns the message of a layer, see LayerMessage.
+#[cfg(feature = "protobuf")]
+fn layer_message (axis: Axis, layer: Coord)
+-> LayerMessage
+{
+    LayerMessage { axis: axis.to_string(), layer: layer as u32 }
+
+}   /* layer_message() */
+
+/// Returns the axis and layer of a LayerMessage, if it names one letter
+/// and a layer that fits a Coord.
+#[cfg(feature = "protobuf")]
+fn layer_of_message (message: &LayerMessage)
+-> Result<(Axis, Coord), CubusError>
+{
+    let mut letters = message.axis.chars();
+    match (letters.next(), letters.next(), Coord::try_from(message.layer))
+    {
+        (Some(axis), None, Ok(layer))
+                =>  Ok((axis, layer)),
+        _       =>  Err(CubusError::InvalidProtobuf(format!("no layer {}{}", message.axis, message.layer)))
+    }
+
+}   /* layer_of_message() */
+
+/// Returns the message of a brick, see LocMessage and HueMessage.
+#[cfg(feature = "protobuf")]
+fn brick_message (brick: &Brick)
+-> BrickMessage
+{
+    let loc = &brick.curLoc;
+    let faces = hue_faces(&brick.curHue).map(|name| name as i32);
+    BrickMessage {
+        cur_loc: Some(LocMessage { x: loc.x as u32, y: loc.y as u32, z: loc.z as u32 }),
+        cur_hue: Some(HueMessage {
+            xpos: faces[0], xneg: faces[1],
+            ypos: faces[2], yneg: faces[3],
+            zpos: faces[4], zneg: faces[5]
+        })
+    }
+
+}   /* brick_message() */
+
+/// Returns the brick of a BrickMessage, if its coordinates fit a Coord and
+/// its colors are Huename values; Cube::from_protobuf() checks the rest.
+#[cfg(feature = "protobuf")]
+fn brick_of_message (message: &BrickMessage)
+-> Result<Brick, CubusError>
+{
+    let invalid = || CubusError::InvalidProtobuf(String::from("a brick without location or colors"));
+    let loc = message.cur_loc.as_ref().ok_or_else(invalid)?;
+    let hue = message.cur_hue.as_ref().ok_or_else(invalid)?;
+
+    let coords = [loc.x, loc.y, loc.z].map(Coord::try_from);
+    let colors = [hue.xpos, hue.xneg, hue.ypos, hue.yneg, hue.zpos, hue.zneg].map(|value| match value
+    {
+        1       =>  Some(Huename::RD),
+        2       =>  Some(Huename::OR),
+        3       =>  Some(Huename::WT),
+        4       =>  Some(Huename::YL),
+        5       =>  Some(Huename::GN),
+        6       =>  Some(Huename::BL),
+        _       =>  None
+    });
+    match (coords, colors)
+    {
+        ([Ok(x), Ok(y), Ok(z)], [Some(xpos), Some(xneg), Some(ypos), Some(yneg), Some(zpos), Some(zneg)])
+                =>  Ok(Brick {
+                        curLoc: Loc { x, y, z },
+                        curHue: hue_of_faces(&[xpos, xneg, ypos, yneg, zpos, zneg])
+                    }),
+        _       =>  Err(invalid())
+    }
+
+}   /* brick_of_message() */
+
+impl Cube
+{
+    /// Cube constructor.  Panics for sizes other than 1 to 10, which
+    /// try_new() refuses instead.
+    pub fn new (size: Coord)
+    -> Cube
+    {
+        match Cube::try_new(size)
+        {
+            Ok(cube)    =>  cube,
+            Err(error)  =>  panic!("{}", error)
+        }
+
+    } /* ::new() */
+
+    /// Cube constructor, for sizes from 1 to 10.
+    pub fn try_new (size: Coord)
+    -> Result<Cube, CubusError>
+    {
+        if !(1 ..= 10).contains(&size)
+        {
+            return Err(CubusError::InvalidSize(size));
+        }
+
+        let axmax = size - 1;
+        let mut bricks: Vec<Brick> = vec![];
+
+        for z in 0 .. size
+        {
+            for y in 0 .. size
+            {
+                for x in 0 .. size
+                {
+                    // We're only interested in bricks that partake in the cube's surface.
+                    if x == 0 || x == axmax
+                    || y == 0 || y == axmax
+                    || z == 0 || z == axmax
+                    {
+                        bricks.push(Brick::new(x, y, z));
+                    }
+                }
+            }
+        }
+
+        Ok(Cube {
+            size,
+            bricks,
+            locks: vec![]
+        })
+
+    } /* ::try_new() */
+
+    /// Manipulates the receiving Cube instance according to the given Move
+    /// sequence and returns a new Cube instance in the resulting state.
+    pub fn copy_with_moves (&self, moves: &[Move])
+    -> Result<Cube, CubusError>
+    {
+        movevec_check(moves, self.size - 1)?;
+        if let Some((mov, axval)) = moves.iter().find_map(|mov| Some((mov, self.locked_layer_of(mov)?)))
+        {
+            return Err(CubusError::LockedLayer(mov.axdir, axval));
+        }
+
+        Ok(self.copy_with_checked_moves(moves))
+
+    } /* .copy_with_moves() */
+
+    /// Like copy_with_moves(), for moves that have been checked already,
+    /// such as those of legal_moves() or movevec_of_string().
+    pub(crate) fn copy_with_checked_moves (&self, moves: &[Move])
+    -> Cube
+    {
+        let mut cube = self.clone();
+        cube.apply_checked_moves(moves);
+
+        cube
+
+    } /* .copy_with_checked_moves() */
+
+    /// Performs the moves of the named pattern on this cube in place, see
+    /// pattern_movevec().  On an ordered cube, this shows the pattern.
+    pub fn apply_pattern (&mut self, name: &str)
+    -> Result<(), CubusError>
+    {
+        self.apply_moves(&pattern_movevec(name, self.size)?)
+
+    } /* .apply_pattern() */
+
+    /// Performs the given moves on this cube in place, without allocating.
+    /// Like copy_with_moves(), rejects all moves if any of them is invalid
+    /// or turns a locked layer.
+    pub fn apply_moves (&mut self, moves: &[Move])
+    -> Result<(), CubusError>
+    {
+        movevec_check(moves, self.size - 1)?;
+        if let Some((mov, axval)) = moves.iter().find_map(|mov| Some((mov, self.locked_layer_of(mov)?)))
+        {
+            return Err(CubusError::LockedLayer(mov.axdir, axval));
+        }
+
+        self.apply_checked_moves(moves);
+
+        Ok(())
+
+    } /* .apply_moves() */
+
+    /// Performs a single move on this cube in place, like apply_moves().
+    pub fn apply_move (&mut self, mov: &Move)
+    -> Result<(), CubusError>
+    {
+        self.apply_moves(core::slice::from_ref(mov))
+
+    } /* .apply_move() */
+
+    /// Like apply_moves(), for moves that have been checked already.
+    pub(crate) fn apply_checked_moves (&mut self, moves: &[Move])
+    {
+        let axmax = self.size - 1;
+        for mov in moves.iter()
+        {
+            brickvec_turn(&mut self.bricks, mov.axdir, mov.axval, axmax);
+        }
+
+    } /* .apply_checked_moves() */
+
+    /// Freezes the indicated layer, so that legal_moves() leaves it out
+    /// and copy_with_moves() rejects moves that turn it.
+    pub fn lock_layer (&mut self, axdir: Axis, axval: Coord)
+    {
+        if axval == ALL_LAYERS
+        {
+            (0 .. self.size).for_each(|axval| self.lock_layer(axdir, axval));
+        }
+        else if !self.is_locked(axdir, axval)
+        {
+            self.locks.push((axdir.to_ascii_uppercase(), axval));
+        }
+
+    } /* .lock_layer() */
+
+    /// Tells whether the indicated layer is locked, or for ALL_LAYERS any
+    /// layer about the axis.
+    pub fn is_locked (&self, axdir: Axis, axval: Coord)
+    -> bool
+    {
+        self.locked_layer_of(&Move::new(axdir, axval)).is_some()
+
+    } /* .is_locked() */
+
+    /// Returns a locked layer that the move turns, if any.
+    fn locked_layer_of (&self, mov: &Move)
+    -> Option<Coord>
+    {
+        let axis = mov.axdir.to_ascii_uppercase();
+        self.locks.iter().find(|&&(locked, axval)| locked == axis && (mov.is_rotation() || axval == mov.axval))
+                         .map(|&(_, axval)| axval)
+
+    } /* .locked_layer_of() */
+
+    /// Returns the moves that may currently be made on this cube, in the
+    /// order in which the move finders try them.  Every layer of an
+    /// unconstrained cube can be turned either way about each axis.
+    pub fn legal_moves (&self)
+    -> Vec<Move>
+    {
+        let mut moves: Vec<Move> = Vec::with_capacity(6 * self.size as usize);
+        for axdirRef in ['X', 'x', 'Y', 'y', 'Z', 'z'].iter()
+        {
+            let axdir = *axdirRef;
+            for axval in 0 .. self.size
+            {
+                if !self.is_locked(axdir, axval)
+                {
+                    moves.push(Move { axdir, axval, ident: ident_of_move(axdir, axval) });
+                }
+            }
+        }
+
+        moves
+
+    } /* .legal_moves() */
+
+    /// Returns the state reached by applying to this cube the transformation
+    /// that takes an ordered cube to the `other` cube, which is like playing
+    /// the moves that produced this cube followed by those that produced
+    /// `other`.
+    pub fn compose (&self, other: &Cube)
+    -> Result<Cube, CubusError>
+    {
+        if other.size != self.size
+        {
+            return Err(CubusError::SizeMismatch(self.size, other.size));
+        }
+
+        // Indices of the bricks that start out at each location.
+        let size = self.size;
+        let mut homeAt: Vec<usize> = vec_of_size((size as usize).pow(3), 0);
+        for (ind, brick) in Cube::new(size).bricks.iter().enumerate()
+        {
+            homeAt[loc_index(&brick.curLoc, size)] = ind;
+        }
+
+        let bricks = self.bricks.iter().map(|brick| {
+            // `other` moves whichever brick is at this location
+            // like it moved the brick that started out here.
+            let then = &other.bricks[homeAt[loc_index(&brick.curLoc, size)]];
+            Brick {
+                curLoc: then.curLoc,
+                curHue: hue_composed(&brick.curHue, &then.curHue)
+            }
+        }).collect();
+
+        Ok(Cube {
+            size,
+            bricks,
+            locks: self.locks.clone()
+        })
+
+    } /* .compose() */
+
+    /// Returns the cube's bricks indexed by `loc_index()` of their locations.
+    /// Inner locations hold no brick.
+    pub fn bricks_by_loc (&self)
+    -> Vec<Option<&Brick>>
+    {
+        let size = self.size as usize;
+
+        let mut brickAt: Vec<Option<&Brick>> = vec_of_size(size * size * size, None);
+        for brick in self.bricks.iter()
+        {
+            brickAt[loc_index(&brick.curLoc, self.size)] = Some(brick);
+        }
+
+        brickAt
+
+    } /* .bricks_by_loc() */
+
+    /// Tells whether every face of the cube shows a single color, whichever
+    /// way the cube as a whole is turned.
+    pub fn is_solved (&self)
+    -> bool
+    {
+        let axmax = self.size - 1;
+
+        let mut faceHues: [Option<Huename>; 6] = [None; 6];
+        for brick in self.bricks.iter()
+        {
+            let faces = hue_faces(&brick.curHue);
+            let isOut = loc_outward(&brick.curLoc, axmax);
+            for face in (0 .. 6).filter(|&face| isOut[face])
+            {
+                match faceHues[face]
+                {
+                    None                                 =>  faceHues[face] = Some(faces[face]),
+                    Some(name) if name != faces[face]    =>  return false,
+                    _                                    =>  ()
+                }
+            }
+        }
+
+        true
+
+    } /* .is_solved() */
+
+    /// Returns the color that each face belongs to, in the order xpos, xneg,
+    /// ypos, yneg, zpos, zneg, which tells how the cube as a whole is turned.
+    /// Odd sized cubes show it by their face centers.  Even sized cubes have
+    /// none, so each face counts as belonging to the color it shows most;
+    /// returns None if that gives no six different colors.
+    pub fn orientation (&self)
+    -> Option<[Huename; 6]>
+    {
+        let axmax = self.size - 1;
+        let mid = axmax / 2;
+        let names = hue_faces(&Brick::new(0, 0, 0).curHue);
+
+        let mut counts = [[0usize; 6]; 6];
+        for brick in self.bricks.iter()
+        {
+            let loc = &brick.curLoc;
+            let faces = hue_faces(&brick.curHue);
+            let isOut = loc_outward(loc, axmax);
+            let coords = [loc.x, loc.y, loc.z];
+            for face in (0 .. 6).filter(|&face| isOut[face])
+            {
+                let isCenter = (0 .. 3).all(|axis| axis == face / 2 || coords[axis] == mid);
+                if self.size % 2 == 0 || isCenter
+                {
+                    counts[face][names.iter().position(|&name| name == faces[face]).unwrap()] += 1;
+                }
+            }
+        }
+
+        let mut colors = names;
+        for face in 0 .. 6
+        {
+            let most = *counts[face].iter().max().unwrap();
+            let mut found = (0 .. 6).filter(|&ind| counts[face][ind] == most);
+            colors[face] = names[found.next().unwrap()];
+            if found.next().is_some()
+            {
+                return None;
+            }
+        }
+
+        if (0 .. 6).any(|face| colors[face + 1 ..].contains(&colors[face]))
+        {
+            return None;
+        }
+
+        Some(colors)
+
+    } /* .orientation() */
+
+    /// Returns the color of the sticker at `row`, `col` of a face, counted
+    /// from the top left as seen from outside the cube.  The side faces have
+    /// Up above them, Up has Back above it and Down has Front above it.
+    /// Returns None if the cube has no such row or column.
+    pub fn sticker_at (&self, face: Face, row: Coord, col: Coord)
+    -> Option<Huename>
+    {
+        if row >= self.size || col >= self.size
+        {
+            return None;
+        }
+
+        let loc = face_loc(face as usize, row, col, self.size - 1);
+        self.bricks.iter().find(|brick| brick.curLoc == loc)
+                          .map(|brick| hue_faces(&brick.curHue)[face as usize])
+
+    } /* .sticker_at() */
+
+    /// Returns the colors of all stickers of a face, row by row, in the
+    /// arrangement of sticker_at().
+    pub fn stickers (&self, face: Face)
+    -> impl Iterator<Item = Huename> + '_
+    {
+        let size  = self.size;
+        let axmax = size - 1;
+        let brickAt = self.bricks_by_loc();
+        let face = face as usize;
+
+        (0 ..= axmax).flat_map(move |row| (0 ..= axmax).map(move |col| (row, col)))
+                     .map(move |(row, col)| {
+                         let loc = face_loc(face, row, col, axmax);
+                         hue_faces(&brickAt[loc_index(&loc, size)].unwrap().curHue)[face]
+                     })
+
+    } /* .stickers() */
+
+    /// Returns the sticker letters of a face, in the order of `hue_faces()`,
+    /// one string per row, in the arrangement of sticker_at().
+    fn face_rows (&self, face: usize)
+    -> Vec<String>
+    {
+        let letters: Vec<char> = self.stickers(Face::ALL[face]).map(|name| name.letter()).collect();
+
+        letters.chunks(self.size as usize).map(|row| row.iter().collect()).collect()
+
+    } /* .face_rows() */
+
+    /// Returns the cube's stickers as one letter each, face by face in the
+    /// order Up, Right, Front, Down, Left, Back that solver programs use,
+    /// and row by row as the net of Display shows them.
+    pub fn facelets (&self)
+    -> String
+    {
+        [2, 0, 4, 3, 1, 5].iter().map(|&face| self.face_rows(face).concat()).collect()
+
+    } /* .facelets() */
+
+    /// Constructs the cube whose stickers read as the given facelet string,
+    /// e.g. "UUUUUUUUURRR…" for a solved 3x3x3 cube.  As in the encoding of
+    /// Kociemba-style solvers, the 6·N² letters U, R, F, D, L, B name the
+    /// face whose home color each sticker shows, face by face in the order
+    /// Up, Right, Front, Down, Left, Back as for facelets().  Whitespace is
+    /// ignored.  Fails if the stickers cannot be shown by any arrangement
+    /// of the cube's bricks; states that no moves reach are accepted, and
+    /// invariants() tells them apart.
+    pub fn from_facelet_string (facelets: &str)
+    -> Result<Cube, CubusError>
+    {
+        let letters: Vec<char> = facelets.chars().filter(|chr| !chr.is_whitespace()).collect();
+        let size = match (1 ..= 10).find(|&size: &usize| 6 * size * size == letters.len())
+        {
+            Some(size)  =>  size as Coord,
+            None        =>  return Err(CubusError::FaceletCount(letters.len()))
+        };
+        let axmax = size - 1;
+
+        let home = Cube::new(size);
+        let homeFaces = hue_faces(&home.bricks[0].curHue);
+
+        // The color on each face of each location, where one shows.
+        let mut shown: Vec<[Option<Huename>; 6]> = vec_of_size(loc_index(&Loc { x: axmax, y: axmax, z: axmax }, size) + 1, [None; 6]);
+        let mut letterInd = 0;
+        for &face in [2, 0, 4, 3, 1, 5].iter()
+        {
+            for row in 0 ..= axmax
+            {
+                for col in 0 ..= axmax
+                {
+                    let chr = letters[letterInd];
+                    let name = match "RLUDFB".find(chr)
+                    {
+                        Some(slot)  =>  homeFaces[slot],
+                        None        =>  return Err(CubusError::InvalidFacelet(chr))
+                    };
+                    shown[loc_index(&face_loc(face, row, col, axmax), size)][face] = Some(name);
+                    letterInd += 1;
+                }
+            }
+        }
+
+        // The orientations in which a brick from `home` would show at `loc`
+        // what the facelets say.
+        let fitsAt = |home: &Loc, loc: &Loc| -> Vec<Hue> {
+            let want = &shown[loc_index(loc, size)];
+            hue_orientations_at(home, loc, axmax).into_iter().filter(|hue| {
+                let faces = hue_faces(hue);
+                (0 .. 6).all(|face| want[face].is_none_or(|name| name == faces[face]))
+            }).collect()
+        };
+
+        // Within each orbit, match bricks to locations by augmenting paths,
+        // so that look-alike bricks find places wherever one exists.
+        let mut cube = home.clone();
+        for orbit in brickvec_orbits(&home.bricks, axmax).iter()
+        {
+            let locs: Vec<Loc> = orbit.iter().map(|&ind| home.bricks[ind].curLoc).collect();
+            let fits: Vec<Vec<bool>> = orbit.iter().map(|&ind| {
+                locs.iter().map(|loc| !fitsAt(&home.bricks[ind].curLoc, loc).is_empty()).collect()
+            }).collect();
+
+            fn augment (brick: usize, fits: &[Vec<bool>], seen: &mut [bool], brickOf: &mut [Option<usize>])
+            -> bool
+            {
+                for loc in 0 .. fits[brick].len()
+                {
+                    if fits[brick][loc] && !seen[loc]
+                    {
+                        seen[loc] = true;
+                        if brickOf[loc].is_none_or(|other| augment(other, fits, seen, brickOf))
+                        {
+                            brickOf[loc] = Some(brick);
+                            return true;
+                        }
+                    }
+                }
+
+                false
+            }
+
+            let mut brickOf: Vec<Option<usize>> = vec![None; locs.len()];
+            for brick in 0 .. orbit.len()
+            {
+                if !augment(brick, &fits, &mut vec![false; locs.len()], &mut brickOf)
+                {
+                    return Err(CubusError::ImpossibleFacelets);
+                }
+            }
+
+            // Stickers leave the turn of face centers open; prefer the least turned.
+            for (loc, brick) in brickOf.iter().enumerate()
+            {
+                let ind = orbit[brick.unwrap()];
+                cube.bricks[ind].curLoc = locs[loc];
+                cube.bricks[ind].curHue =
+                    fitsAt(&home.bricks[ind].curLoc, &locs[loc]).into_iter()
+                        .min_by_key(|hue| (0 .. 6).filter(|&face| hue_faces(hue)[face] != homeFaces[face]).count())
+                        .unwrap();
+            }
+        }
+
+        Ok(cube)
+
+    } /* ::from_facelet_string() */
+
+    /// Reports the milestones of a layer-by-layer solve that this 3x3x3
+    /// cube has reached, judged against the colors of its face centers.
+    /// Returns None for other cube sizes.
+    pub fn solved_report (&self)
+    -> Option<SolvedReport>
+    {
+        if self.size != 3
+        {
+            return None;
+        }
+
+        let brickAt = self.bricks_by_loc();
+
+        // The location on the axis of the given face, at coordinate `along`
+        // on that axis and `p`, `q` on the two others.
+        let locOf = |face: usize, along: Coord, p: Coord, q: Coord| {
+            let mut coords = [p, q, q];
+            let axis = face / 2;
+            coords[axis] = along;
+            coords[(axis + 1) % 3] = p;
+            coords[(axis + 2) % 3] = q;
+            Loc { x: coords[0], y: coords[1], z: coords[2] }
+        };
+        let alongOf = |face: usize| if face % 2 == 0 {2} else {0};
+
+        let faceAt = |loc: &Loc, face: usize| hue_faces(&brickAt[loc_index(loc, 3)].unwrap().curHue)[face];
+
+        let centers: Vec<Huename> = (0 .. 6).map(|face| faceAt(&locOf(face, alongOf(face), 1, 1), face)).collect();
+
+        // A brick is in place if all its visible stickers match their face centers.
+        let isInPlace = |loc: &Loc| {
+            let isOut = loc_outward(loc, 2);
+            (0 .. 6).all(|face| !isOut[face] || faceAt(loc, face) == centers[face])
+        };
+
+        let mut report = SolvedReport {
+            crosses: vec![],
+            bottom:  None,
+            slots:   [false; 4],
+            oll:     false,
+            solved:  self.bricks.iter().all(|brick| isInPlace(&brick.curLoc))
+        };
+
+        let mut bestRank = 0;
+        for face in 0 .. 6
+        {
+            let along = alongOf(face);
+            let hasCross = [(1, 0), (1, 2), (0, 1), (2, 1)].iter()
+                                .all(|&(p, q)| isInPlace(&locOf(face, along, p, q)));
+            if !hasCross
+            {
+                continue;
+            }
+            report.crosses.push(centers[face]);
+
+            // A slot pairs a corner of this face with the middle layer edge above it.
+            let mut slots = [false; 4];
+            for (slot, &(p, q)) in [(0, 0), (0, 2), (2, 0), (2, 2)].iter().enumerate()
+            {
+                slots[slot] = isInPlace(&locOf(face, along, p, q)) && isInPlace(&locOf(face, 1, p, q));
+            }
+
+            let top = face ^ 1;
+            let hasOll = slots.iter().all(|&slot| slot)
+                      && (0 .. 3).all(|p| (0 .. 3).all(|q| faceAt(&locOf(top, alongOf(top), p, q), top) == centers[top]));
+
+            // Prefer the cross with the most progress beyond it.
+            let rank = 1 + slots.iter().filter(|&&slot| slot).count() + hasOll as usize;
+            if rank > bestRank
+            {
+                bestRank = rank;
+                report.bottom = Some(centers[face]);
+                report.slots  = slots;
+                report.oll    = hasOll;
+            }
+        }
+
+        Some(report)
+
+    } /* .solved_report() */
+
+    /// Recognizes the last-layer case of this 3x3x3 cube among ALGORITHMS,
+    /// once its first two layers are complete with the last layer on top:
+    /// an OLL case while that layer is not oriented, a PLL case after.
+    /// Returns the algorithm with the turns of the U layer to do before it,
+    /// after which only a turn of the U layer is left, or None if no
+    /// bundled algorithm fits.
+    pub fn last_layer_case (&self)
+    -> Option<(&'static Algorithm, Vec<Move>)>
+    {
+        let report = self.solved_report()?;
+        if report.solved || !report.slots.iter().all(|&slot| slot)
+        {
+            return None;
+        }
+        let set = if report.oll {"PLL"} else {"OLL"};
+
+        let turnU = Move::new('y', 2);
+        for algorithm in ALGORITHMS.iter().filter(|algorithm| algorithm.set == set)
+        {
+            let moves = algorithm.movevec().ok()?;
+            for turns in 0 .. 4
+            {
+                let mut cube = self.clone();
+                cube.apply_checked_moves(&vec![turnU; turns]);
+                cube.apply_checked_moves(&moves);
+                let isDone = match set
+                {
+                    "OLL"   =>  cube.solved_report().is_some_and(|after| after.oll),
+                    _       =>  (0 .. 4).any(|_| { cube.apply_checked_moves(&[turnU]); cube.is_solved() })
+                };
+                if isDone
+                {
+                    return Some((algorithm, vec![turnU; turns]));
+                }
+            }
+        }
+
+        None
+
+    } /* .last_layer_case() */
+
+    /// Returns the bricks whose stickers have exactly the given colors, in
+    /// any order, e.g. [WT, GN] for the white-green edge.  Each brick's
+    /// curLoc tells where it is, and its curHue which way it faces.  Cubes
+    /// larger than 3x3x3 have look-alike bricks; all of them are returned.
+    pub fn find_piece (&self, colors: &[Huename])
+    -> Vec<&Brick>
+    {
+        let axmax = self.size - 1;
+        let home = Cube::new(self.size);
+
+        let mut wanted = colors.to_vec();
+        wanted.sort_by_key(|&name| name as u8);
+
+        self.bricks.iter().zip(home.bricks.iter()).filter(|(_, homeBrick)| {
+            let isOut = loc_outward(&homeBrick.curLoc, axmax);
+            let faces = hue_faces(&homeBrick.curHue);
+            let mut shown: Vec<Huename> = (0 .. 6).filter(|&face| isOut[face]).map(|face| faces[face]).collect();
+            shown.sort_by_key(|&name| name as u8);
+            shown == wanted
+        }).map(|(brick, _)| brick).collect()
+
+    } /* .find_piece() */
+
+    /// Returns the 3x3x3 cube's total corner twist (mod 3), total edge flip
+    /// (mod 2) and permutation parity (mod 2).  No sequence of moves changes
+    /// these, so two states are connected only if their values agree.
+    /// Larger odd-sized cubes yield those of their reduced() 3x3x3 cube, and
+    /// even-sized cubes only their corner twist, with 0 for the others.
+    /// Returns None for the 1x1x1 cube.
+    pub fn invariants (&self)
+    -> Option<[u8; 3]>
+    {
+        match self.size
+        {
+            1       =>  return None,
+            2 | 3   =>  {},
+            _       =>  return self.reduced().invariants()
+        }
+
+        let axmax = self.size - 1;
+        let home = Cube::new(self.size);
+        let isColor = |name: Huename, colors: [Huename; 2]| name == colors[0] || name == colors[1];
+
+        let mut twist = 0;
+        let mut flip = 0;
+        let mut parity = 0;
+        for outNum in 1 ..= 3
+        {
+            // Corners, edges and centers each permute among themselves.
+            let mut target: Vec<usize> = vec![];
+            let mut homeInds: Vec<usize> = vec![];
+            for (ind, brick) in self.bricks.iter().enumerate()
+            {
+                let homeLoc = &home.bricks[ind].curLoc;
+                if loc_outward(homeLoc, axmax).iter().filter(|&&out| out).count() != outNum
+                {
+                    continue;
+                }
+                homeInds.push(loc_index(homeLoc, self.size));
+                target.push(loc_index(&brick.curLoc, self.size));
+
+                let faces = hue_faces(&brick.curHue);
+                let locOut = loc_outward(&brick.curLoc, axmax);
+                if outNum == 3
+                {
+                    // Which face shows the Y color, counted around the corner.
+                    let face = (0 .. 6).find(|&face| isColor(faces[face], [Huename::WT, Huename::YL])).unwrap();
+                    let mut turn = [2, 0, 1][face / 2];
+                    let negNum = [brick.curLoc.x, brick.curLoc.y, brick.curLoc.z].iter().filter(|&&val| val == 0).count();
+                    if negNum % 2 == 1
+                    {
+                        turn = (3 - turn) % 3;
+                    }
+                    twist = (twist + turn) % 3;
+                }
+                else if outNum == 2
+                {
+                    // An edge shows its Y color, or failing that its Z color,
+                    // on the Y face if it has one, or else on the Z face.
+                    let colors = if homeLoc.y == 0 || homeLoc.y == axmax
+                                 { [Huename::WT, Huename::YL] } else { [Huename::GN, Huename::BL] };
+                    let axis = if locOut[2] || locOut[3] { 1 } else { 2 };
+                    if !isColor(faces[2 * axis], colors) && !isColor(faces[2 * axis + 1], colors)
+                    {
+                        flip ^= 1;
+                    }
+                }
+            }
+
+            // A permutation is odd if its length and cycle count differ in parity.
+            let mut seen = vec![false; target.len()];
+            let mut cycles = 0;
+            for start in 0 .. target.len()
+            {
+                let mut ind = start;
+                if seen[ind]
+                {
+                    continue;
+                }
+                cycles += 1;
+                while !seen[ind]
+                {
+                    seen[ind] = true;
+                    ind = homeInds.iter().position(|&loc| loc == target[ind]).unwrap();
+                }
+            }
+            parity ^= ((target.len() - cycles) % 2) as u8;
+        }
+
+        // A 2x2x2 cube has no edges or centers to make up for an odd
+        // corner permutation.
+        if self.size == 2
+        {
+            return Some([twist, 0, 0]);
+        }
+
+        Some([twist, flip, parity])
+
+    } /* .invariants() */
+
+    /// Returns the cube that the corners form with, for odd sizes, the
+    /// middle edges and middle centers: a 2x2x2 or 3x3x3 cube that every
+    /// move turns either like a move of its own or not at all.
+    pub fn reduced (&self)
+    -> Cube
+    {
+        let axmax = self.size - 1;
+        let small = if self.size % 2 == 0 {2} else {3};
+        let shrink = |val: Coord| -> Option<Coord> {
+            if val == 0 { Some(0) }
+            else if val == axmax { Some(small - 1) }
+            else if 2 * val == axmax { Some(1) }
+            else { None }
+        };
+        let shrinkLoc = |loc: &Loc| -> Option<Loc> {
+            Some(Loc { x: shrink(loc.x)?, y: shrink(loc.y)?, z: shrink(loc.z)? })
+        };
+
+        let home = Cube::new(self.size);
+        let smallHome = Cube::new(small);
+        let mut reduced = smallHome.clone();
+        for (homeBrick, brick) in home.bricks.iter().zip(self.bricks.iter())
+        {
+            if let Some(homeLoc) = shrinkLoc(&homeBrick.curLoc)
+            {
+                let slot = smallHome.bricks.iter().position(|known| known.curLoc == homeLoc).unwrap();
+                reduced.bricks[slot].curLoc = shrinkLoc(&brick.curLoc).unwrap_or(homeLoc);
+                reduced.bricks[slot].curHue = brick.curHue;
+            }
+        }
+
+        reduced
+
+    } /* .reduced() */
+
+    /// Checks that the bricks fill the cube's places, each once, within
+    /// the orbit of its home place and turned so that its outer faces are
+    /// outside, as moves leave them.  Cubes that were deserialized or built
+    /// by hand may fail this.
+    pub fn check_bricks (&self)
+    -> Result<(), CubusError>
+    {
+        if self.size < 1 || 10 < self.size
+        {
+            return Err(CubusError::InvalidBricks);
+        }
+
+        let axmax = self.size - 1;
+        let home = Cube::new(self.size);
+        if self.bricks.len() != home.bricks.len()
+        {
+            return Err(CubusError::InvalidBricks);
+        }
+
+        let mut isTaken = vec![false; (self.size as usize).pow(3)];
+        for (homeBrick, brick) in home.bricks.iter().zip(self.bricks.iter())
+        {
+            let loc = &brick.curLoc;
+            if axmax < loc.x || axmax < loc.y || axmax < loc.z
+            || loc_orbit(loc, axmax) != loc_orbit(&homeBrick.curLoc, axmax)
+            || core::mem::replace(&mut isTaken[loc_index(loc, self.size)], true)
+            || !hue_orientations_at(&homeBrick.curLoc, loc, axmax).iter()
+                                   .any(|hue| hue_faces(hue) == hue_faces(&brick.curHue))
+            {
+                return Err(CubusError::InvalidBricks);
+            }
+        }
+
+        Ok(())
+
+    } /* .check_bricks() */
+
+    /// Checks that moves may turn this cube into the other one, as far as
+    /// check_bricks() and invariants() can tell.
+    pub fn check_reachable (&self, other: &Cube)
+    -> Result<(), CubusError>
+    {
+        if other.size != self.size
+        {
+            return Err(CubusError::SizeMismatch(self.size, other.size));
+        }
+        self.check_bricks()?;
+        other.check_bricks()?;
+
+        if let (Some(from), Some(to)) = (self.invariants(), other.invariants())
+        {
+            let faults = invariant_faults(&from, &to);
+            if !faults.is_empty()
+            {
+                return Err(CubusError::Unreachable(faults));
+            }
+        }
+
+        Ok(())
+
+    } /* .check_reachable() */
+
+    /// Returns the permutation that takes the ordered 3x3x3 cube to this
+    /// one as disjoint cycles of corners, edges and centers, in that order,
+    /// each with the twist or flip that its pieces gather on one round.
+    /// Pieces that stay in place untwisted are left out.  Returns None for
+    /// other cube sizes.
+    pub fn cycles (&self)
+    -> Option<Vec<PieceCycle>>
+    {
+        if self.size != 3
+        {
+            return None;
+        }
+
+        let axmax = self.size - 1;
+        let home = Cube::new(self.size);
+        let outNum = |loc: &Loc| loc_outward(loc, axmax).iter().filter(|&&out| out).count();
+
+        // The home color that a corner or edge shows on its Y face, or else
+        // on its Z face, marks its orientation: how far that color has turned
+        // away from the Y face, or Z face, of the place the piece is at.
+        // Corners count thirds of a counterclockwise turn.
+        let orientation = |ind: usize| {
+            let brick = &self.bricks[ind];
+            let homeLoc = &home.bricks[ind].curLoc;
+            let homeOut = loc_outward(homeLoc, axmax);
+            let homeFaces = hue_faces(&home.bricks[ind].curHue);
+            let refFace = [2, 3, 4, 5, 0, 1].into_iter().find(|&face| homeOut[face]).unwrap();
+            let faces = hue_faces(&brick.curHue);
+            let locOut = loc_outward(&brick.curLoc, axmax);
+            let face = (0 .. 6).find(|&face| locOut[face] && faces[face] == homeFaces[refFace]).unwrap();
+            match outNum(homeLoc)
+            {
+                3   =>  {
+                    let turn = [2, 0, 1][face / 2];
+                    let negNum = [brick.curLoc.x, brick.curLoc.y, brick.curLoc.z].iter().filter(|&&val| val == 0).count();
+                    if negNum % 2 == 1 { (3 - turn) % 3 } else { turn }
+                },
+                2   =>  {
+                    let axis = if locOut[2] || locOut[3] { 1 } else { 2 };
+                    (face / 2 != axis) as u8
+                },
+                _   =>  0
+            }
+        };
+
+        let homeAt: Vec<usize> = home.bricks.iter().map(|brick| loc_index(&brick.curLoc, self.size)).collect();
+        let mut cycles: Vec<PieceCycle> = vec![];
+        for kind in (1 ..= 3).rev()
+        {
+            let mut seen = vec![false; self.bricks.len()];
+            for start in 0 .. self.bricks.len()
+            {
+                if seen[start] || outNum(&home.bricks[start].curLoc) != kind
+                {
+                    continue;
+                }
+
+                // The piece from one place moves to the next place in the cycle.
+                let mut cycle = PieceCycle { pieces: vec![], twist: 0, modulus: [1, 1, 2, 3][kind] };
+                let mut ind = start;
+                while !seen[ind]
+                {
+                    seen[ind] = true;
+                    cycle.pieces.push(piece_name(&home.bricks[ind].curLoc, axmax));
+                    cycle.twist = (cycle.twist + orientation(ind)) % cycle.modulus;
+                    let target = loc_index(&self.bricks[ind].curLoc, self.size);
+                    ind = homeAt.iter().position(|&loc| loc == target).unwrap();
+                }
+                if 1 < cycle.pieces.len() || cycle.twist != 0
+                {
+                    cycles.push(cycle);
+                }
+            }
+        }
+
+        Some(cycles)
+
+    } /* .cycles() */
+
+    /// Returns how often the permutation that takes the ordered cube to this
+    /// one must be applied to restore the ordered cube: the least common
+    /// multiple of its cycle lengths like in cycles(), each multiplied by how
+    /// many rounds its pieces take to face the way they started, spin of
+    /// centers included.  Works for any cube size, and returns usize::MAX for
+    /// orders too large to count.
+    pub fn order (&self)
+    -> usize
+    {
+        let home = Cube::new(self.size);
+        let homeHue = home.bricks[0].curHue;
+        let homeAt: Vec<usize> = home.bricks.iter().map(|brick| loc_index(&brick.curLoc, self.size)).collect();
+        let gcd = |mut lhs: usize, mut rhs: usize| { while rhs != 0 { (lhs, rhs) = (rhs, lhs % rhs); } lhs };
+
+        let mut order: usize = 1;
+        let mut seen = vec![false; self.bricks.len()];
+        for start in 0 .. self.bricks.len()
+        {
+            if seen[start]
+            {
+                continue;
+            }
+
+            // Follow the brick at `start` round its cycle, turning as it goes.
+            let mut length = 0;
+            let mut roundHue = homeHue;
+            let mut ind = start;
+            while !seen[ind]
+            {
+                seen[ind] = true;
+                length += 1;
+                roundHue = hue_composed(&roundHue, &self.bricks[ind].curHue);
+                let target = loc_index(&self.bricks[ind].curLoc, self.size);
+                ind = homeAt.iter().position(|&loc| loc == target).unwrap();
+            }
+            let mut rounds = 1;
+            let mut hue = roundHue;
+            while hue != homeHue
+            {
+                hue = hue_composed(&hue, &roundHue);
+                rounds += 1;
+            }
+
+            let cycle = length * rounds;
+            order = (order / gcd(order, cycle)).saturating_mul(cycle);
+        }
+
+        order
+
+    } /* .order() */
+
+    /// Returns the corner and edge pieces of a 3x3x3 cube as a CubieCube.
+    /// Returns None for other cube sizes, for bricks that fail check_bricks(),
+    /// and when a center is away from its place.  Center spin is dropped.
+    pub fn to_cubies (&self)
+    -> Option<CubieCube>
+    {
+        if self.size != 3
+        {
+            return None;
+        }
+        self.check_bricks().ok()?;
+
+        self.checked_to_cubies()
+
+    } /* .to_cubies() */
+
+    /// Like to_cubies(), for a 3x3x3 cube whose bricks have been checked
+    /// already, such as those that moves make in a search.
+    pub(crate) fn checked_to_cubies (&self)
+    -> Option<CubieCube>
+    {
+        let axmax = self.size - 1;
+        let home = Cube::new(self.size);
+        let homeFaces = hue_faces(&Brick::new(0, 0, 0).curHue);
+        let mut indAt: Vec<usize> = vec_of_size((self.size as usize).pow(3), 0);
+        for (ind, brick) in self.bricks.iter().enumerate()
+        {
+            indAt[loc_index(&brick.curLoc, self.size)] = ind;
+        }
+        if home.bricks.iter().zip(self.bricks.iter())
+               .any(|(homeBrick, brick)| piece_name(&homeBrick.curLoc, axmax).len() == 1 && brick.curLoc != homeBrick.curLoc)
+        {
+            return None;
+        }
+
+        // Which piece is at each place, and which of the place's faces,
+        // counted clockwise from its first, shows the piece's first color.
+        let pieces = |names: &[&str]| -> (Vec<u8>, Vec<u8>) {
+            let mut perm: Vec<u8> = vec![];
+            let mut ori: Vec<u8> = vec![];
+            for name in names.iter()
+            {
+                let ind = indAt[loc_index(&loc_of_faces(name, axmax), self.size)];
+                let brick = &self.bricks[ind];
+                let homeLoc = &home.bricks[ind].curLoc;
+                let piece = names.iter().position(|known| loc_of_faces(known, axmax) == *homeLoc).unwrap();
+                let refColor = homeFaces[face_of_letter(names[piece].chars().next().unwrap())];
+                let faces = hue_faces(&brick.curHue);
+                perm.push(piece as u8);
+                ori.push(name.chars().position(|letter| faces[face_of_letter(letter)] == refColor).unwrap() as u8);
+            }
+            (perm, ori)
+        };
+        let (cp, co) = pieces(&CORNER_NAMES);
+        let (ep, eo) = pieces(&EDGE_NAMES);
+
+        Some(CubieCube {
+            cp: cp.try_into().unwrap(),
+            co: co.try_into().unwrap(),
+            ep: ep.try_into().unwrap(),
+            eo: eo.try_into().unwrap()
+        })
+
+    } /* .checked_to_cubies() */
+
+    /// Returns the 3x3x3 cube whose corners and edges are as the CubieCube
+    /// tells, with its centers in place.
+    pub fn from_cubies (cubies: &CubieCube)
+    -> Result<Cube, CubusError>
+    {
+        let isPerm = |perm: &[u8]| (0 .. perm.len()).all(|piece| perm.contains(&(piece as u8)));
+        if !isPerm(&cubies.cp) || cubies.co.iter().any(|&ori| 3 <= ori)
+        || !isPerm(&cubies.ep) || cubies.eo.iter().any(|&ori| 2 <= ori)
+        {
+            return Err(CubusError::InvalidBricks);
+        }
+
+        let axmax = 2;
+        let home = Cube::new(3);
+        let homeFaces = hue_faces(&Brick::new(0, 0, 0).curHue);
+        let mut cube = home.clone();
+        for (names, perm, ori) in [(&CORNER_NAMES[..], &cubies.cp[..], &cubies.co[..]),
+                                   (&EDGE_NAMES[..],   &cubies.ep[..], &cubies.eo[..])].into_iter()
+        {
+            for (place, name) in names.iter().enumerate()
+            {
+                let pieceName: Vec<char> = names[perm[place] as usize].chars().collect();
+                let homeLoc = loc_of_faces(names[perm[place] as usize], axmax);
+                let loc = loc_of_faces(name, axmax);
+                let ind = home.bricks.iter().position(|brick| brick.curLoc == homeLoc).unwrap();
+                let len = pieceName.len();
+
+                // The place's face `num` shows the piece's color `num - ori`.
+                cube.bricks[ind].curLoc = loc;
+                cube.bricks[ind].curHue = hue_orientations_at(&homeLoc, &loc, axmax).into_iter().find(|hue| {
+                    let faces = hue_faces(hue);
+                    name.chars().enumerate().all(|(num, letter)| {
+                        faces[face_of_letter(letter)] == homeFaces[face_of_letter(pieceName[(num + len - ori[place] as usize) % len])]
+                    })
+                }).unwrap();
+            }
+        }
+
+        Ok(cube)
+
+    } /* ::from_cubies() */
+
+    /// Counts the stickers whose colors differ between this and another
+    /// cube of the same size.
+    pub fn sticker_distance (&self, other: &Cube)
+    -> usize
+    {
+        let axmax = self.size - 1;
+        let otherAt = other.bricks_by_loc();
+
+        let mut distance = 0;
+        for brick in self.bricks.iter()
+        {
+            let otherFaces = hue_faces(&otherAt[loc_index(&brick.curLoc, self.size)].unwrap().curHue);
+            let faces = hue_faces(&brick.curHue);
+            let isOut = loc_outward(&brick.curLoc, axmax);
+            for face in 0 .. 6
+            {
+                if isOut[face] && faces[face] != otherFaces[face]
+                {
+                    distance += 1;
+                }
+            }
+        }
+
+        distance
+
+    } /* .sticker_distance() */
+
+    /// Returns the number of bytes in the packed encoding of a cube of the given size.
+    pub fn packed_len (size: Coord)
+    -> Result<usize, CubusError>
+    {
+        let home = Cube::try_new(size)?;
+        let axmax = size - 1;
+
+        // The number of distinct encodings is the product of all digit radices.
+        let mut count: Vec<u8> = vec![1];
+        for orbit in brickvec_orbits(&home.bricks, axmax).iter()
+        {
+            let loc = &home.bricks[orbit[0]].curLoc;
+            let hueNum = hue_orientations_at(loc, loc, axmax).len() as u32;
+            for ind in 0 .. orbit.len()
+            {
+                bignum_mul_add(&mut count, (orbit.len() - ind) as u32, 0);
+                bignum_mul_add(&mut count, hueNum, 0);
+            }
+        }
+
+        // Bytes needed for the largest encoding, count - 1.
+        let mut ind = 0;
+        while count[ind] == 0
+        {
+            count[ind] = 0xFF;
+            ind += 1;
+        }
+        count[ind] -= 1;
+        while count.len() > 1 && count[count.len() - 1] == 0
+        {
+            count.pop();
+        }
+
+        Ok(count.len())
+
+    } /* ::packed_len() */
+
+    /// Returns a compact key for the cube's state, one number per brick that
+    /// holds its location index and its X and Y face colors.  Keys of cubes
+    /// of one size are equal exactly when the cubes are, and cost far less
+    /// to compute than to_packed(), so they suit visited-state sets and
+    /// lookup tables.
+    pub fn state_key (&self)
+    -> Vec<u16>
+    {
+        self.bricks.iter().map(|brick| {
+            let hue = &brick.curHue;
+            ((loc_index(&brick.curLoc, self.size) as u16) << 6) | ((hue.xpos as u16) << 3) | (hue.ypos as u16)
+        }).collect()
+
+    } /* .state_key() */
+
+    /// Encodes the cube's state in `packed_len()` bytes.  Within each orbit,
+    /// the bricks' locations are ranked as a permutation and each brick's
+    /// orientation is ranked among those possible at its location; all ranks
+    /// together form one mixed-radix number.
+    pub fn to_packed (&self)
+    -> Vec<u8>
+    {
+        let axmax = self.size - 1;
+        let home = Cube::new(self.size);
+
+        let mut digits: Vec<(u32, u32)> = vec![];
+        for orbit in brickvec_orbits(&home.bricks, axmax).iter()
+        {
+            let mut free: Vec<Loc> = orbit.iter().map(|&ind| home.bricks[ind].curLoc).collect();
+            for &ind in orbit.iter()
+            {
+                let pos = free.iter().position(|loc| *loc == self.bricks[ind].curLoc).unwrap();
+                digits.push((free.len() as u32, pos as u32));
+                free.remove(pos);
+            }
+
+            for &ind in orbit.iter()
+            {
+                let hues = hue_orientations_at(&home.bricks[ind].curLoc, &self.bricks[ind].curLoc, axmax);
+                let pos = hues.iter().position(|hue| *hue == self.bricks[ind].curHue).unwrap();
+                digits.push((hues.len() as u32, pos as u32));
+            }
+        }
+
+        let mut packed: Vec<u8> = vec![];
+        for &(radix, digit) in digits.iter().rev()
+        {
+            bignum_mul_add(&mut packed, radix, digit);
+        }
+        packed.resize(Cube::packed_len(self.size).unwrap(), 0);
+
+        packed
+
+    } /* .to_packed() */
+
+    /// Decodes a cube of the given size from its packed encoding.
+    /// Returns None if the bytes are not a valid encoding.
+    pub fn from_packed (size: Coord, packed: &[u8])
+    -> Option<Cube>
+    {
+        if Cube::packed_len(size).ok() != Some(packed.len())
+        {
+            return None;
+        }
+
+        let axmax = size - 1;
+        let home = Cube::new(size);
+        let mut cube = home.clone();
+
+        let mut num = packed.to_vec();
+        for orbit in brickvec_orbits(&home.bricks, axmax).iter()
+        {
+            let mut free: Vec<Loc> = orbit.iter().map(|&ind| home.bricks[ind].curLoc).collect();
+            for &ind in orbit.iter()
+            {
+                let pos = bignum_div_rem(&mut num, free.len() as u32);
+                cube.bricks[ind].curLoc = free.remove(pos as usize);
+            }
+
+            for &ind in orbit.iter()
+            {
+                let hues = hue_orientations_at(&home.bricks[ind].curLoc, &cube.bricks[ind].curLoc, axmax);
+                let pos = bignum_div_rem(&mut num, hues.len() as u32);
+                cube.bricks[ind].curHue = hues[pos as usize];
+            }
+        }
+
+        // Anything left over means the number exceeded the encoding's range.
+        if num.iter().any(|&byte| byte != 0)
+        {
+            return None;
+        }
+
+        Some(cube)
+
+    } /* ::from_packed() */
+
+    /// Encodes the cube, its bricks and locks as a Cube message of
+    /// proto/cubus.proto.
+    #[cfg(feature = "protobuf")]
+    pub fn to_protobuf (&self)
+    -> Vec<u8>
+    {
+        prost::Message::encode_to_vec(&CubeMessage {
+            size: self.size as u32,
+            bricks: self.bricks.iter().map(brick_message).collect(),
+            locks: self.locks.iter().map(|&(axis, axval)| layer_message(axis, axval)).collect()
+        })
+
+    } /* .to_protobuf() */
+
+    /// Decodes a Cube message of proto/cubus.proto.  Fails with
+    /// InvalidProtobuf on malformed bytes, and as deserialization does on
+    /// a cube that no moves can reach, see cube_of_fields().
+    #[cfg(feature = "protobuf")]
+    pub fn from_protobuf (bytes: &[u8])
+    -> Result<Cube, CubusError>
+    {
+        let message: CubeMessage = prost::Message::decode(bytes)
+            .map_err(|error| CubusError::InvalidProtobuf(error.to_string()))?;
+        let size = Coord::try_from(message.size)
+            .map_err(|_| CubusError::InvalidProtobuf(format!("no size {}", message.size)))?;
+        let bricks = message.bricks.iter().map(brick_of_message).collect::<Result<Vec<Brick>, CubusError>>()?;
+        let locks = message.locks.iter().map(layer_of_message).collect::<Result<Vec<(Axis, Coord)>, CubusError>>()?;
+
+        cube_of_fields(size, bricks, locks)
+
+    } /* ::from_protobuf() */
+
+    /// Returns a random state of a cube of up to 3x3x3 in which each state
+    /// that moves can reach is equally likely.  The bricks are placed and
+    /// turned at random, and a last corner, edge and center is set so that
+    /// the state is reachable; the whole cube is then turned at random.
+    /// Larger cubes, whose reachable states this crate does not tell, fail
+    /// with NoRandomState; random_movevec() scrambles those.
+    pub fn random (size: Coord, rng: &mut Rng)
+    -> Result<Cube, CubusError>
+    {
+        let home = Cube::try_new(size)?;
+        if 3 < size
+        {
+            return Err(CubusError::NoRandomState(size));
+        }
+
+        let axmax = size - 1;
+        let mut cube = home.clone();
+        let outNum = |loc: &Loc| loc_outward(loc, axmax).iter().filter(|&&out| out).count();
+        let mut corners: Vec<usize> = vec![];
+        let mut edges: Vec<usize> = vec![];
+        let mut centers: Vec<usize> = vec![];
+        for orbit in brickvec_orbits(&home.bricks, axmax).iter()
+        {
+            match outNum(&home.bricks[orbit[0]].curLoc)
+            {
+                0       =>  continue,
+                1       =>  centers.extend_from_slice(orbit),
+                2       =>  edges.extend_from_slice(orbit),
+                _       =>  corners.extend_from_slice(orbit)
+            }
+
+            // Centers stay for now; the final turn of the cube moves them.
+            let mut free: Vec<Loc> = orbit.iter().map(|&ind| home.bricks[ind].curLoc).collect();
+            for &ind in orbit.iter()
+            {
+                if 1 < outNum(&home.bricks[ind].curLoc)
+                {
+                    cube.bricks[ind].curLoc = free.swap_remove(rng.below(free.len()));
+                }
+            }
+        }
+
+        let randomize = |cube: &mut Cube, ind: usize, rng: &mut Rng| {
+            let hues = hue_orientations_at(&home.bricks[ind].curLoc, &cube.bricks[ind].curLoc, axmax);
+            cube.bricks[ind].curHue = hues[rng.below(hues.len())];
+        };
+        for ind in corners.iter().chain(edges.iter()).chain(centers.iter())
+        {
+            randomize(&mut cube, *ind, rng);
+        }
+
+        // Corners and edges of a 3x3x3 cube permute with equal parity.
+        if size == 3 && cube.invariants().is_some_and(|invs| invs[2] != 0)
+        {
+            let (lhs, rhs) = (edges[0], edges[1]);
+            let loc = cube.bricks[lhs].curLoc;
+            cube.bricks[lhs].curLoc = cube.bricks[rhs].curLoc;
+            cube.bricks[rhs].curLoc = loc;
+            randomize(&mut cube, lhs, rng);
+            randomize(&mut cube, rhs, rng);
+        }
+
+        // The last corner and edge undo the twist and flip of the others.
+        for (group, inv) in [(&corners, 0), (&edges, 1)].into_iter()
+        {
+            let Some(&ind) = group.last() else { continue };
+            for hue in hue_orientations_at(&home.bricks[ind].curLoc, &cube.bricks[ind].curLoc, axmax).into_iter()
+            {
+                cube.bricks[ind].curHue = hue;
+                if cube.invariants().is_none_or(|invs| invs[inv] == 0)
+                {
+                    break;
+                }
+            }
+        }
+
+        // Every quarter turn of a face turns its center by a quarter and
+        // permutes the corners oddly, so the centers have turned an odd
+        // number of quarters in all just when the corners permute oddly.
+        if let Some(&last) = centers.last()
+        {
+            let isOddTurn = |brick: &Brick| {
+                let axis = (loc_outward(&brick.curLoc, axmax).iter().position(|&out| out).unwrap() / 2 + 1) % 3;
+                let faces = hue_faces(&brick.curHue);
+                let homeFaces = hue_faces(&Brick::new(0, 0, 0).curHue);
+                faces[2 * axis] != homeFaces[2 * axis] && faces[2 * axis] != homeFaces[2 * axis + 1]
+            };
+            let mut seen = vec![false; corners.len()];
+            let mut isOddPerm = false;
+            for start in 0 .. corners.len()
+            {
+                let mut pos = start;
+                while !seen[pos]
+                {
+                    seen[pos] = true;
+                    pos = corners.iter().position(|&ind| home.bricks[ind].curLoc == cube.bricks[corners[pos]].curLoc).unwrap();
+                    isOddPerm ^= !seen[pos];
+                }
+            }
+            let isOddSum = centers.iter().filter(|&&ind| isOddTurn(&cube.bricks[ind])).count() % 2 == 1;
+            if isOddSum != isOddPerm
+            {
+                let hues: Vec<Hue> = hue_orientations_at(&home.bricks[last].curLoc, &cube.bricks[last].curLoc, axmax)
+                                         .into_iter()
+                                         .filter(|hue| isOddTurn(&Brick { curLoc: cube.bricks[last].curLoc, curHue: *hue }) != isOddTurn(&cube.bricks[last]))
+                                         .collect();
+                cube.bricks[last].curHue = hues[rng.below(hues.len())];
+            }
+        }
+
+        // One of the 24 whole-cube turns, each reached by turning all layers.
+        let mut turns: Vec<(Cube, Vec<Move>)> = vec![(home.clone(), vec![])];
+        let mut ind = 0;
+        while ind < turns.len()
+        {
+            for axdir in ['X', 'Y'].into_iter()
+            {
+                let mut moves = turns[ind].1.clone();
+                moves.extend((0 ..= axmax).map(|axval| Move::new(axdir, axval)));
+                let turned = home.copy_with_checked_moves(&moves);
+                if !turns.iter().any(|(known, _)| *known == turned)
+                {
+                    turns.push((turned, moves));
+                }
+            }
+            ind += 1;
+        }
+        cube.apply_checked_moves(&turns[rng.below(turns.len())].1);
+
+        Ok(cube)
+
+    } /* ::random() */
+
+}   /* impl Cube */
+
+
+/// Composition of cube states, see Cube::compose().
+impl Mul for &Cube
+{
+    type Output = Result<Cube, CubusError>;
+
+    fn mul (self, other: &Cube)
+    -> Result<Cube, CubusError>
+    {
+        self.compose(other)
+    }
+
+}   /* impl Mul for &Cube */
+
+impl Mul for Cube
+{
+    type Output = Result<Cube, CubusError>;
+
+    fn mul (self, other: Cube)
+    -> Result<Cube, CubusError>
+    {
+        self.compose(&other)
+    }
+
+}   /* impl Mul for Cube */
+
+/// Writes the cube as an uncolored net of its faces, one letter per sticker:
+/// Up on top, then Left, Front, Right and Back side by side, then Down.
+/// Each face appears as seen from outside, with Front as the reference.
+impl fmt::Display for Cube
+{
+    #[allow(clippy::needless_range_loop, reason = "each row joins the rows of four faces")]
+    fn fmt (&self, f: &mut fmt::Formatter)
+    -> fmt::Result
+    {
+        let faceRows: Vec<Vec<String>> = (0 .. 6).map(|face| self.face_rows(face)).collect();
+        let indent = " ".repeat(self.size as usize + 1);
+
+        for row in faceRows[2].iter()
+        {
+            writeln!(f, "{}{}", indent, row)?;
+        }
+        for row in 0 .. self.size as usize
+        {
+            let sides: Vec<&str> = [1, 4, 0, 5].iter().map(|&face| faceRows[face][row].as_str()).collect();
+            writeln!(f, "{}", sides.join(" "))?;
+        }
+        for row in faceRows[3].iter()
+        {
+            writeln!(f, "{}{}", indent, row)?;
+        }
+
+        Ok(())
+    }
+
+}   /* impl fmt::Display for Cube */
+
+
+/// Milestones of a layer-by-layer solve of a 3x3x3 cube.
+pub struct SolvedReport
+{
+    pub crosses: Vec<Huename>,      // Colors whose cross is complete.
+    pub bottom:  Option<Huename>,   // The cross with the most progress beyond it.
+    pub slots:   [bool; 4],         // First-two-layers slots complete above it.
+    pub oll:     bool,              // Last layer oriented above complete slots.
+    pub solved:  bool
+
+}   /* SolvedReport */
+
+impl SolvedReport
+{
+    /// Returns the report as a single line of text.
+    pub fn as_string (&self)
+    -> String
+    {
+        let crosses: Vec<String> = self.crosses.iter().map(|name| name.letter().to_string()).collect();
+        let yesNo = |flag: bool| if flag {"yes"} else {"no"};
+
+        let mut string = format!("Cross: {}", if crosses.is_empty() {"-".to_string()} else {crosses.join(" ")});
+        if let Some(bottom) = self.bottom
+        {
+            string.push_str(&format!("  F2L on {}: {}/4  OLL: {}",
+                                     bottom.letter(),
+                                     self.slots.iter().filter(|&&slot| slot).count(),
+                                     yesNo(self.oll)));
+        }
+        string.push_str(&format!("  Solved: {}", yesNo(self.solved)));
+
+        string
+
+    }   /* .as_string() */
+
+    /// Returns how far the solve got, as an index into SOLVE_STAGE_NAMES.
+    pub fn stage (&self)
+    -> usize
+    {
+        if self.solved                  { 7 }
+        else if self.oll                { 6 }
+        else if self.bottom.is_some()   { 1 + self.slots.iter().filter(|&&slot| slot).count() }
+        else                            { 0 }
+
+    }   /* .stage() */
+
+}   /* impl SolvedReport */
+
+
+/// Pieces that a permutation of the cube moves round in a cycle.
+pub struct PieceCycle
+{
+    pub pieces:  Vec<String>,       // Home places of the pieces, e.g. UFR, in cycle order.
+    pub twist:   u8,                // Counterclockwise twist or flip the pieces gather on one round.
+    pub modulus: u8                 // 3 for corners, 2 for edges, 1 for centers.
+
+}   /* PieceCycle */
+
+impl PieceCycle
+{
+    /// Returns the cycle as a single line of text, e.g. `(UFR UBR UBL)`,
+    /// followed by `+` or `-` for a clockwise or counterclockwise twist of
+    /// corners, or by `'` for flipped edges.
+    pub fn as_string (&self)
+    -> String
+    {
+        let suffix = match (self.modulus, self.twist)
+        {
+            (3, 1)  =>  "-",
+            (3, 2)  =>  "+",
+            (2, 1)  =>  "'",
+            _       =>  ""
+        };
+
+        format!("({}){}", self.pieces.join(" "), suffix)
+
+    }   /* .as_string() */
+
+}   /* impl PieceCycle */
+
+
+/// Returns the cycles of 3x3x3 corners and edges written as PieceCycle's
+/// as_string() writes them, e.g. `(UFR UBL DFR) (UF UB)'`.  The faces of a
+/// piece may be named in any order.
+pub fn cyclevec_of_string (string: &str)
+-> Result<Vec<PieceCycle>, CubusError>
+{
+    let home = Cube::new(3);
+    let mut cycles: Vec<PieceCycle> = vec![];
+    let mut rest = string.trim_start();
+    while !rest.is_empty()
+    {
+        if !rest.starts_with('(')
+        {
+            return Err(CubusError::UnbalancedParens);
+        }
+        let Some(end) = rest.find(')') else { return Err(CubusError::UnbalancedParens) };
+        let inner = &rest[1 .. end];
+        if inner.contains('(')
+        {
+            return Err(CubusError::UnbalancedParens);
+        }
+        rest = &rest[end + 1 ..];
+        let suffix = rest.chars().next().filter(|chr| "+-'".contains(*chr));
+        if let Some(chr) = suffix
+        {
+            rest = &rest[chr.len_utf8() ..];
+        }
+        rest = rest.trim_start();
+
+        let text = format!("({}){}", inner, suffix.map(String::from).unwrap_or_default());
+        let mut pieces: Vec<String> = vec![];
+        for piece in inner.split_whitespace()
+        {
+            let mut letters: Vec<char> = piece.to_ascii_uppercase().chars().collect();
+            letters.sort_by_key(|&chr| "UDFBRL".find(chr).unwrap_or(6));
+            let name: String = letters.into_iter().collect();
+            if name.len() < 2 || !home.bricks.iter().any(|brick| piece_name(&brick.curLoc, 2) == name)
+            {
+                return Err(CubusError::UnknownPiece(piece.to_string()));
+            }
+            pieces.push(name);
+        }
+
+        let modulus = pieces.first().map_or(0, |name| name.len() as u8);
+        let twist = match (modulus, suffix)
+        {
+            (_, None)           =>  0,
+            (3, Some('-'))      =>  1,
+            (3, Some('+'))      =>  2,
+            (2, Some('\''))     =>  1,
+            _                   =>  return Err(CubusError::InvalidCycle(text))
+        };
+        if pieces.iter().any(|name| name.len() as u8 != modulus)
+        || pieces.iter().enumerate().any(|(ind, name)| pieces[.. ind].contains(name))
+        || cycles.iter().any(|cycle| cycle.pieces.iter().any(|name| pieces.contains(name)))
+        {
+            return Err(CubusError::InvalidCycle(text));
+        }
+
+        cycles.push(PieceCycle { pieces, twist, modulus });
+    }
+
+    Ok(cycles)
+
+}   /* cyclevec_of_string() */
+
+
+/// Names the piece at the given place by the faces that it shows, from
+/// among U and D, F and B, R and L in that order, e.g. UFR.  Inner bricks
+/// get the empty name.
+fn piece_name (loc: &Loc, axmax: Coord)
+-> String
+{
+    let isOut = loc_outward(loc, axmax);
+
+    [(2, 'U'), (3, 'D'), (4, 'F'), (5, 'B'), (0, 'R'), (1, 'L')].iter()
+        .filter(|&&(face, _)| isOut[face])
+        .map(|&(_, letter)| letter)
+        .collect()
+
+}   /* piece_name() */
+
+
+/// Returns the name of a piece as piece_name() writes it, given by the
+/// faces of its home place in any order and case, e.g. rfu for UFR.  Fails
+/// if no piece of a cube of the given size has that name.
+fn piece_name_checked (piece: &str, size: Coord)
+-> Result<String, CubusError>
+{
+    let mut letters: Vec<char> = piece.to_ascii_uppercase().chars().collect();
+    letters.sort_by_key(|&chr| "UDFBRL".find(chr).unwrap_or(6));
+    let name: String = letters.into_iter().collect();
+    if name.is_empty() || !Cube::new(size).bricks.iter().any(|brick| piece_name(&brick.curLoc, size - 1) == name)
+    {
+        return Err(CubusError::UnknownPiece(piece.to_string()));
+    }
+
+    Ok(name)
+
+}   /* piece_name_checked() */
+
+
+/// Returns the index in the order of `hue_faces()` of the face with the
+/// given letter, one of R, L, U, D, F, B.
+fn face_of_letter (letter: char)
+-> usize
+{
+    "RLUDFB".find(letter).unwrap()
+
+}   /* face_of_letter() */
+
+
+/// Returns the place of the piece that shows the faces with the given
+/// letters, e.g. UFR, the reverse of piece_name().
+fn loc_of_faces (name: &str, axmax: Coord)
+-> Loc
+{
+    let mid = axmax / 2;
+    let mut loc = Loc { x: mid, y: mid, z: mid };
+    for letter in name.chars()
+    {
+        match letter
+        {
+            'R' =>  loc.x = axmax,
+            'L' =>  loc.x = 0,
+            'U' =>  loc.y = axmax,
+            'D' =>  loc.y = 0,
+            'F' =>  loc.z = axmax,
+            _   =>  loc.z = 0
+        }
+    }
+
+    loc
+
+}   /* loc_of_faces() */
+
+
+/// The places of the 3x3x3 cube's corners, by their faces listed clockwise
+/// from the Up or Down face, in the order that CubieCube numbers them.
+pub const CORNER_NAMES: [&str; 8] = ["URF", "UFL", "ULB", "UBR", "DFR", "DLF", "DBL", "DRB"];
+
+/// The places of the 3x3x3 cube's edges, by their faces listed from the
+/// Up or Down face, or else the Front or Back face, in the order that
+/// CubieCube numbers them.
+pub const EDGE_NAMES: [&str; 12] = ["UR", "UF", "UL", "UB", "DR", "DF", "DL", "DB", "FR", "FL", "BL", "BR"];
+
+
+/// The corners and edges of a 3x3x3 cube with its centers in place, as
+/// the permutation and orientation of pieces that most solving methods
+/// work with.  `cp[place]` is the piece at a place of CORNER_NAMES, and
+/// `co[place]` counts the place's faces clockwise from its first to the
+/// one that shows the piece's first color; `ep` and `eo` do the same for
+/// EDGE_NAMES.  See Cube::to_cubies() and Cube::from_cubies().
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub struct CubieCube
+{
+    pub cp: [u8; 8],
+    pub co: [u8; 8],
+    pub ep: [u8; 12],
+    pub eo: [u8; 12]
+
+}   /* CubieCube */
+
+impl CubieCube
+{
+    /// CubieCube constructor, for the ordered cube.
+    pub fn new ()
+    -> CubieCube
+    {
+        CubieCube {
+            cp: [0, 1, 2, 3, 4, 5, 6, 7],
+            co: [0; 8],
+            ep: [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+            eo: [0; 12]
+        }
+
+    } /* ::new() */
+
+    /// Returns the CubieCube of a single face move of the ordered cube.
+    /// Moves of the middle layers and rotations, which move centers, are
+    /// refused.
+    pub fn of_move (mov: &Move)
+    -> Result<CubieCube, CubusError>
+    {
+        move_check(mov.axdir, mov.axval, 2)?;
+        if mov.axval == 1 || mov.is_rotation()
+        {
+            return Err(CubusError::CenterMove(mov.axdir, mov.axval));
+        }
+
+        // Both face layers of each axis, turned either way, worked out
+        // once where the standard library can keep them.
+        let table = || -> Vec<CubieCube> {
+            "XYZxyz".chars().flat_map(|axdir| [0, 2].into_iter().map(move |axval| {
+                Cube::new(3).copy_with_checked_moves(&[Move::new(axdir, axval)]).to_cubies().unwrap()
+            })).collect()
+        };
+        #[cfg(feature = "std")]
+        let moves = {
+            static MOVES: OnceLock<Vec<CubieCube>> = OnceLock::new();
+            MOVES.get_or_init(table)
+        };
+        #[cfg(not(feature = "std"))]
+        let moves = &table();
+
+        Ok(moves[2 * "XYZxyz".find(mov.axdir).unwrap() + mov.axval as usize / 2])
+
+    } /* ::of_move() */
+
+    /// Returns the state reached by playing the moves that produced this
+    /// CubieCube followed by those that produced `other`, like
+    /// Cube::compose().
+    pub fn compose (&self, other: &CubieCube)
+    -> CubieCube
+    {
+        let mut cubies = *self;
+        for place in 0 .. 8
+        {
+            let from = other.cp[place] as usize;
+            cubies.cp[place] = self.cp[from];
+            cubies.co[place] = (self.co[from] + other.co[place]) % 3;
+        }
+        for place in 0 .. 12
+        {
+            let from = other.ep[place] as usize;
+            cubies.ep[place] = self.ep[from];
+            cubies.eo[place] = (self.eo[from] + other.eo[place]) % 2;
+        }
+
+        cubies
+
+    } /* .compose() */
+
+    /// Returns the CubieCube that composes with this one to the ordered cube.
+    pub fn inverse (&self)
+    -> CubieCube
+    {
+        let mut cubies = *self;
+        for place in 0 .. 8
+        {
+            let piece = self.cp[place] as usize;
+            cubies.cp[piece] = place as u8;
+            cubies.co[piece] = (3 - self.co[place]) % 3;
+        }
+        for place in 0 .. 12
+        {
+            let piece = self.ep[place] as usize;
+            cubies.ep[piece] = place as u8;
+            cubies.eo[piece] = self.eo[place];
+        }
+
+        cubies
+
+    } /* .inverse() */
+
+    /// Applies the given face moves to this CubieCube. All the moves are
+    /// checked first, so on error the CubieCube is left as it was.
+    pub fn apply_moves (&mut self, moves: &[Move])
+    -> Result<(), CubusError>
+    {
+        let turns = moves.iter().map(CubieCube::of_move).collect::<Result<Vec<CubieCube>, CubusError>>()?;
+        for turn in turns.iter()
+        {
+            *self = self.compose(turn);
+        }
+
+        Ok(())
+
+    } /* .apply_moves() */
+
+    /// Tells whether every corner and edge is in place and unturned.
+    pub fn is_solved (&self)
+    -> bool
+    {
+        *self == CubieCube::new()
+
+    } /* .is_solved() */
+
+}   /* impl CubieCube */
+
+impl Default for CubieCube
+{
+    fn default ()
+    -> CubieCube
+    {
+        CubieCube::new()
+    }
+
+}   /* impl Default for CubieCube */
+
+
+/// Describes how the invariants of two 3x3x3 states differ, each with the
+/// smallest change to the second state's stickers that would settle it.
+pub fn invariant_faults (from: &[u8; 3], to: &[u8; 3])
+-> Vec<String>
+{
+    let mut faults: Vec<String> = vec![];
+
+    let twist = (to[0] + 3 - from[0]) % 3;
+    if twist != 0
+    {
+        faults.push(format!("The corners are twisted by {} third{} of a turn in total; \
+                             twisting one corner in place would fix that.",
+                            twist, if twist != 1 {"s"} else {""}));
+    }
+    if to[1] != from[1]
+    {
+        faults.push("One edge is flipped; flipping one edge in place would fix that.".to_string());
+    }
+    if to[2] != from[2]
+    {
+        faults.push("Two bricks are swapped; swapping two edges or two corners \
+                     would fix that.".to_string());
+    }
+
+    faults
+
+}   /* invariant_faults() */
+
+
+/*  ––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––  *
+ *
+ *      Move Finding
+ */
+
+/// A memory structure storing which layers have been moved.
+struct Layers
+{
+    xpos: Vec<bool>,
+    xneg: Vec<bool>,
+    ypos: Vec<bool>,
+    yneg: Vec<bool>,
+    zpos: Vec<bool>,
+    zneg: Vec<bool>
+
+}   /* Layers */
+
+// Vec allocation helper.
+fn vec_of_size<T: Clone> (size: usize, value: T)
+-> Vec<T>
+{
+    let mut vec: Vec<T> = vec![];
+    vec.resize(size, value);
+
+    vec
+
+}   /* vec_of_size<T>() */
+
+impl Layers
+{
+    /// Layers constructor.
+    fn new (size: Coord)
+    -> Layers
+    {
+        let size: usize = size as usize;
+        Layers {
+            xpos: vec_of_size(size, false),
+            xneg: vec_of_size(size, false),
+            ypos: vec_of_size(size, false),
+            yneg: vec_of_size(size, false),
+            zpos: vec_of_size(size, false),
+            zneg: vec_of_size(size, false)
+        }
+
+    } /* ::new() */
+
+    /// Sets the flag for the layer identified by axis and coordinate value.
+    fn set_flag (&mut self, axdir: Axis, axval: Coord)
+    {
+        let axflags = match axdir
+        {
+            'X' =>  &mut self.xpos,
+            'x' =>  &mut self.xneg,
+            'Y' =>  &mut self.ypos,
+            'y' =>  &mut self.yneg,
+            'Z' =>  &mut self.zpos,
+            'z' =>  &mut self.zneg,
+            _   =>  panic!()
+        };
+
+        axflags[axval as usize] = true;
+
+    } /* .set_flag() */
+
+    /// Tests the flag for the layer identified by axis and coordinate value.
+    fn has_flag (&self, axdir: Axis, axval: Coord)
+    -> bool
+    {
+        let axflags = match axdir
+        {
+            'X' =>  &self.xpos,
+            'x' =>  &self.xneg,
+            'Y' =>  &self.ypos,
+            'y' =>  &self.yneg,
+            'Z' =>  &self.zpos,
+            'z' =>  &self.zneg,
+            _   =>  panic!()
+        };
+
+        axflags[axval as usize]
+
+    } /* .has_flag() */
+
+}   /* impl Layers */
+
+
+fn brickvec_eq (lhs: &[Brick], rhs: &[Brick])
+-> bool
+{
+    let len = lhs.len();
+    if rhs.len() != len
+    {
+        return false;
+    }
+
+    for ind in 0 .. len
+    {
+        if lhs[ind] != rhs[ind]
+        {
+            return false;
+        }
+    }
+
+    true
+
+}   /* brickvec_eq() */
+
+
+/// An experimental move sequence of find_moves_pruned(), as its last move
+/// and the index of the trail that it continues among all trails, so that
+/// a trail costs one move instead of a copy of all of its moves.
+struct Trail
+{
+    parent: usize,                  // Index of the trail one move shorter; 0, the empty trail, for itself.
+    mov:    Move
+
+}   /* Trail */
+
+impl Trail
+{
+    /// Stores the moves of the trail of the given index among `trails` in
+    /// `steps`, whose allocation is reused, in reverse, so the most recent
+    /// moves are easily accessible.
+    fn moves_into (trails: &[Trail], mut ind: usize, steps: &mut Vec<Move>)
+    {
+        steps.clear();
+        while ind != 0
+        {
+            steps.push(trails[ind].mov);
+            ind = trails[ind].parent;
+        }
+
+    }   /* ::moves_into() */
+
+}   /* impl Trail */
+
+
+/// Returns the given axis with its rotational sense inverted.
+fn invert_axis (axdir: Axis)
+-> Axis
+{
+    // Flips uppercase <-> lowercase.
+    ((axdir as u8) ^ 0x20) as Axis
+
+}   /* invert_axis() */
+
+
+/// A caller-supplied pruning rule for the move search.  It receives the
+/// moves made so far, most recent first, and a candidate next move, and
+/// returns true if the candidate should not be explored.
+pub type Pruner<'a> = &'a dyn Fn (&[Move], &Move) -> bool;
+
+
+/// Lets another thread stop a running search.  Clones share one flag.
+/// Searches look at the flag once every `interval` exploratory moves and
+/// return what they have found so far once it is set.
+#[derive(Clone)]
+pub struct CancelToken
+{
+    flag:     Arc<AtomicBool>,
+    interval: u64
+
+}   /* CancelToken */
+
+impl CancelToken
+{
+    /// CancelToken constructor.
+    pub fn new (interval: u64)
+    -> CancelToken
+    {
+        CancelToken {
+            flag: Arc::new(AtomicBool::new(false)),
+            interval: interval.max(1)
+        }
+
+    } /* ::new() */
+
+    /// Asks searches that use this token to stop.
+    pub fn cancel (&self)
+    {
+        self.flag.store(true, Ordering::Relaxed);
+
+    } /* .cancel() */
+
+    /// Tells whether cancel() has been called.
+    pub fn is_cancelled (&self)
+    -> bool
+    {
+        self.flag.load(Ordering::Relaxed)
+
+    } /* .is_cancelled() */
+
+    /// Tells whether a search that has made `moveNum` exploratory moves
+    /// should stop now.
+    pub fn should_stop (&self, moveNum: u64)
+    -> bool
+    {
+        moveNum % self.interval == 0 && self.is_cancelled()
+
+    } /* .should_stop() */
+
+}   /* impl CancelToken */
+
+
+/// Returns a CancelToken that a background thread cancels once the given
+/// time has passed.
+#[cfg(feature = "std")]
+pub fn cancel_after (limit: Duration)
+-> CancelToken
+{
+    let token = CancelToken::new(1000);
+    let timer = token.clone();
+    thread::spawn(move || {
+        thread::sleep(limit);
+        timer.cancel();
+    });
+
+    token
+
+}   /* cancel_after() */
+
+
+/// Finds all move sequences, no longer than maxLen, that transform the
+/// srcCube into the dstCube.  If the optional CancelToken gets cancelled,
+/// returns the sequences found until then.
+pub fn find_moves (maxLen: usize, srcCube: &Cube, dstCube: &Cube, cancel: Option<&CancelToken>)
+-> Result<(Vec<String>, u64), CubusError>
+{
+    find_moves_pruned(maxLen, srcCube, dstCube, &|_, _| false, cancel)
+
+}   /* find_moves() */
+
+
+/// Like find_moves(), but additionally skips every candidate move that the
+/// given Pruner rejects.
+pub fn find_moves_pruned (maxLen: usize, srcCube: &Cube, dstCube: &Cube, pruner: Pruner,
+                      cancel: Option<&CancelToken>)
+-> Result<(Vec<String>, u64), CubusError>
+{
+    let cubeSize = srcCube.size;
+    srcCube.check_reachable(dstCube)?;
+
+    let axmax = cubeSize - 1;
+    let movSet = srcCube.legal_moves();
+
+    let mut dblMovs = Layers::new(cubeSize);
+    let mut lastLen = 0;
+
+    // All trails in the order found, the empty one first; those from
+    // `next` on wait to be processed, breadth first.
+    let mut trails: Vec<Trail> = vec![Trail { parent: 0, mov: Move::new('X', 0) }];
+    let mut next = 0;
+
+    let mut seqStrs: Vec<String> = vec![];
+    let mut moveNum: u64 = 0;
+    let mut bricks: Vec<Brick> = Vec::with_capacity(srcCube.bricks.len());
+    let mut movStack: Vec<Move> = Vec::with_capacity(maxLen);
+
+    // Process available trails.
+    while next < trails.len()
+    {
+        let trail = next;
+        next += 1;
+        Trail::moves_into(&trails, trail, &mut movStack);
+        bricks.clear();
+        bricks.extend_from_slice(&srcCube.bricks);
+        for mov in movStack.iter().rev()
+        {
+            brickvec_turn(&mut bricks, mov.axdir, mov.axval, axmax);
+        }
+
+        // Does the trail's move sequence produce the target state?
+        if brickvec_eq(&bricks, &dstCube.bricks)
+        {
+            // Collect successful target match and don't continue the trail.
+            seqStrs.push(string_of_movevec(&movStack.iter().rev().copied().collect::<Vec<Move>>()));
+        }
+        else
+        {
+            // Explore possible continuations of the trail's move sequence.
+            let trailLen = movStack.len();
+            if trailLen < maxLen
+            {
+                let mut negdir: Axis  = '_';
+                let mut axval1: Coord = 0x0F;
+                let mut ident1: u16   = 0x00;
+                let mut ident2: u16   = 0x00;
+                if trailLen > 0
+                {
+                    if trailLen > 1
+                    {
+                        ident2 = movStack[1].ident;
+                    }
+
+                    if trailLen > lastLen
+                    {
+                        dblMovs = Layers::new(cubeSize);
+                        lastLen = trailLen;
+                    }
+
+                    let move1 = &movStack[0];
+                    negdir = invert_axis(move1.axdir);
+                    axval1 = move1.axval;
+                    ident1 = move1.ident;
+                }
+
+                // Systematically explore layer movements.
+                for mov in movSet.iter()
+                {
+                    let (axdir, axval, ident) = (mov.axdir, mov.axval, mov.ident);
+
+                    // Don't rotate a layer in the opposite direction of its previous move.
+                    if trailLen > 0
+                    && axval == axval1
+                    && axdir == negdir
+                    {
+                        continue;
+                    }
+
+                    // Don't rotate a layer in the same direction thrice.
+                    if trailLen > 1
+                    && ident == ident1
+                    && ident == ident2
+                    {
+                        continue;
+                    }
+
+                    // Is the candidate move a duplicate of the most recent move in this trail?
+                    let isDbl = (trailLen > 0 && ident == ident1);
+
+                    // Don't do a double move if the opposite double has been done.
+                    if isDbl && dblMovs.has_flag(negdir, axval)
+                    {
+                        continue;
+                    }
+
+                    if trailLen >= axmax as usize
+                    {
+                        // Check if all layers rotate identically.  This would be equivalent
+                        // to a rotation of the cube as a whole.  Such a transformation is too
+                        // trivial to be used as a basis for meaningful alternative moves.
+                        let sameDir = movStack[.. axmax as usize].iter().all(|mov| mov.axdir == axdir);
+                        if sameDir
+                        {
+                            let mut usedVal: Vec<bool> = vec_of_size(cubeSize as usize, false);
+                            usedVal[axval as usize] = true;
+                            for mov in movStack[.. axmax as usize].iter()
+                            {
+                                usedVal[mov.axval as usize] = true
+                            }
+
+                            let usedAll = usedVal.iter().all(|&used| used);
+                            if usedAll
+                            {
+                                // Skip cube rotation.
+                                continue
+                            }
+                        }
+                    }
+
+                    // Ask the caller's pruning rule.
+                    if pruner(&movStack, mov)
+                    {
+                        continue;
+                    }
+
+                    // Perform new exploratory move, and attempt to continue
+                    // this move sequence.
+                    trails.push(Trail { parent: trail, mov: *mov });
+
+                    if isDbl
+                    {
+                        // Register any double moves.
+                        dblMovs.set_flag(axdir, axval);
+                    }
+
+                    // Count the exploratory moves actually performed.
+                    moveNum += 1;
+
+                    if cancel.is_some_and(|token| token.should_stop(moveNum))
+                    {
+                        return Ok((seqStrs, moveNum));
+                    }
+                }
+            }
+        }
+    }
+
+    Ok((seqStrs, moveNum))
+
+}   /* find_moves_pruned() */
+
+
+/// An estimate of the number of moves that separate a cube from a goal
+/// state, used to guide find_moves_ida().  Estimates must never exceed the
+/// actual number of moves, or the search may miss the shortest sequences.
+pub trait Heuristic
+{
+    fn estimate (&self, cube: &Cube, goal: &Cube) -> usize;
+
+}   /* Heuristic */
+
+
+/// The Heuristic that knows nothing, which makes IDA* plain iterative deepening.
+pub struct ZeroHeuristic;
+
+impl Heuristic for ZeroHeuristic
+{
+    fn estimate (&self, _cube: &Cube, _goal: &Cube)
+    -> usize
+    {
+        0
+    }
+
+}   /* impl Heuristic for ZeroHeuristic */
+
+
+/// Divides the number of misplaced stickers by the most stickers that a
+/// single move can change, which is a full face plus its four side rows.
+pub struct MisplacedStickers;
+
+impl Heuristic for MisplacedStickers
+{
+    fn estimate (&self, cube: &Cube, goal: &Cube)
+    -> usize
+    {
+        let size = cube.size as usize;
+        let perMove = size * size + 4 * size;
+
+        cube.sticker_distance(goal).div_ceil(perMove)
+    }
+
+}   /* impl Heuristic for MisplacedStickers */
+
+
+/// Depth-first probe of find_moves_ida() that extends `path`.  Returns Ok when
+/// `path` leads to the goal, or else the smallest cost estimate that exceeded
+/// the bound, which is usize::MAX once the search has been cancelled.
+/// The successors of `cube` are built in `buffers[0]`, and theirs in the
+/// remaining buffers, so that the search allocates no cubes.
+#[allow(clippy::too_many_arguments)]
+fn ida_probe (cube: &Cube, goal: &Cube, heuristic: &dyn Heuristic, bound: usize,
+              movSet: &[Move], path: &mut Vec<Move>, buffers: &mut [Cube], moveNum: &mut u64,
+              cancel: Option<&CancelToken>)
+-> Result<(), usize>
+{
+    let cost = path.len() + heuristic.estimate(cube, goal);
+    if cost > bound
+    {
+        return Err(cost);
+    }
+
+    if cube == goal
+    {
+        return Ok(());
+    }
+
+    let pathLen = path.len();
+    let (next, buffers) = buffers.split_first_mut().unwrap();
+    let mut nextBound = usize::MAX;
+    for &mov in movSet.iter()
+    {
+        if pathLen > 0
+        {
+            // Don't rotate a layer in the opposite direction of its previous move.
+            let move1 = &path[pathLen - 1];
+            if mov.axval == move1.axval && mov.axdir == invert_axis(move1.axdir)
+            {
+                continue;
+            }
+
+            // Don't rotate a layer in the same direction thrice.
+            if pathLen > 1 && mov.ident == move1.ident && mov.ident == path[pathLen - 2].ident
+            {
+                continue;
+            }
+        }
+
+        *moveNum += 1;
+        if cancel.is_some_and(|token| token.should_stop(*moveNum))
+        {
+            return Err(usize::MAX);
+        }
+
+        path.push(mov);
+        next.bricks.copy_from_slice(&cube.bricks);
+        next.apply_checked_moves(&[mov]);
+        match ida_probe(next, goal, heuristic, bound, movSet, path, buffers, moveNum, cancel)
+        {
+            Ok(())      =>  return Ok(()),
+            Err(usize::MAX)
+                        =>  return Err(usize::MAX),
+            Err(cost)   =>  nextBound = nextBound.min(cost)
+        }
+        path.pop();
+    }
+
+    Err(nextBound)
+
+}   /* ida_probe() */
+
+
+/// Depth-first probe of find_moves_with_cycles(), like ida_probe() but on
+/// the cubies of a 3x3x3 cube and their face moves.
+#[allow(clippy::too_many_arguments)]
+fn cubie_probe (cubies: &CubieCube, isGoal: &dyn Fn (&CubieCube) -> bool, estimate: &dyn Fn (&CubieCube) -> usize,
+                bound: usize, movSet: &[(Move, CubieCube)], path: &mut Vec<Move>, moveNum: &mut u64,
+                cancel: Option<&CancelToken>)
+-> Result<(), usize>
+{
+    let cost = path.len() + estimate(cubies);
+    if cost > bound
+    {
+        return Err(cost);
+    }
+
+    if isGoal(cubies)
+    {
+        return Ok(());
+    }
+
+    let pathLen = path.len();
+    let mut nextBound = usize::MAX;
+    for (mov, movCubies) in movSet.iter()
+    {
+        if pathLen > 0
+        {
+            // Don't rotate a layer in the opposite direction of its previous move.
+            let move1 = &path[pathLen - 1];
+            if mov.axval == move1.axval && mov.axdir == invert_axis(move1.axdir)
+            {
+                continue;
+            }
+
+            // Don't rotate a layer in the same direction thrice.
+            if pathLen > 1 && mov.ident == move1.ident && mov.ident == path[pathLen - 2].ident
+            {
+                continue;
+            }
+
+            // Opposite faces commute, so turn them in one order only.
+            if mov.axdir.eq_ignore_ascii_case(&move1.axdir) && mov.axval < move1.axval
+            {
+                continue;
+            }
+        }
+
+        *moveNum += 1;
+        if cancel.is_some_and(|token| token.should_stop(*moveNum))
+        {
+            return Err(usize::MAX);
+        }
+
+        path.push(*mov);
+        match cubie_probe(&cubies.compose(movCubies), isGoal, estimate, bound, movSet, path, moveNum, cancel)
+        {
+            Ok(())      =>  return Ok(()),
+            Err(usize::MAX)
+                        =>  return Err(usize::MAX),
+            Err(cost)   =>  nextBound = nextBound.min(cost)
+        }
+        path.pop();
+    }
+
+    Err(nextBound)
+
+}   /* cubie_probe() */
+
+
+/// Finds one shortest sequence of face moves, no longer than maxLen, that
+/// moves the corners and edges of an ordered 3x3x3 cube round the given
+/// cycles, each with the twist or flip that it gathers, and leaves all
+/// other corners and edges in place and unturned.  How the pieces of a
+/// cycle face one by one is left open, as are the spins of the centers.
+/// Searches by iterative-deepening A* like find_moves_ida().
+pub fn find_moves_with_cycles (maxLen: usize, cycles: &[PieceCycle], cancel: Option<&CancelToken>)
+-> Result<(Option<String>, u64), CubusError>
+{
+    // The places of the pieces, by CubieCube's numbering, and which piece
+    // each place gets.
+    let place = |name: &str| -> usize {
+        let names: &[&str] = if name.len() == 3 { &CORNER_NAMES } else { &EDGE_NAMES };
+        names.iter().position(|known| loc_of_faces(known, 2) == loc_of_faces(name, 2)).unwrap()
+    };
+    let mut goal = CubieCube::new();
+    let mut orbits: Vec<(bool, Vec<usize>, u8)> = vec![];
+    let mut isMoved = [vec![false; 8], vec![false; 12]];
+    for cycle in cycles.iter()
+    {
+        let isCorner = cycle.modulus == 3;
+        let places: Vec<usize> = cycle.pieces.iter().map(|name| place(name)).collect();
+        for (ind, &from) in places.iter().enumerate()
+        {
+            let to = places[(ind + 1) % places.len()];
+            if isCorner { goal.cp[to] = from as u8; } else { goal.ep[to] = from as u8; }
+            isMoved[!isCorner as usize][from] = true;
+        }
+        // PieceCycle counts counterclockwise twists, CubieCube clockwise.
+        orbits.push((isCorner, places, (cycle.modulus - cycle.twist) % cycle.modulus));
+    }
+    for (kind, moved) in isMoved.iter().enumerate()
+    {
+        for (place, _) in moved.iter().enumerate().filter(|(_, &isMoved)| !isMoved)
+        {
+            orbits.push((kind == 0, vec![place], 0));
+        }
+    }
+
+    let isGoal = |cubies: &CubieCube| {
+        cubies.cp == goal.cp && cubies.ep == goal.ep
+     && orbits.iter().all(|(isCorner, places, twist)| {
+            let (ori, modulus) = if *isCorner { (&cubies.co[..], 3) } else { (&cubies.eo[..], 2) };
+            places.iter().map(|&place| ori[place]).sum::<u8>() % modulus == *twist
+        })
+    };
+
+    // A quarter turn moves four corners and four edges.
+    let estimate = |cubies: &CubieCube| {
+        let corners = (0 .. 8).filter(|&place| cubies.cp[place] != goal.cp[place]).count();
+        let edges = (0 .. 12).filter(|&place| cubies.ep[place] != goal.ep[place]).count();
+        corners.div_ceil(4).max(edges.div_ceil(4))
+    };
+
+    let mut movSet: Vec<(Move, CubieCube)> = vec![];
+    for axdir in ['X', 'x', 'Y', 'y', 'Z', 'z'].into_iter()
+    {
+        for axval in [0, 2].into_iter()
+        {
+            let mov = Move::new(axdir, axval);
+            movSet.push((mov, CubieCube::of_move(&mov)?));
+        }
+    }
+
+    let start = CubieCube::new();
+    let mut path: Vec<Move> = vec![];
+    let mut moveNum: u64 = 0;
+    let mut bound = estimate(&start);
+    while bound <= maxLen
+    {
+        match cubie_probe(&start, &isGoal, &estimate, bound, &movSet, &mut path, &mut moveNum, cancel)
+        {
+            Ok(())      =>  return Ok((Some(string_of_movevec(&path)), moveNum)),
+            Err(cost)   =>  bound = cost
+        }
+    }
+
+    Ok((None, moveNum))
+
+}   /* find_moves_with_cycles() */
+
+
+/// Finds one shortest move sequence, no longer than maxLen, that transforms
+/// the srcCube into the dstCube, by iterative-deepening A* search.  Unlike
+/// find_moves(), memory use stays proportional to the sequence length.
+/// If the optional CancelToken gets cancelled, returns no sequence.
+pub fn find_moves_ida (maxLen: usize, srcCube: &Cube, dstCube: &Cube, heuristic: &dyn Heuristic,
+                   cancel: Option<&CancelToken>)
+-> Result<(Option<String>, u64), CubusError>
+{
+    srcCube.check_reachable(dstCube)?;
+
+    let movSet = srcCube.legal_moves();
+    let mut buffers: Vec<Cube> = vec_of_size(maxLen + 1, srcCube.clone());
+    let mut path: Vec<Move> = vec![];
+    let mut moveNum: u64 = 0;
+
+    let mut bound = heuristic.estimate(srcCube, dstCube);
+    while bound <= maxLen
+    {
+        match ida_probe(srcCube, dstCube, heuristic, bound, &movSet, &mut path, &mut buffers, &mut moveNum, cancel)
+        {
+            Ok(())      =>  return Ok((Some(string_of_movevec(&path)), moveNum)),
+            Err(cost)   =>  bound = cost
+        }
+    }
+
+    Ok((None, moveNum))
+
+}   /* find_moves_ida() */
+
+
+/// Returns how many moves it takes at least to solve the cube, i.e. to turn
+/// it into the ordered cube of its size, and whether that is exact: the
+/// length of a shortest solution if the search of find_moves_ida() finds
+/// one of at most maxLen moves, or else the length below which the search
+/// ruled out all solutions before it passed maxLen or got cancelled.
+pub fn solve_distance (cube: &Cube, maxLen: usize, heuristic: &dyn Heuristic, cancel: Option<&CancelToken>)
+-> Result<(usize, bool), CubusError>
+{
+    let goal = Cube::new(cube.size);
+    cube.check_reachable(&goal)?;
+
+    let movSet = goal.legal_moves();
+    let mut buffers: Vec<Cube> = vec_of_size(maxLen + 1, cube.clone());
+    let mut path: Vec<Move> = vec![];
+    let mut moveNum: u64 = 0;
+
+    let mut bound = heuristic.estimate(cube, &goal);
+    while bound <= maxLen
+    {
+        match ida_probe(cube, &goal, heuristic, bound, &movSet, &mut path, &mut buffers, &mut moveNum, cancel)
+        {
+            Ok(())          =>  return Ok((path.len(), true)),
+            Err(usize::MAX) =>  break,
+            Err(cost)       =>  bound = cost
+        }
+    }
+
+    Ok((bound, false))
+
+}   /* solve_distance() */
+
+
+/// The moves of the two-phase solver, by index: each face of URFDLB in
+/// turn, a quarter clockwise, half, and a quarter counterclockwise.
+const TWO_PHASE_FACES: [char; 6] = ['U', 'R', 'F', 'D', 'L', 'B'];
+
+/// The moves of the second phase, which keep a cube in the subgroup
+/// ⟨U, D, R2, L2, F2, B2⟩, as indices of two-phase moves.
+const PHASE2_MOVES: [usize; 10] = [0, 1, 2, 4, 7, 9, 10, 11, 13, 16];
+
+/// The slice coordinate of the middle layer edges in their own layer.
+const SLICE_HOME: usize = 494;
+
+
+/// Returns the binomial coefficient of n over k, for small n.
+fn binomial (n: usize, k: usize)
+-> usize
+{
+    if k > n
+    {
+        return 0;
+    }
+
+    (0 .. k).fold(1, |product, ind| product * (n - ind) / (ind + 1))
+
+}   /* binomial() */
+
+
+/// Returns the rank of a permutation of 0 .. n among all n! of them.
+fn perm_rank (perm: &[u8])
+-> usize
+{
+    let len = perm.len();
+    (0 .. len).fold(0, |rank, ind| {
+        rank * (len - ind) + perm[ind + 1 ..].iter().filter(|&&later| later < perm[ind]).count()
+    })
+
+}   /* perm_rank() */
+
+
+/// Returns the permutation of 0 .. len of the given rank, see perm_rank().
+fn perm_of_rank (mut rank: usize, len: usize)
+-> Vec<u8>
+{
+    let mut digits = vec![0; len];
+    for ind in (0 .. len).rev()
+    {
+        digits[ind] = rank % (len - ind);
+        rank /= len - ind;
+    }
+    let mut unused: Vec<u8> = (0 .. len as u8).collect();
+
+    digits.into_iter().map(|digit| unused.remove(digit)).collect()
+
+}   /* perm_of_rank() */
+
+
+/// The coordinates of a CubieCube that the two-phase solver works with.
+/// Phase one brings twist, flip and slice home, which leaves the cube in
+/// the subgroup of PHASE2_MOVES; phase two then brings the permutations
+/// home, which only that subgroup can keep apart.
+impl CubieCube
+{
+    /// Returns how the first seven corners are turned, in base 3.
+    fn twist (&self)
+    -> usize
+    {
+        self.co[.. 7].iter().fold(0, |twist, &ori| 3 * twist + ori as usize)
+
+    } /* .twist() */
+
+    /// Turns the corners as twist() tells, the last one as the others
+    /// require.
+    fn set_twist (&mut self, mut twist: usize)
+    {
+        for place in (0 .. 7).rev()
+        {
+            self.co[place] = (twist % 3) as u8;
+            twist /= 3;
+        }
+        self.co[7] = (3 - self.co[.. 7].iter().sum::<u8>() % 3) % 3;
+
+    } /* .set_twist() */
+
+    /// Returns how the first eleven edges are flipped, in base 2.
+    fn flip (&self)
+    -> usize
+    {
+        self.eo[.. 11].iter().fold(0, |flip, &ori| 2 * flip + ori as usize)
+
+    } /* .flip() */
+
+    /// Flips the edges as flip() tells, the last one as the others require.
+    fn set_flip (&mut self, mut flip: usize)
+    {
+        for place in (0 .. 11).rev()
+        {
+            self.eo[place] = (flip % 2) as u8;
+            flip /= 2;
+        }
+        self.eo[11] = self.eo[.. 11].iter().sum::<u8>() % 2;
+
+    } /* .set_flip() */
+
+    /// Returns the rank of the four places that the middle layer edges
+    /// are at among all C(12, 4) choices, SLICE_HOME for their own.
+    fn slice (&self)
+    -> usize
+    {
+        let mut rank = 0;
+        let mut count = 0;
+        for place in 0 .. 12
+        {
+            if self.ep[place] >= 8
+            {
+                count += 1;
+                rank += binomial(place, count);
+            }
+        }
+
+        rank
+
+    } /* .slice() */
+
+    /// Puts the middle layer edges at the places of the given slice(), the
+    /// others in order around them.
+    fn set_slice (&mut self, mut rank: usize)
+    {
+        let mut isSlice = [false; 12];
+        for count in (1 ..= 4).rev()
+        {
+            let place = (0 .. 12).rev().find(|&place| binomial(place, count) <= rank).unwrap();
+            rank -= binomial(place, count);
+            isSlice[place] = true;
+        }
+        let (mut slice, mut other) = (8, 0);
+        for (place, &isSliced) in isSlice.iter().enumerate()
+        {
+            let piece = if isSliced { &mut slice } else { &mut other };
+            self.ep[place] = *piece;
+            *piece += 1;
+        }
+
+    } /* .set_slice() */
+
+}   /* impl CubieCube */
+
+
+/// The move and pruning tables of solve_two_phase().  Move tables tell
+/// the coordinate that each move leads to, pruning tables how many moves
+/// a pair of coordinates takes at least to get home.
+struct TwoPhaseTables
+{
+    moves:     Vec<CubieCube>,      // The 18 two-phase moves.
+    twistMove: Vec<[u16; 18]>,      // 3⁷ corner twists.
+    flipMove:  Vec<[u16; 18]>,      // 2¹¹ edge flips.
+    sliceMove: Vec<[u16; 18]>,      // C(12, 4) places of the middle layer edges.
+    cpermMove: Vec<[u16; 10]>,      // 8! corner permutations, by PHASE2_MOVES.
+    epermMove: Vec<[u16; 10]>,      // 8! permutations of the Up and Down layer edges.
+    spermMove: Vec<[u16; 10]>,      // 4! permutations of the middle layer edges.
+    twistSlicePrune: Vec<u8>,
+    flipSlicePrune:  Vec<u8>,
+    cpermSpermPrune: Vec<u8>,
+    epermSpermPrune: Vec<u8>
+
+}   /* TwoPhaseTables */
+
+impl TwoPhaseTables
+{
+    /// Works out all tables, which takes a moment.
+    fn new ()
+    -> TwoPhaseTables
+    {
+        let moves = face_turn_cubies();
+
+        // A move table of `count` coordinates, each set into an ordered
+        // cube and read back after each of the given moves.
+        let moveTable = |count: usize, moveInds: &[usize], set: &dyn Fn(&mut CubieCube, usize), get: &dyn Fn(&CubieCube) -> usize| {
+            (0 .. count).map(|coord| {
+                let mut cubies = CubieCube::new();
+                set(&mut cubies, coord);
+                moveInds.iter().map(|&ind| get(&cubies.compose(&moves[ind])) as u16).collect::<Vec<u16>>()
+            }).collect::<Vec<Vec<u16>>>()
+        };
+        let allMoves: Vec<usize> = (0 .. 18).collect();
+        let fixed18 = |table: Vec<Vec<u16>>| table.into_iter().map(|row| <[u16; 18]>::try_from(row).unwrap()).collect::<Vec<_>>();
+        let fixed10 = |table: Vec<Vec<u16>>| table.into_iter().map(|row| <[u16; 10]>::try_from(row).unwrap()).collect::<Vec<_>>();
+
+        let twistMove = fixed18(moveTable(2187, &allMoves, &|cubies, coord| cubies.set_twist(coord), &|cubies| cubies.twist()));
+        let flipMove  = fixed18(moveTable(2048, &allMoves, &|cubies, coord| cubies.set_flip(coord), &|cubies| cubies.flip()));
+        let sliceMove = fixed18(moveTable(495, &allMoves, &|cubies, coord| cubies.set_slice(coord), &|cubies| cubies.slice()));
+        let cpermMove = fixed10(moveTable(40320, &PHASE2_MOVES,
+                                          &|cubies, coord| cubies.cp.copy_from_slice(&perm_of_rank(coord, 8)),
+                                          &|cubies| perm_rank(&cubies.cp)));
+        let epermMove = fixed10(moveTable(40320, &PHASE2_MOVES,
+                                          &|cubies, coord| cubies.ep[.. 8].copy_from_slice(&perm_of_rank(coord, 8)),
+                                          &|cubies| perm_rank(&cubies.ep[.. 8])));
+        let spermMove = fixed10(moveTable(24, &PHASE2_MOVES,
+                                          &|cubies, coord| {
+                                              let perm = perm_of_rank(coord, 4);
+                                              (0 .. 4).for_each(|ind| cubies.ep[8 + ind] = 8 + perm[ind]);
+                                          },
+                                          &|cubies| perm_rank(&cubies.ep[8 ..].iter().map(|piece| piece - 8).collect::<Vec<u8>>())));
+
+        let twistSlicePrune = prune_table(2187 * 495, twist_slice_index(0, SLICE_HOME), 18,
+                                          &|ind, mov| twist_slice_index(twistMove[ind / 495][mov] as usize, sliceMove[ind % 495][mov] as usize));
+        let flipSlicePrune  = prune_table(2048 * 495, twist_slice_index(0, SLICE_HOME), 18,
+                                          &|ind, mov| twist_slice_index(flipMove[ind / 495][mov] as usize, sliceMove[ind % 495][mov] as usize));
+        let cpermSpermPrune = prune_table(40320 * 24, 0, 10,
+                                          &|ind, mov| 24 * cpermMove[ind / 24][mov] as usize + spermMove[ind % 24][mov] as usize);
+        let epermSpermPrune = prune_table(40320 * 24, 0, 10,
+                                          &|ind, mov| 24 * epermMove[ind / 24][mov] as usize + spermMove[ind % 24][mov] as usize);
+
+        TwoPhaseTables {
+            moves, twistMove, flipMove, sliceMove, cpermMove, epermMove, spermMove,
+            twistSlicePrune, flipSlicePrune, cpermSpermPrune, epermSpermPrune
+        }
+
+    } /* ::new() */
+
+}   /* impl TwoPhaseTables */
+
+
+/// Returns the cubies of the 18 face turns of the two-phase moves.
+fn face_turn_cubies ()
+-> Vec<CubieCube>
+{
+    let mut moves: Vec<CubieCube> = vec![];
+    for letter in TWO_PHASE_FACES
+    {
+        let quarter = CubieCube::of_move(&movevec_of_face(letter, 1, 1, 2).unwrap()[0]).unwrap();
+        let half = quarter.compose(&quarter);
+        moves.extend([quarter, half, half.compose(&quarter)]);
+    }
+
+    moves
+
+}   /* face_turn_cubies() */
+
+
+/// Returns the moves of the given face turns, as indexed by the two-phase
+/// moves: half turns as two quarter turns.
+fn movevec_of_face_turns (turns: &[usize])
+-> Vec<Move>
+{
+    let mut moves: Vec<Move> = vec![];
+    for &turn in turns
+    {
+        let face = movevec_of_face(TWO_PHASE_FACES[turn / 3], 1, 1, 2).unwrap();
+        moves.extend(match turn % 3
+        {
+            0   =>  face,
+            1   =>  [face.clone(), face].concat(),
+            _   =>  movevec_inverted(&face)
+        });
+    }
+
+    moves
+
+}   /* movevec_of_face_turns() */
+
+
+/// Returns the shortest whole-cube rotation after which the cube meets the
+/// given goal, which some rotation must, and the cube after it.
+fn shortest_rotation (cube: &Cube, goal: &dyn Fn(&Cube) -> bool)
+-> Result<(Vec<Move>, Cube), CubusError>
+{
+    let turns = [Move::rotation('X'), Move::rotation('Y'), Move::rotation('Z')];
+    let mut rotations: Vec<Vec<Move>> = vec![vec![]];
+    let mut ind = 0;
+    loop
+    {
+        let rotation = &rotations[ind];
+        let turned = cube.copy_with_moves(rotation)?;
+        if goal(&turned)
+        {
+            return Ok((movevec_simplified(rotation), turned));
+        }
+        let longer: Vec<Vec<Move>> = turns.iter().map(|turn| [rotation.as_slice(), &[*turn]].concat()).collect();
+        rotations.extend(longer);
+        ind += 1;
+    }
+
+}   /* shortest_rotation() */
+
+
+/// Returns the shortest whole-cube rotation that brings the centers of a
+/// 3x3x3 cube to their places, and the cubies of the cube after it.
+fn cubies_with_centers_home (cube: &Cube)
+-> Result<(Vec<Move>, CubieCube), CubusError>
+{
+    let (rotation, turned) = shortest_rotation(cube, &|turned| turned.to_cubies().is_some())?;
+
+    Ok((rotation, turned.to_cubies().unwrap()))
+
+}   /* cubies_with_centers_home() */
+
+
+/// Returns the index of a pair of a twist or flip and a slice coordinate
+/// in the phase one pruning tables.
+fn twist_slice_index (coord: usize, slice: usize)
+-> usize
+{
+    495 * coord + slice
+
+}   /* twist_slice_index() */
+
+
+/// Returns how many moves each of `count` indices takes at least to get to
+/// `home`, found breadth first by the `moveNum` moves of `step`.  Indices
+/// that the moves cannot reach are left at u8::MAX.
+fn prune_table (count: usize, home: usize, moveNum: usize, step: &dyn Fn(usize, usize) -> usize)
+-> Vec<u8>
+{
+    let mut depths: Vec<u8> = vec_of_size(count, u8::MAX);
+    depths[home] = 0;
+    let mut done = 1;
+    let mut depth = 0;
+    loop
+    {
+        let before = done;
+        for ind in 0 .. count
+        {
+            if depths[ind] != depth
+            {
+                continue;
+            }
+            for mov in 0 .. moveNum
+            {
+                let next = step(ind, mov);
+                if depths[next] == u8::MAX
+                {
+                    depths[next] = depth + 1;
+                    done += 1;
+                }
+            }
+        }
+        depth += 1;
+        if done == count || done == before
+        {
+            break;
+        }
+    }
+
+    depths
+
+}   /* prune_table() */
+
+
+/// Tells whether the face turn `turn` may follow the path of face turns:
+/// not the same face again, and of opposite faces, which commute, only U
+/// before D, R before L and F before B.
+fn face_turn_may_follow (path: &[usize], turn: usize)
+-> bool
+{
+    match path.last()
+    {
+        Some(&last) =>  turn / 3 != last / 3 && turn / 3 + 3 != last / 3,
+        None        =>  true
+    }
+
+}   /* face_turn_may_follow() */
+
+
+/// The state of a search of solve_two_phase().
+struct TwoPhaseSearch<'a>
+{
+    tables:  &'a TwoPhaseTables,
+    start:   CubieCube,
+    target:  usize,                 // Stop at a solution of this many moves.
+    path:    Vec<usize>,            // Two-phase moves so far.
+    best:    Option<Vec<usize>>,    // The shortest solution yet.
+    moveNum: u64,
+    cancel:  Option<&'a CancelToken>,
+    isCancelled: bool
+
+}   /* TwoPhaseSearch */
+
+impl TwoPhaseSearch<'_>
+{
+    /// Tells whether the search should stop, counting an exploratory move.
+    fn should_stop (&mut self)
+    -> bool
+    {
+        self.moveNum += 1;
+        if self.cancel.is_some_and(|cancel| cancel.should_stop(self.moveNum))
+        {
+            self.isCancelled = true;
+        }
+
+        self.isCancelled
+
+    } /* .should_stop() */
+
+    /// Extends the path by `togo` phase one moves into the subgroup of
+    /// PHASE2_MOVES, and each such path by phase two.  Returns true once
+    /// the search should end.
+    fn phase1 (&mut self, twist: usize, flip: usize, slice: usize, togo: usize)
+    -> bool
+    {
+        if self.should_stop()
+        {
+            return true;
+        }
+
+        let tables = self.tables;
+        if togo < (tables.twistSlicePrune[twist_slice_index(twist, slice)] as usize)
+                  .max(tables.flipSlicePrune[twist_slice_index(flip, slice)] as usize)
+        {
+            return false;
+        }
+        if togo == 0
+        {
+            // A path that ends in a phase two move was tried one move shorter.
+            return self.path.last().is_none_or(|last| !PHASE2_MOVES.contains(last)) && self.phase2_start();
+        }
+
+        for mov in 0 .. 18
+        {
+            if !face_turn_may_follow(&self.path, mov)
+            {
+                continue;
+            }
+            self.path.push(mov);
+            let isDone = self.phase1(tables.twistMove[twist][mov] as usize, tables.flipMove[flip][mov] as usize,
+                                     tables.sliceMove[slice][mov] as usize, togo - 1);
+            self.path.pop();
+            if isDone
+            {
+                return true;
+            }
+        }
+
+        false
+
+    } /* .phase1() */
+
+    /// Finds the shortest phase two that completes the path of phase one
+    /// in fewer moves than the best solution yet.  Returns true once the
+    /// search should end.
+    fn phase2_start (&mut self)
+    -> bool
+    {
+        let tables = self.tables;
+        let cubies = self.path.iter().fold(self.start, |cubies, &mov| cubies.compose(&tables.moves[mov]));
+        let cperm = perm_rank(&cubies.cp);
+        let eperm = perm_rank(&cubies.ep[.. 8]);
+        let sperm = perm_rank(&cubies.ep[8 ..].iter().map(|piece| piece - 8).collect::<Vec<u8>>());
+
+        let phase1Len = self.path.len();
+        let most = match &self.best
+        {
+            Some(best)  =>  (best.len() - 1).saturating_sub(phase1Len).min(18),
+            None        =>  18
+        };
+        for togo in 0 ..= most
+        {
+            if self.phase2(cperm, eperm, sperm, togo)
+            {
+                self.best = Some(self.path.clone());
+                self.path.truncate(phase1Len);
+                return self.path.len() + togo <= self.target;
+            }
+            if self.isCancelled
+            {
+                return true;
+            }
+        }
+
+        false
+
+    } /* .phase2_start() */
+
+    /// Extends the path by `togo` phase two moves to the ordered cube.
+    /// Returns true if it got there, with the path extended.
+    fn phase2 (&mut self, cperm: usize, eperm: usize, sperm: usize, togo: usize)
+    -> bool
+    {
+        if self.should_stop()
+        {
+            return false;
+        }
+
+        let tables = self.tables;
+        if togo < (tables.cpermSpermPrune[24 * cperm + sperm] as usize)
+                  .max(tables.epermSpermPrune[24 * eperm + sperm] as usize)
+        {
+            return false;
+        }
+        if togo == 0
+        {
+            return true;
+        }
+
+        for (ind, &mov) in PHASE2_MOVES.iter().enumerate()
+        {
+            if !face_turn_may_follow(&self.path, mov)
+            {
+                continue;
+            }
+            self.path.push(mov);
+            if self.phase2(tables.cpermMove[cperm][ind] as usize, tables.epermMove[eperm][ind] as usize,
+                           tables.spermMove[sperm][ind] as usize, togo - 1)
+            {
+                return true;
+            }
+            self.path.pop();
+        }
+
+        false
+
+    } /* .phase2() */
+
+}   /* impl TwoPhaseSearch */
+
+
+/// Returns a solution of a 3x3x3 cube, i.e. moves that turn it into the
+/// ordered cube, by Kociemba's two-phase algorithm: the first phase brings
+/// all corners and edges into the subgroup that U, D, R2, L2, F2 and B2
+/// generate, the second solves the cube within it.  The search goes on with
+/// ever shorter first phases until a solution has at most `target` face
+/// turns, which 22 or more soon do, or until no shorter one can be found
+/// this way, or cancellation; it then returns the shortest solution found,
+/// None if none was before cancellation, and the number of exploratory
+/// moves.  A whole-cube rotation comes first if the centers are away from
+/// their places.  The tables take a moment to work out, once with `std`.
+pub fn solve_two_phase (cube: &Cube, target: usize, cancel: Option<&CancelToken>)
+-> Result<(Option<Vec<Move>>, u64), CubusError>
+{
+    cube.check_reachable(&Cube::new(3))?;
+
+    let (rotation, start) = cubies_with_centers_home(cube)?;
+
+    let table = TwoPhaseTables::new;
+    #[cfg(feature = "std")]
+    let tables = {
+        static TABLES: OnceLock<TwoPhaseTables> = OnceLock::new();
+        TABLES.get_or_init(table)
+    };
+    #[cfg(not(feature = "std"))]
+    let tables = &table();
+
+    let mut search = TwoPhaseSearch {
+        tables, start, target,
+        path: vec![], best: None, moveNum: 0, cancel, isCancelled: false
+    };
+    let (twist, flip, slice) = (start.twist(), start.flip(), start.slice());
+    for phase1Len in 0 ..
+    {
+        if search.best.as_ref().is_some_and(|best| best.len() <= phase1Len)
+        || search.phase1(twist, flip, slice, phase1Len)
+        {
+            break;
+        }
+    }
+
+    let solution = search.best.map(|best| [rotation, movevec_of_face_turns(&best)].concat());
+
+    Ok((solution, search.moveNum))
+
+}   /* solve_two_phase() */
+
+
+/// Returns moves that turn one 3x3x3 cube into another, by solving with
+/// solve_two_phase() the state that they differ by, so that the moves are
+/// short but one possible path of many; None if none was found before
+/// cancellation, and the number of exploratory moves.  Whole-cube
+/// rotations come first and last if the centers of either cube are away
+/// from their places.
+pub fn solve_between (from: &Cube, to: &Cube, target: usize, cancel: Option<&CancelToken>)
+-> Result<(Option<Vec<Move>>, u64), CubusError>
+{
+    from.check_reachable(to)?;
+
+    // The moves M with from·M = to are those that solve to⁻¹·from.
+    let (fromRotation, fromCubies) = cubies_with_centers_home(from)?;
+    let (toRotation, toCubies) = cubies_with_centers_home(to)?;
+    let difference = Cube::from_cubies(&toCubies.inverse().compose(&fromCubies))?;
+    let (found, moveNum) = solve_two_phase(&difference, target, cancel)?;
+
+    let path = found.map(|moves| movevec_simplified(&[fromRotation, moves, movevec_inverted(&toRotation)].concat()));
+
+    Ok((path, moveNum))
+
+}   /* solve_between() */
+
+
+/// The states of the corner pattern database: 8! corner permutations
+/// times 3⁷ twists.
+const CORNER_STATES: usize = 40320 * 2187;
+
+/// The states of an edge pattern database: the 12!/6! ways to place six
+/// edges times their 2⁶ flips.
+const EDGE_STATES: usize = 665280 * 64;
+
+/// The first bytes of a pattern database file, which change with its layout.
+const DATABASE_MAGIC: &[u8; 8] = b"CUBUSPD1";
+
+
+/// The bytes of pattern databases, worked out or read from a file.
+enum DatabaseBytes
+{
+    Owned(Vec<u8>),
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap)
+
+}   /* DatabaseBytes */
+
+impl Deref for DatabaseBytes
+{
+    type Target = [u8];
+
+    fn deref (&self)
+    -> &[u8]
+    {
+        match self
+        {
+            DatabaseBytes::Owned(bytes)     =>  bytes,
+            #[cfg(feature = "mmap")]
+            DatabaseBytes::Mapped(bytes)    =>  bytes
+        }
+    }
+
+}   /* impl Deref for DatabaseBytes */
+
+
+/// The pattern databases of solve_optimal(), after Korf: how many face
+/// turns each arrangement of the corners, of the first six edges, and of
+/// the last six edges takes at least to get home, a nibble per
+/// arrangement.  Working them out takes a few minutes and some 90 MB,
+/// so they are best written to a file once and opened from there.
+pub struct PatternDatabases
+{
+    bytes:     DatabaseBytes,       // DATABASE_MAGIC, then the corner and both edge tables.
+    cpermMove: Vec<[u16; 18]>,      // 8! corner permutations, by the two-phase moves.
+    twistMove: Vec<[u16; 18]>,      // 3⁷ corner twists.
+    slotMove:  [[u8; 18]; 24]       // Where an edge at a place, times 2, plus its flip goes.
+
+}   /* PatternDatabases */
+
+impl PatternDatabases
+{
+    /// Works out the pattern databases, which takes a few minutes.
+    pub fn generate ()
+    -> PatternDatabases
+    {
+        let mut databases = PatternDatabases::with_bytes(DatabaseBytes::Owned(vec![]));
+
+        let mut corners = vec_of_size(CORNER_STATES.div_ceil(2), 0xFF);
+        let (cpermMove, twistMove) = (&databases.cpermMove, &databases.twistMove);
+        nibble_prune_table(&mut corners, CORNER_STATES, 0, &|ind, next| {
+            let (cperm, twist) = (ind / 2187, ind % 2187);
+            for mov in 0 .. 18
+            {
+                next[mov] = 2187 * cpermMove[cperm][mov] as usize + twistMove[twist][mov] as usize;
+            }
+        });
+
+        let mut bytes = DATABASE_MAGIC.to_vec();
+        bytes.extend(corners);
+        for first in [0, 6]
+        {
+            let mut edges = vec_of_size(EDGE_STATES.div_ceil(2), 0xFF);
+            let home: Vec<u8> = (first .. first + 6).map(|place| 2 * place).collect();
+            let slotMove = &databases.slotMove;
+            nibble_prune_table(&mut edges, EDGE_STATES, edge_slots_index(&home), &|ind, next| {
+                let slots = edge_slots_of_index::<6>(ind);
+                for mov in 0 .. 18
+                {
+                    next[mov] = edge_slots_index(&slots.map(|slot| slotMove[slot as usize][mov]));
+                }
+            });
+            bytes.extend(edges);
+        }
+        databases.bytes = DatabaseBytes::Owned(bytes);
+
+        databases
+
+    } /* ::generate() */
+
+    /// Returns pattern databases of the given bytes, with the move tables
+    /// that searching them takes.
+    fn with_bytes (bytes: DatabaseBytes)
+    -> PatternDatabases
+    {
+        let moves = face_turn_cubies();
+
+        let cpermMove = (0 .. 40320).map(|cperm| {
+            let mut cubies = CubieCube::new();
+            cubies.cp.copy_from_slice(&perm_of_rank(cperm, 8));
+            core::array::from_fn(|mov| perm_rank(&cubies.compose(&moves[mov]).cp) as u16)
+        }).collect();
+        let twistMove = (0 .. 2187).map(|twist| {
+            let mut cubies = CubieCube::new();
+            cubies.set_twist(twist);
+            core::array::from_fn(|mov| cubies.compose(&moves[mov]).twist() as u16)
+        }).collect();
+
+        let slotMove = edge_slot_moves(&moves);
+
+        PatternDatabases { bytes, cpermMove, twistMove, slotMove }
+
+    } /* ::with_bytes() */
+
+    /// Opens pattern databases that write_to_file() wrote, mapping the
+    /// file into memory with the `mmap` feature and reading it otherwise.
+    #[cfg(feature = "std")]
+    pub fn open (path: &str)
+    -> Result<PatternDatabases, CubusError>
+    {
+        #[cfg(feature = "mmap")]
+        // Safe as long as no one changes the file while it is mapped.
+        let bytes = DatabaseBytes::Mapped(unsafe { memmap2::Mmap::map(&File::open(path)?)? });
+        #[cfg(not(feature = "mmap"))]
+        let bytes = DatabaseBytes::Owned(fs::read(path)?);
+
+        if bytes.len() != DATABASE_MAGIC.len() + CORNER_STATES.div_ceil(2) + 2 * EDGE_STATES.div_ceil(2)
+        || !bytes.starts_with(DATABASE_MAGIC)
+        {
+            return Err(CubusError::InvalidDatabase(path.to_string()));
+        }
+
+        Ok(PatternDatabases::with_bytes(bytes))
+
+    } /* ::open() */
+
+    /// Writes the pattern databases to the given file, creating its
+    /// directory if need be.
+    #[cfg(feature = "std")]
+    pub fn write_to_file (&self, path: &str)
+    -> Result<(), CubusError>
+    {
+        if let Some(dir) = Path::new(path).parent()
+        {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, &*self.bytes)?;
+
+        Ok(())
+
+    } /* .write_to_file() */
+
+    /// Tells whether the cube of the given corner permutation and twist and
+    /// edge slots takes more than `togo` face turns to get home by any of
+    /// the databases, looking at the next only if the last does not tell.
+    fn is_farther (&self, cperm: usize, twist: usize, slots: &[u8; 12], togo: usize)
+    -> bool
+    {
+        togo < self.nibble(0, 2187 * cperm + twist)
+     || togo < self.nibble(1, edge_slots_index(&slots[.. 6]))
+     || togo < self.nibble(2, edge_slots_index(&slots[6 ..]))
+
+    } /* .is_farther() */
+
+    /// Returns the entry `ind` of the corner database, for `table` 0, or of
+    /// the database of the first or last six edges, for 1 or 2.
+    fn nibble (&self, table: usize, ind: usize)
+    -> usize
+    {
+        let offset = match table
+        {
+            0   =>  DATABASE_MAGIC.len(),
+            1   =>  DATABASE_MAGIC.len() + CORNER_STATES.div_ceil(2),
+            _   =>  DATABASE_MAGIC.len() + CORNER_STATES.div_ceil(2) + EDGE_STATES.div_ceil(2)
+        };
+
+        (self.bytes[offset + ind / 2] >> (4 * (ind % 2)) & 0xF) as usize
+
+    } /* .nibble() */
+
+    /// Returns the fewest face turns that the cubies take to get home as
+    /// far as the databases tell, which is the most that any one says.
+    pub fn least_turns (&self, cubies: &CubieCube)
+    -> usize
+    {
+        let slots = edge_slots_of_cubies(cubies);
+
+        self.nibble(0, 2187 * perm_rank(&cubies.cp) + cubies.twist())
+            .max(self.nibble(1, edge_slots_index(&slots[.. 6])))
+            .max(self.nibble(2, edge_slots_index(&slots[6 ..])))
+
+    } /* .least_turns() */
+
+}   /* impl PatternDatabases */
+
+/// Estimates by the face turns that the databases tell, relative to the
+/// goal, for 3x3x3 cubes with their centers in place.  A move of a middle
+/// layer does the work of up to two face turns, so unless the cube's middle
+/// layers are all locked, that is halved, rounded up, to never exceed the
+/// moves.  Where MisplacedStickers tells more, or the databases nothing,
+/// its estimate counts instead.  Like searches, this expects cubes whose
+/// bricks check_bricks() accepts.
+impl Heuristic for PatternDatabases
+{
+    fn estimate (&self, cube: &Cube, goal: &Cube)
+    -> usize
+    {
+        let stickers = MisplacedStickers.estimate(cube, goal);
+        if cube.size != 3 || goal.size != 3
+        {
+            return stickers;
+        }
+
+        let (Some(cubies), Some(goalCubies)) = (cube.checked_to_cubies(), goal.checked_to_cubies())
+        else
+        {
+            return stickers;
+        };
+        let turns = self.least_turns(&goalCubies.inverse().compose(&cubies));
+        let isFaceOnly = ['X', 'Y', 'Z'].iter().all(|&axis| cube.locks.contains(&(axis, 1)));
+
+        stickers.max(if isFaceOnly { turns } else { turns.div_ceil(2) })
+    }
+
+}   /* impl Heuristic for PatternDatabases */
+
+
+/// Returns for each edge of the cubies its slot, twice the place that it
+/// is at plus its flip, as the edge databases index them.
+fn edge_slots_of_cubies (cubies: &CubieCube)
+-> [u8; 12]
+{
+    let mut slots = [0; 12];
+    for place in 0 .. 12
+    {
+        slots[cubies.ep[place] as usize] = (2 * place) as u8 + cubies.eo[place];
+    }
+
+    slots
+
+}   /* edge_slots_of_cubies() */
+
+
+/// Returns for each slot of an edge, twice the place that it is at plus
+/// its flip, the slot that each of the given `N` moves takes it to.
+fn edge_slot_moves<const N: usize> (moves: &[CubieCube])
+-> [[u8; N]; 24]
+{
+    // The edge that a move brings to a place came from the place that
+    // its cubies tell, flipped as they tell.
+    let mut slotMove = [[0; N]; 24];
+    for (mov, cubies) in moves.iter().enumerate()
+    {
+        for place in 0 .. 12
+        {
+            let from = cubies.ep[place] as usize;
+            for flip in 0 .. 2
+            {
+                slotMove[2 * from + flip][mov] = (2 * place) as u8 + (flip as u8 ^ cubies.eo[place]);
+            }
+        }
+    }
+
+    slotMove
+
+}   /* edge_slot_moves<N>() */
+
+
+/// Returns the index of some edges, six in an edge pattern database, of
+/// their slots: their places ranked among all arrangements, then their
+/// flips.
+fn edge_slots_index (slots: &[u8])
+-> usize
+{
+    let mut used: u16 = 0;
+    let mut rank = 0;
+    let mut flips = 0;
+    for (ind, &slot) in slots.iter().enumerate()
+    {
+        let place = slot / 2;
+        rank = rank * (12 - ind) + place as usize - (used & ((1 << place) - 1)).count_ones() as usize;
+        used |= 1 << place;
+        flips = 2 * flips + (slot % 2) as usize;
+    }
+
+    (rank << slots.len()) + flips
+
+}   /* edge_slots_index() */
+
+
+/// Returns the slots of `N` edges of the given index, see edge_slots_index().
+fn edge_slots_of_index<const N: usize> (ind: usize)
+-> [u8; N]
+{
+    let (mut rank, mut flips) = (ind >> N, ind % (1 << N));
+    let mut digits = [0; N];
+    for pos in (0 .. N).rev()
+    {
+        digits[pos] = rank % (12 - pos);
+        rank /= 12 - pos;
+    }
+
+    let mut unused: Vec<u8> = (0 .. 12).collect();
+    let mut slots = digits.map(|digit| 2 * unused.remove(digit));
+    for slot in slots.iter_mut().rev()
+    {
+        *slot += (flips % 2) as u8;
+        flips /= 2;
+    }
+
+    slots
+
+}   /* edge_slots_of_index<N>() */
+
+
+/// Fills a table of `count` nibbles, all 0xF to begin with, with how many
+/// moves each index takes at least to get to `home`, breadth first by the
+/// 18 indices that `step` gives for each.
+fn nibble_prune_table (table: &mut [u8], count: usize, home: usize, step: &dyn Fn(usize, &mut [usize; 18]))
+{
+    let get = |table: &[u8], ind: usize| table[ind / 2] >> (4 * (ind % 2)) & 0xF;
+    let set = |table: &mut [u8], ind: usize, depth: u8| table[ind / 2] &= depth << (4 * (ind % 2)) | 0xF0 >> (4 * (ind % 2));
+
+    set(table, home, 0);
+    let mut done = 1;
+    let mut depth = 0;
+    let mut next = [0; 18];
+    while done < count
+    {
+        for ind in 0 .. count
+        {
+            if get(table, ind) != depth
+            {
+                continue;
+            }
+            step(ind, &mut next);
+            for &later in next.iter()
+            {
+                if get(table, later) == 0xF
+                {
+                    set(table, later, depth + 1);
+                    done += 1;
+                }
+            }
+        }
+        depth += 1;
+    }
+
+}   /* nibble_prune_table() */
+
+
+/// The state of a search of solve_optimal().
+struct OptimalSearch<'a>
+{
+    databases: &'a PatternDatabases,
+    path:      Vec<usize>,          // Face turns so far.
+    moveNum:   u64,
+    cancel:    Option<&'a CancelToken>,
+    isCancelled: bool
+
+}   /* OptimalSearch */
+
+impl OptimalSearch<'_>
+{
+    /// Extends the path by `togo` face turns to the ordered cube.  Returns
+    /// true if it got there, with the path extended.
+    fn search (&mut self, cperm: usize, twist: usize, slots: &[u8; 12], togo: usize)
+    -> bool
+    {
+        self.moveNum += 1;
+        if self.cancel.is_some_and(|cancel| cancel.should_stop(self.moveNum))
+        {
+            self.isCancelled = true;
+        }
+        if self.isCancelled || self.databases.is_farther(cperm, twist, slots, togo)
+        {
+            return false;
+        }
+        if togo == 0
+        {
+            return true;
+        }
+
+        let databases = self.databases;
+        for mov in 0 .. 18
+        {
+            if !face_turn_may_follow(&self.path, mov)
+            {
+                continue;
+            }
+            self.path.push(mov);
+            if self.search(databases.cpermMove[cperm][mov] as usize, databases.twistMove[twist][mov] as usize,
+                           &slots.map(|slot| databases.slotMove[slot as usize][mov]), togo - 1)
+            {
+                return true;
+            }
+            self.path.pop();
+        }
+
+        false
+
+    } /* .search() */
+
+}   /* impl OptimalSearch */
+
+
+/// Returns a shortest solution of a 3x3x3 cube in face turns, of at most
+/// `maxLength` of them, by Korf's IDA* with the given pattern databases.
+/// Random cubes take 17 or 18 face turns, and searches that deep take
+/// minutes or more.  Returns None if there is no solution that short or
+/// the search was cancelled first, and the number of exploratory moves.
+/// A whole-cube rotation comes first if the centers are away from their
+/// places.
+pub fn solve_optimal (cube: &Cube, databases: &PatternDatabases, maxLength: usize, cancel: Option<&CancelToken>)
+-> Result<(Option<Vec<Move>>, u64), CubusError>
+{
+    cube.check_reachable(&Cube::new(3))?;
+    let (rotation, start) = cubies_with_centers_home(cube)?;
+
+    let slots = edge_slots_of_cubies(&start);
+    let (cperm, twist) = (perm_rank(&start.cp), start.twist());
+
+    let mut search = OptimalSearch { databases, path: vec![], moveNum: 0, cancel, isCancelled: false };
+    for length in 0 ..= maxLength
+    {
+        if search.search(cperm, twist, &slots, length)
+        {
+            return Ok((Some([rotation, movevec_of_face_turns(&search.path)].concat()), search.moveNum));
+        }
+        if search.isCancelled
+        {
+            break;
+        }
+    }
+
+    Ok((None, search.moveNum))
+
+}   /* solve_optimal() */
+
+
+/// A step of a solve, with the moves that it takes.
+#[derive(Clone)]
+pub struct SolveStep
+{
+    pub name:  String,
+    pub note:  String,              // What the step does, in a line.
+    pub moves: Vec<Move>
+
+}   /* SolveStep */
+
+
+/// A phase of a solving method, as solve_phases() runs them one after the
+/// other: it brings the stickers of its goal home, making only the moves
+/// that it may.  Implement it for the phases of a method of your own, or
+/// read them from a file by phases_of_json().
+pub trait Phase
+{
+    /// Returns the name of the phase.
+    fn name (&self)
+    -> &str;
+
+    /// Returns what the phase does, in a line.
+    fn note (&self)
+    -> &str;
+
+    /// Returns which stickers the phase brings home, as sticker_mask()
+    /// marks them.
+    fn goal (&self)
+    -> &[bool];
+
+    /// Returns the moves that the phase may make.
+    fn moves (&self)
+    -> &[Move];
+
+    /// Returns whether the cube meets the goal of the phase: by default,
+    /// whether the stickers of goal() show the colors of the ordered cube.
+    fn is_done (&self, cube: &Cube)
+    -> bool
+    {
+        stickers_home(cube, self.goal())
+    }
+
+    /// Returns moves, among moves() and whole-cube rotations, that take
+    /// the cube to the goal.
+    fn solve (&self, cube: &Cube)
+    -> Result<Vec<Move>, CubusError>;
+
+}   /* Phase */
+
+
+/// Tells whether the stickers of the mask show the colors of the ordered
+/// cube of the same size.
+fn stickers_home (cube: &Cube, mask: &[bool])
+-> bool
+{
+    Cube::new(cube.size).facelets().chars().zip(cube.facelets().chars()).zip(mask.iter())
+                        .all(|((home, chr), &isCovered)| !isCovered || chr == home)
+
+}   /* stickers_home() */
+
+
+/// Returns the solution of a cube by the given phases, one step for each,
+/// each phase solving the cube as those before it leave it.
+pub fn solve_phases (cube: &Cube, phases: &[Box<dyn Phase>])
+-> Result<Vec<SolveStep>, CubusError>
+{
+    let mut turned = cube.clone();
+    let mut steps: Vec<SolveStep> = vec![];
+    for phase in phases.iter()
+    {
+        let moves = phase.solve(&turned)?;
+        turned.apply_moves(&moves)?;
+        steps.push(SolveStep { name: phase.name().to_string(), note: phase.note().to_string(), moves });
+    }
+
+    Ok(steps)
+
+}   /* solve_phases() */
+
+
+/// Returns the moves of a 3x3x3 cube that phases may make of the given
+/// letters in Singmaster notation, e.g. "R U M", each turned both ways.
+fn phase_moves (letters: &str)
+-> Vec<Move>
+{
+    letters.split_whitespace().flat_map(|letter| movevec_of_singmaster(&format!("{0} {0}'", letter), 2).unwrap()).collect()
+
+}   /* phase_moves() */
+
+
+/// Returns the fewest moves of the phase, up to `depth`, that take the
+/// cube to its goal, by iterative deepening that never undoes a move or
+/// makes one a third time in a row.  None if there are none so few.
+pub fn phase_search (phase: &dyn Phase, cube: &Cube, depth: usize)
+-> Option<Vec<Move>>
+{
+    let moves = phase.moves();
+    let inverse: Vec<Option<usize>> = moves.iter().map(|mov| {
+        let undone = movevec_inverted(&[*mov]);
+        moves.iter().position(|other| *other == undone[0])
+    }).collect();
+
+    fn deepen (phase: &dyn Phase, cube: &Cube, inverse: &[Option<usize>], togo: usize, path: &mut Vec<usize>)
+    -> bool
+    {
+        if togo == 0
+        {
+            return phase.is_done(cube);
+        }
+        for ind in 0 .. inverse.len()
+        {
+            let last = path.last().copied();
+            if last.is_some_and(|last| inverse[last] == Some(ind)) || path.ends_with(&[ind, ind])
+            {
+                continue;
+            }
+            path.push(ind);
+            if deepen(phase, &cube.copy_with_checked_moves(&phase.moves()[ind ..= ind]), inverse, togo - 1, path)
+            {
+                return true;
+            }
+            path.pop();
+        }
+
+        false
+    }
+
+    let mut path: Vec<usize> = vec![];
+    (0 ..= depth).find(|&togo| deepen(phase, cube, &inverse, togo, &mut path))
+                 .map(|_| path.iter().map(|&ind| moves[ind]).collect())
+
+}   /* phase_search() */
+
+
+/// A phase of a method of one's own, as phases_of_json() reads them, that
+/// finds its moves by phase_search().
+pub struct CustomPhase
+{
+    name:  String,
+    note:  String,
+    goal:  Vec<bool>,
+    moves: Vec<Move>,
+    depth: usize,                   // The most moves that it searches.
+    size:  Coord
+
+}   /* CustomPhase */
+
+impl CustomPhase
+{
+    /// CustomPhase constructor, for a cube of the given size, of the goal
+    /// in the terms of sticker_mask() and the moves it may make in
+    /// Singmaster notation, which are also made the other way.
+    pub fn new (name: &str, note: &str, goal: &str, moves: &str, depth: usize, size: Coord)
+    -> Result<CustomPhase, CubusError>
+    {
+        let mut both = movevec_of_singmaster(moves, size - 1)?;
+        both.extend(movevec_inverted(&both));
+        let mut distinct: Vec<Move> = vec![];
+        for mov in both
+        {
+            if !distinct.contains(&mov)
+            {
+                distinct.push(mov);
+            }
+        }
+
+        Ok(CustomPhase { name: name.to_string(), note: note.to_string(), goal: sticker_mask(goal, size)?,
+                         moves: distinct, depth, size })
+
+    } /* ::new() */
+
+}   /* impl CustomPhase */
+
+impl Phase for CustomPhase
+{
+    fn name (&self)
+    -> &str
+    {
+        &self.name
+    }
+
+    fn note (&self)
+    -> &str
+    {
+        &self.note
+    }
+
+    fn goal (&self)
+    -> &[bool]
+    {
+        &self.goal
+    }
+
+    fn moves (&self)
+    -> &[Move]
+    {
+        &self.moves
+    }
+
+    fn solve (&self, cube: &Cube)
+    -> Result<Vec<Move>, CubusError>
+    {
+        if cube.size != self.size
+        {
+            return Err(CubusError::SizeMismatch(cube.size, self.size));
+        }
+        phase_search(self, cube, self.depth).ok_or_else(|| CubusError::PhaseFailed(self.name.clone()))
+    }
+
+}   /* impl Phase for CustomPhase */
+
+
+/// Returns the phases of a method of one's own in JSON, for a cube of the
+/// given size: an array of objects, each with a `name`, a `goal` in the
+/// terms of sticker_mask() and the `moves` it may make in Singmaster
+/// notation, and optionally a `note` and the `depth` it searches to, by
+/// default 6, e.g. `[{"name": "Cross", "goal": "D, DF, DR, DB, DL",
+/// "moves": "R L F B D", "depth": 7}]`.
+pub fn phases_of_json (text: &str, size: Coord)
+-> Result<Vec<Box<dyn Phase>>, CubusError>
+{
+    let mut reader = JsonReader { chars: text.chars().peekable(), position: 1 };
+    let mut phases: Vec<Box<dyn Phase>> = vec![];
+    reader.expect('[')?;
+    while reader.has_next(']', phases.is_empty())?
+    {
+        reader.expect('{')?;
+        let (mut name, mut note, mut goal, mut moves, mut depth) = (None, String::new(), None, None, 6);
+        let mut isFirst = true;
+        while reader.has_next('}', isFirst)?
+        {
+            let key = reader.string()?;
+            reader.expect(':')?;
+            match key.as_str()
+            {
+                "depth"     =>  depth = reader.number()?,
+                "name"      =>  name = Some(reader.string()?),
+                "note"      =>  note = reader.string()?,
+                "goal"      =>  goal = Some(reader.string()?),
+                "moves"     =>  moves = Some(reader.string()?),
+                _           =>  { reader.string()?; }
+            }
+            isFirst = false;
+        }
+        match (name, goal, moves)
+        {
+            (Some(name), Some(goal), Some(moves))
+                    =>  phases.push(Box::new(CustomPhase::new(&name, &note, &goal, &moves, depth, size)?)),
+            _       =>  return Err(CubusError::Unexpected(reader.position - 1, Some('}'),
+                                                          "a \"name\", a \"goal\" and a \"moves\" member before".to_string()))
+        }
+    }
+    if reader.peek().is_some()
+    {
+        return Err(reader.unexpected("the end"));
+    }
+
+    Ok(phases)
+
+}   /* phases_of_json() */
+
+
+/// The steps of solve_layer_by_layer(), each with what it does.
+const LBL_STEPS: [(&str, &str); 7] = [
+    ("Cross",           "Hold white down and bring its edges around it, each below the side center of its color."),
+    ("First layer",     "Bring each white corner above its slot and repeat R U R' U' there until it sits in place."),
+    ("Second layer",    "Bring each middle edge above the center of its front color and insert it with U R U' R' U' F' U F or its mirror."),
+    ("Top cross",       "Flip the top edges up with F R U R' U' F', holding a line left to right or an angle at the back left."),
+    ("Top face",        "Turn the top corners up with R U R' U R U2 R', turning the top face in between."),
+    ("Top corners",     "Cycle the top corners into place with R' F R' B2 R F' R' B2 R2, a placed corner at the front left."),
+    ("Top edges",       "Cycle the top edges into place with R2 U R U R' U' R' U' R' U R', a placed edge at the back.")
+];
+
+
+/// Returns the face turns, as indexed by the two-phase moves, of face
+/// moves in Singmaster notation such as R U2 F'.
+fn face_turns_of_singmaster (text: &str)
+-> Vec<usize>
+{
+    text.split_whitespace().map(|token| {
+        let mut chars = token.chars();
+        let face = chars.next().and_then(|letter| TWO_PHASE_FACES.iter().position(|&known| known == letter)).unwrap();
+        3 * face + match chars.next()
+        {
+            None        =>  0,
+            Some('2')   =>  1,
+            _           =>  2
+        }
+    }).collect()
+
+}   /* face_turns_of_singmaster() */
+
+
+/// Returns the face turns of the given ones for the cube turned `quarters`
+/// times about the Up axis, which takes the front face to the right: the
+/// algorithm of the front right slot for the others in turn.
+fn face_turns_turned (turns: &[usize], quarters: usize)
+-> Vec<usize>
+{
+    // U R F D L B after a quarter turn.
+    let next = [0, 5, 1, 3, 2, 4];
+
+    turns.iter().map(|&turn| 3 * (0 .. quarters).fold(turn / 3, |face, _| next[face]) + turn % 3).collect()
+
+}   /* face_turns_turned() */
+
+
+/// Returns the given face turns with turns of the same face in a row
+/// merged, as where one macro of solve_layer_by_layer() ends and the next
+/// begins.  Turns of M, as solve_roux() numbers them, merge alike.
+fn face_turns_merged (turns: &[usize])
+-> Vec<usize>
+{
+    let mut merged: Vec<usize> = vec![];
+    for &turn in turns
+    {
+        match merged.last()
+        {
+            Some(&last) if last / 3 == turn / 3
+                    =>  {
+                merged.pop();
+                let quarters = (last % 3 + turn % 3 + 2) % 4;
+                if quarters != 0
+                {
+                    merged.push(turn / 3 * 3 + quarters - 1);
+                }
+            },
+            _       =>  merged.push(turn)
+        }
+    }
+
+    merged
+
+}   /* face_turns_merged() */
+
+
+/// Searches for the shortest sequence of at most `depth` macros, each a
+/// series of face turns, that leads from the start to the goal, and returns
+/// its face turns.
+fn macro_search (start: &CubieCube, macros: &[Vec<usize>], depth: usize, goal: &dyn Fn(&CubieCube) -> bool)
+-> Option<Vec<usize>>
+{
+    let moves = face_turn_cubies();
+    let effects: Vec<CubieCube> = macros.iter().map(|turns| turns.iter().fold(CubieCube::new(), |cubies, &turn| cubies.compose(&moves[turn])))
+                                        .collect();
+
+    fn deepen (cubies: &CubieCube, effects: &[CubieCube], togo: usize, path: &mut Vec<usize>, goal: &dyn Fn(&CubieCube) -> bool)
+    -> bool
+    {
+        if togo == 0
+        {
+            return goal(cubies);
+        }
+        for (ind, effect) in effects.iter().enumerate()
+        {
+            path.push(ind);
+            if deepen(&cubies.compose(effect), effects, togo - 1, path, goal)
+            {
+                return true;
+            }
+            path.pop();
+        }
+
+        false
+    }
+
+    let mut path: Vec<usize> = vec![];
+    (0 ..= depth).find(|&togo| deepen(start, &effects, togo, &mut path, goal))
+                 .map(|_| path.iter().flat_map(|&ind| macros[ind].iter().copied()).collect())
+
+}   /* macro_search() */
+
+
+/// Returns the macros of solve_layer_by_layer() for the given algorithms
+/// of the front right slot: each for all four slots, `powers` times in a
+/// row up to that many, or else each after a turn of the top face or none,
+/// and the turns of the top face alone.
+fn lbl_macros (algorithms: &[&str], powers: usize, bySlot: bool)
+-> Vec<Vec<usize>>
+{
+    let mut macros: Vec<Vec<usize>> = vec![vec![0], vec![1], vec![2]];
+    for turns in algorithms.iter().map(|algorithm| face_turns_of_singmaster(algorithm))
+    {
+        for quarters in 0 .. 4
+        {
+            if bySlot
+            {
+                let turned = face_turns_turned(&turns, quarters);
+                macros.extend((1 ..= powers).map(|power| turned.repeat(power)));
+            }
+            else
+            {
+                let setup: &[usize] = if quarters == 0 { &[] } else { &[quarters - 1] };
+                macros.push([setup, &turns].concat());
+            }
+        }
+    }
+
+    macros
+
+}   /* lbl_macros() */
+
+
+/// Returns the cubies of a 3x3x3 cube with each sticker named by the face
+/// whose center has its color, so that the cubies follow the centers
+/// wherever the cube turns them.
+fn cubies_by_centers (cube: &Cube)
+-> Result<CubieCube, CubusError>
+{
+    let colors: Vec<char> = cube.facelets().chars().collect();
+    let faceOf = |color: char| "URFDLB".chars().nth((0 .. 6).position(|face| colors[9 * face + 4] == color).unwrap()).unwrap();
+
+    Cube::from_facelet_string(&colors.iter().map(|&color| faceOf(color)).collect::<String>())?.to_cubies()
+        .ok_or(CubusError::InvalidBricks)
+
+}   /* cubies_by_centers() */
+
+
+/// Tells whether the cubies meet the goal of the step of LBL_STEPS with
+/// the given index and those of all steps before it.
+fn lbl_step_done (cubies: &CubieCube, step: usize)
+-> bool
+{
+    let edgeOk = |edge: usize| cubies.ep[edge] as usize == edge && cubies.eo[edge] == 0;
+    let cornerOk = |corner: usize| cubies.cp[corner] as usize == corner && cubies.co[corner] == 0;
+
+    (0 ..= step).all(|part| match part
+    {
+        0   =>  (4 .. 8).all(edgeOk),
+        1   =>  (4 .. 8).all(cornerOk),
+        2   =>  (8 .. 12).all(edgeOk),
+        3   =>  cubies.eo[.. 4] == [0; 4],
+        4   =>  cubies.co[.. 4] == [0; 4],
+        5   =>  cubies.cp[.. 4] == [0, 1, 2, 3],
+        _   =>  cubies.is_solved()
+    })
+
+}   /* lbl_step_done() */
+
+
+/// Returns the face turns of the cross of solve_layer_by_layer(), found by
+/// IDA* bounded by how far its four edges alone are from home.
+fn lbl_cross_turns (cubies: &CubieCube)
+-> Vec<usize>
+{
+    let moves = face_turn_cubies();
+    let slotMove = edge_slot_moves(&moves);
+    let crossTable = || {
+        let crossMove: Vec<[u32; 18]> = (0 .. 11880 * 16).map(|ind| {
+            let slots = edge_slots_of_index::<4>(ind);
+            core::array::from_fn(|mov| edge_slots_index(&slots.map(|slot| slotMove[slot as usize][mov])) as u32)
+        }).collect();
+        prune_table(11880 * 16, edge_slots_index(&[8, 10, 12, 14]), 18, &|ind, mov| crossMove[ind][mov] as usize)
+    };
+    #[cfg(feature = "std")]
+    let distances = {
+        static CROSS_DISTANCES: OnceLock<Vec<u8>> = OnceLock::new();
+        CROSS_DISTANCES.get_or_init(crossTable)
+    };
+    #[cfg(not(feature = "std"))]
+    let distances = &crossTable();
+    let mut slots = [0; 4];
+    for place in 0 .. 12
+    {
+        if (4 .. 8).contains(&cubies.ep[place])
+        {
+            slots[cubies.ep[place] as usize - 4] = (2 * place) as u8 + cubies.eo[place];
+        }
+    }
+    fn deepen (slots: &[u8; 4], slotMove: &[[u8; 18]; 24], distances: &[u8], togo: usize, path: &mut Vec<usize>)
+    -> bool
+    {
+        let distance = distances[edge_slots_index(slots)] as usize;
+        if distance > togo
+        {
+            return false;
+        }
+        if togo == 0
+        {
+            return true;
+        }
+        for turn in 0 .. 18
+        {
+            if !face_turn_may_follow(path, turn)
+            {
+                continue;
+            }
+            path.push(turn);
+            if deepen(&slots.map(|slot| slotMove[slot as usize][turn]), slotMove, distances, togo - 1, path)
+            {
+                return true;
+            }
+            path.pop();
+        }
+
+        false
+    }
+    let mut crossTurns: Vec<usize> = vec![];
+    let mut togo = 0;
+    while !deepen(&slots, &slotMove, distances, togo, &mut crossTurns)
+    {
+        togo += 1;
+    }
+
+    crossTurns
+
+}   /* lbl_cross_turns() */
+
+
+/// The algorithms of the last-layer steps of LBL_STEPS.
+const LBL_ALGORITHMS: [&str; 4] = ["F R U R' U' F'", "R U R' U R U2 R'", "R' F R' B2 R F' R' B2 R2", "R2 U R U R' U' R' U' R' U R'"];
+
+
+/// Returns the face turns of the step of LBL_STEPS with the given index
+/// from the cubies, which meet the goals of the steps before it: the
+/// cross as short as can be, the first-layer corners and the middle edges
+/// one at a time, the one that takes the fewest turns first, and the last
+/// layer by the algorithms of the steps, with turns of the top face
+/// between.
+fn lbl_step_turns (cubies: &CubieCube, step: usize)
+-> Vec<usize>
+{
+    let moves = face_turn_cubies();
+    let apply = |cubies: &CubieCube, turns: &[usize]| turns.iter().fold(*cubies, |cubies, &turn| cubies.compose(&moves[turn]));
+
+    match step
+    {
+        0       =>  lbl_cross_turns(cubies),
+        1 | 2   =>  {
+            let isOk = |cubies: &CubieCube, piece: usize| lbl_step_done(cubies, step - 1) && match step
+            {
+                1   =>  cubies.cp[piece] as usize == piece && cubies.co[piece] == 0,
+                _   =>  cubies.ep[piece] as usize == piece && cubies.eo[piece] == 0
+            };
+            let (pieces, macros) = match step
+            {
+                1   =>  ([4, 5, 6, 7], lbl_macros(&["R U R' U'"], 5, true)),
+                _   =>  ([8, 9, 10, 11], lbl_macros(&["U R U' R' U' F' U F", "U' L' U L U F U' F'"], 1, true))
+            };
+            let mut cubies = *cubies;
+            let mut turns: Vec<usize> = vec![];
+            loop
+            {
+                let done: Vec<usize> = pieces.iter().copied().filter(|&piece| isOk(&cubies, piece)).collect();
+                let found = pieces.iter().filter(|piece| !done.contains(piece)).filter_map(|&piece| {
+                    macro_search(&cubies, &macros, 3, &|later| lbl_step_done(later, 0) && done.iter().chain([&piece]).all(|&known| isOk(later, known)))
+                }).min_by_key(Vec::len);
+                match found
+                {
+                    Some(found) =>  {
+                        cubies = apply(&cubies, &found);
+                        turns.extend(found);
+                    },
+                    None        =>  return turns
+                }
+            }
+        },
+        _       =>  macro_search(cubies, &lbl_macros(&[LBL_ALGORITHMS[step - 3]], 1, false), 4, &|later| lbl_step_done(later, step)).unwrap()
+    }
+
+}   /* lbl_step_turns() */
+
+
+/// A step of LBL_STEPS as a Phase.  The steps work on the cubies as the
+/// centers name them, see cubies_by_centers(), the first after a rotation
+/// that brings the white center down, found as short as can be.
+struct LblPhase
+{
+    step:  usize,                   // Index in LBL_STEPS.
+    goal:  Vec<bool>,
+    moves: Vec<Move>
+
+}   /* LblPhase */
+
+impl Phase for LblPhase
+{
+    fn name (&self)
+    -> &str
+    {
+        LBL_STEPS[self.step].0
+    }
+
+    fn note (&self)
+    -> &str
+    {
+        LBL_STEPS[self.step].1
+    }
+
+    fn goal (&self)
+    -> &[bool]
+    {
+        &self.goal
+    }
+
+    fn moves (&self)
+    -> &[Move]
+    {
+        &self.moves
+    }
+
+    /// Tells whether the white center is down and the stickers of the goal
+    /// show the colors of the centers of their faces.
+    fn is_done (&self, cube: &Cube)
+    -> bool
+    {
+        cube.sticker_at(Face::Down, 1, 1) == Some(Huename::WT)
+            && cubies_by_centers(cube).is_ok_and(|cubies| lbl_step_done(&cubies, self.step))
+    }
+
+    fn solve (&self, cube: &Cube)
+    -> Result<Vec<Move>, CubusError>
+    {
+        cube.check_reachable(&Cube::new(3))?;
+        let (rotation, turned) = match self.step
+        {
+            0   =>  shortest_rotation(cube, &|turned| turned.sticker_at(Face::Down, 1, 1) == Some(Huename::WT))?,
+            _   =>  (vec![], cube.clone())
+        };
+        let cubies = cubies_by_centers(&turned)?;
+        if self.step > 0 && !lbl_step_done(&cubies, self.step - 1)
+        {
+            return Err(CubusError::PhaseFailed(self.name().to_string()));
+        }
+
+        Ok([rotation, movevec_of_face_turns(&face_turns_merged(&lbl_step_turns(&cubies, self.step)))].concat())
+    }
+
+}   /* impl Phase for LblPhase */
+
+
+/// Returns the phases of solve_layer_by_layer(), one for each step of
+/// LBL_STEPS.  Their goals count the stickers as the centers name them.
+pub fn layer_by_layer_phases ()
+-> Vec<Box<dyn Phase>>
+{
+    let goals = ["D, F, R, B, L, DF, DR, DB, DL", "D, F, R, B, L, DF, DR, DB, DL, DFR, DRB, DBL, DLF", "F2L", "F2L", "OLL",
+                 "OLL, UFR, URB, UBL, ULF", "LL"];
+
+    goals.iter().enumerate().map(|(step, goal)| {
+        let mut goal = sticker_mask(goal, 3).unwrap();
+        if step == 3
+        {
+            // The top face's center and the top stickers of its edges.
+            for ind in [1, 3, 4, 5, 7]
+            {
+                goal[ind] = true;
+            }
+        }
+        let moves = phase_moves(if step == 0 { "R L U D F B x y z" } else { "R L U D F B" });
+        Box::new(LblPhase { step, goal, moves }) as Box<dyn Phase>
+    }).collect()
+
+}   /* layer_by_layer_phases() */
+
+
+/// Returns the solution of a 3x3x3 cube by the layer-by-layer method that
+/// beginners learn, in the steps of LBL_STEPS, by solve_phases() with
+/// layer_by_layer_phases(): a rotation that brings the white center down
+/// and the cross around it, found as short as can be, then the first-layer
+/// corners and the middle edges one at a time, and the last layer by the
+/// algorithms of the steps, with turns of the top face between.  Steps
+/// that there is nothing to do for are left empty.
+pub fn solve_layer_by_layer (cube: &Cube)
+-> Result<Vec<SolveStep>, CubusError>
+{
+    solve_phases(cube, &layer_by_layer_phases())
+
+}   /* solve_layer_by_layer() */
+
+
+/// How much of a HintPlan a hint reveals.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum HintLevel
+{
+    NextMove,                       // The move to make next.
+    NextStep,                       // The moves to the end of the current step.
+    Solution                        // All moves left.
+
+}   /* HintLevel */
+
+
+/// A solution in steps, revealed a little at a time as hints while the
+/// cube is solved by hand.  The plan knows the state after every prefix of
+/// its moves, so it follows the cube by state rather than by the moves
+/// played: U' U' meets a U2 of the plan, undoing goes back along it, and
+/// moves off it leave the plan waiting until the cube is back on it or a
+/// new plan is made from where it is.  What hints revealed stays revealed,
+/// and is what remains of it ahead of the cube.
+#[derive(Clone)]
+pub struct HintPlan
+{
+    steps:    Vec<SolveStep>,
+    states:   Vec<String>,          // The facelets after none, one, ... all moves of the steps.
+    done:     Option<usize>,        // Moves of the plan that the cube is at, None off the plan.
+    revealed: usize                 // Moves of the plan revealed by hints, from its start.
+
+}   /* HintPlan */
+
+impl HintPlan
+{
+    /// HintPlan constructor, of the given steps that solve the given cube,
+    /// such as those of solve_layer_by_layer().
+    pub fn new (cube: &Cube, steps: Vec<SolveStep>)
+    -> Result<HintPlan, CubusError>
+    {
+        let mut turned = cube.clone();
+        let mut states = vec![turned.facelets()];
+        for mov in steps.iter().flat_map(|step| step.moves.iter())
+        {
+            turned.apply_move(mov)?;
+            states.push(turned.facelets());
+        }
+
+        Ok(HintPlan { steps, states, done: Some(0), revealed: 0 })
+
+    } /* ::new() */
+
+    /// Returns the number of moves of the plan.
+    pub fn len (&self)
+    -> usize
+    {
+        self.states.len() - 1
+
+    } /* .len() */
+
+    /// Returns whether the plan has no moves, its cube solved already.
+    pub fn is_empty (&self)
+    -> bool
+    {
+        self.len() == 0
+
+    } /* .is_empty() */
+
+    /// Returns the number of moves of the plan that the cube is at, None
+    /// if it is off the plan.
+    pub fn done (&self)
+    -> Option<usize>
+    {
+        self.done
+
+    } /* .done() */
+
+    /// Follows the cube to the state it is in now: to the prefix of the
+    /// plan that leads there, the one nearest to where the cube was if the
+    /// plan passes a state twice, or off the plan.  Returns whether the
+    /// cube is on the plan.
+    pub fn follow (&mut self, cube: &Cube)
+    -> bool
+    {
+        let facelets = cube.facelets();
+        let near = self.done.unwrap_or(0);
+        self.done = self.states.iter().enumerate().filter(|(_, state)| **state == facelets)
+                        .map(|(ind, _)| ind).min_by_key(|&ind| ind.abs_diff(near));
+
+        self.done.is_some()
+
+    } /* .follow() */
+
+    /// Reveals the moves that the given level tells beyond the cube, and
+    /// returns all those revealed ahead of it, as revealed() does.  Off the
+    /// plan it reveals nothing.
+    pub fn reveal (&mut self, level: HintLevel)
+    -> Vec<(&str, Vec<Move>)>
+    {
+        if let Some(done) = self.done
+        {
+            let mut end = 0;
+            let stepEnd = self.steps.iter().map(|step| { end += step.moves.len(); end }).find(|&after| after > done);
+            let target = match level
+            {
+                HintLevel::NextMove =>  done + 1,
+                HintLevel::NextStep =>  stepEnd.unwrap_or(done),
+                HintLevel::Solution =>  self.len()
+            };
+            self.revealed = self.revealed.max(target.min(self.len()));
+        }
+
+        self.revealed()
+
+    } /* .reveal() */
+
+    /// Returns the revealed moves that the cube has yet to make, in the
+    /// steps they belong to, each with its name.  Empty off the plan, or
+    /// when the cube has caught up with the hints.
+    pub fn revealed (&self)
+    -> Vec<(&str, Vec<Move>)>
+    {
+        let done = match self.done
+        {
+            Some(done)  =>  done,
+            None        =>  return vec![]
+        };
+
+        let mut start = 0;
+        let mut parts = vec![];
+        for step in self.steps.iter()
+        {
+            let (from, to) = (done.max(start), self.revealed.min(start + step.moves.len()));
+            if from < to
+            {
+                parts.push((step.name.as_str(), step.moves[from - start .. to - start].to_vec()));
+            }
+            start += step.moves.len();
+        }
+
+        parts
+
+    } /* .revealed() */
+
+}   /* impl HintPlan */
+
+
+/// The steps of solve_roux(), each with what it does.
+const ROUX_STEPS: [(&str, &str); 4] = [
+    ("First block",     "Build a 1x2x3 block on the left: the left center with the two corners and three edges below and beside it."),
+    ("Second block",    "Build the same block on the right with R, U and M turns only, which leave the first block alone."),
+    ("CMLL",            "Solve the top corners, whatever their edges do: turn them up with R U R' U R U2 R', then place them with R' F R' B2 R F' R' B2 R2."),
+    ("LSE",             "Solve the last six edges and the centers between the blocks with M and U turns only.")
+];
+
+/// The index of the slice turn M, after those of the 18 face turns, and
+/// of M2 and M' after it.
+const ROUX_M: usize = 18;
+
+/// The places of the last six edges of a Roux solve: UR, UF, UL, UB, DF
+/// and DB.
+const LSE_PLACES: [usize; 6] = [0, 1, 2, 3, 5, 7];
+
+/// The turns of the last six edges, U and M each way.
+const LSE_TURNS: [usize; 6] = [0, 1, 2, ROUX_M, ROUX_M + 1, ROUX_M + 2];
+
+
+/// Returns the cubies of the 18 face turns and of M, M2 and M', which turn
+/// the slice between the left and right faces as L does.  As M carries
+/// centers along, pieces are where they are on the cube held still, not
+/// relative to its centers.
+fn roux_turn_cubies ()
+-> Vec<CubieCube>
+{
+    // UB to UF, UF to DF, DF to DB and DB to UB, each flipped.
+    let mut slice = CubieCube::new();
+    for (place, from) in [(1, 3), (5, 1), (7, 5), (3, 7)]
+    {
+        slice.ep[place] = from;
+        slice.eo[place] = 1;
+    }
+    let half = slice.compose(&slice);
+
+    [face_turn_cubies(), vec![slice, half, half.compose(&slice)]].concat()
+
+}   /* roux_turn_cubies() */
+
+
+/// Returns the moves of the given turns, as indexed by roux_turn_cubies().
+fn movevec_of_roux_turns (turns: &[usize])
+-> Vec<Move>
+{
+    let mut moves: Vec<Move> = vec![];
+    for &turn in turns
+    {
+        if turn < ROUX_M
+        {
+            moves.extend(movevec_of_face_turns(&[turn]));
+            continue;
+        }
+        let slice = movevec_of_face('M', 1, 1, 2).unwrap();
+        moves.extend(match turn - ROUX_M
+        {
+            0   =>  slice,
+            1   =>  [slice.clone(), slice].concat(),
+            _   =>  movevec_inverted(&slice)
+        });
+    }
+
+    moves
+
+}   /* movevec_of_roux_turns() */
+
+
+/// Tells whether the turn `turn` may follow the path of turns, as indexed
+/// by roux_turn_cubies(), as face_turn_may_follow() tells, and of M and R
+/// or L, which commute, only R or L before M.
+fn roux_turn_may_follow (path: &[usize], turn: usize)
+-> bool
+{
+    match path.last()
+    {
+        Some(&last) if last >= ROUX_M
+                    =>  turn < ROUX_M && turn / 3 != 1 && turn / 3 != 4,
+        Some(_) if turn >= ROUX_M
+                    =>  true,
+        _           =>  face_turn_may_follow(path, turn)
+    }
+
+}   /* roux_turn_may_follow() */
+
+
+/// Returns for each slot of a corner, three times the place that it is at
+/// plus its twist, the slot that each of the given `N` moves takes it to.
+fn corner_slot_moves<const N: usize> (moves: &[CubieCube])
+-> [[u8; N]; 24]
+{
+    let mut slotMove = [[0; N]; 24];
+    for (mov, cubies) in moves.iter().enumerate()
+    {
+        for place in 0 .. 8
+        {
+            let from = cubies.cp[place] as usize;
+            for twist in 0 .. 3
+            {
+                slotMove[3 * from + twist][mov] = (3 * place) as u8 + (twist as u8 + cubies.co[place]) % 3;
+            }
+        }
+    }
+
+    slotMove
+
+}   /* corner_slot_moves<N>() */
+
+
+/// Returns the index of two corners of their slots: the slot of the first,
+/// then the place of the second among the seven others and its twist.
+fn corner_pair_index (slots: &[u8; 2])
+-> usize
+{
+    let (first, second) = (slots[0] / 3, slots[1] / 3);
+
+    21 * slots[0] as usize + 3 * (second - (second > first) as u8) as usize + (slots[1] % 3) as usize
+
+}   /* corner_pair_index() */
+
+
+/// Returns the slots of two corners of the given index, see
+/// corner_pair_index().
+fn corner_pair_of_index (ind: usize)
+-> [u8; 2]
+{
+    let first = (ind / 21) as u8;
+    let rank = (ind % 21 / 3) as u8;
+    let second = rank + (rank >= first / 3) as u8;
+
+    [first, 3 * second + (ind % 3) as u8]
+
+}   /* corner_pair_of_index() */
+
+
+/// The move and pruning tables of a block of two corners and three edges
+/// that solve_roux() builds by the given turns.
+struct BlockTables
+{
+    turns:       Vec<usize>,    // As indexed by roux_turn_cubies().
+    cornerMove:  Vec<u16>,      // The corner index after each turn.
+    edgeMove:    Vec<u16>,      // The edge index after each turn.
+    cornerPrune: Vec<u8>,
+    edgePrune:   Vec<u8>
+
+}   /* BlockTables */
+
+impl BlockTables
+{
+    /// BlockTables constructor.
+    fn new (corners: [u8; 2], edges: [u8; 3], turns: Vec<usize>)
+    -> BlockTables
+    {
+        let moves = roux_turn_cubies();
+        let cornerSlotMove: [[u8; 21]; 24] = corner_slot_moves(&moves);
+        let edgeSlotMove: [[u8; 21]; 24] = edge_slot_moves(&moves);
+        let turnNum = turns.len();
+
+        let cornerMove: Vec<u16> = (0 .. 24 * 21).flat_map(|ind| {
+            let slots = corner_pair_of_index(ind);
+            turns.iter().map(move |&turn| corner_pair_index(&slots.map(|slot| cornerSlotMove[slot as usize][turn])) as u16)
+        }).collect();
+        let edgeMove: Vec<u16> = (0 .. 1320 * 8).flat_map(|ind| {
+            let slots = edge_slots_of_index::<3>(ind);
+            turns.iter().map(move |&turn| edge_slots_index(&slots.map(|slot| edgeSlotMove[slot as usize][turn])) as u16)
+        }).collect();
+
+        let cornerHome = corner_pair_index(&corners.map(|corner| 3 * corner));
+        let edgeHome = edge_slots_index(&edges.map(|edge| 2 * edge));
+        let cornerPrune = prune_table(24 * 21, cornerHome, turnNum, &|ind, mov| cornerMove[ind * turnNum + mov] as usize);
+        let edgePrune = prune_table(1320 * 8, edgeHome, turnNum, &|ind, mov| edgeMove[ind * turnNum + mov] as usize);
+
+        BlockTables { turns, cornerMove, edgeMove, cornerPrune, edgePrune }
+
+    } /* ::new() */
+
+    /// Returns the fewest turns that bring the corners and edges of the
+    /// block home from where the cubies have them, by IDA* bounded by how
+    /// far the corners alone and the edges alone are from home.
+    fn solve (&self, cubies: &CubieCube, corners: [u8; 2], edges: [u8; 3])
+    -> Vec<usize>
+    {
+        let cornerSlot = |corner: u8| {
+            let place = cubies.cp.iter().position(|&piece| piece == corner).unwrap();
+            (3 * place) as u8 + cubies.co[place]
+        };
+        let edgeSlot = |edge: u8| {
+            let place = cubies.ep.iter().position(|&piece| piece == edge).unwrap();
+            (2 * place) as u8 + cubies.eo[place]
+        };
+
+        fn deepen (tables: &BlockTables, corner: usize, edge: usize, togo: usize, path: &mut Vec<usize>)
+        -> bool
+        {
+            if togo < (tables.cornerPrune[corner] as usize).max(tables.edgePrune[edge] as usize)
+            {
+                return false;
+            }
+            if togo == 0
+            {
+                return true;
+            }
+            let turnNum = tables.turns.len();
+            for (ind, &turn) in tables.turns.iter().enumerate()
+            {
+                if !roux_turn_may_follow(path, turn)
+                {
+                    continue;
+                }
+                path.push(turn);
+                if deepen(tables, tables.cornerMove[corner * turnNum + ind] as usize,
+                          tables.edgeMove[edge * turnNum + ind] as usize, togo - 1, path)
+                {
+                    return true;
+                }
+                path.pop();
+            }
+
+            false
+        }
+
+        let corner = corner_pair_index(&corners.map(cornerSlot));
+        let edge = edge_slots_index(&edges.map(edgeSlot));
+        let mut path: Vec<usize> = vec![];
+        let mut togo = 0;
+        while !deepen(self, corner, edge, togo, &mut path)
+        {
+            togo += 1;
+        }
+
+        path
+
+    } /* .solve() */
+
+}   /* impl BlockTables */
+
+
+/// The tables of solve_roux(): those of both blocks, and how many turns
+/// of LSE_TURNS each state of the last six edges, see lse_index(), takes
+/// at least to get home.
+struct RouxTables
+{
+    firstBlock:   BlockTables,
+    secondBlock:  BlockTables,
+    lseMove:      Vec<[u16; 6]>,  // The edges after each turn of LSE_TURNS.
+    lseDistances: Vec<u8>
+
+}   /* RouxTables */
+
+impl RouxTables
+{
+    /// RouxTables constructor.
+    fn new ()
+    -> RouxTables
+    {
+        let firstBlock = BlockTables::new([5, 6], [6, 9, 10], (0 .. 18).collect());
+        let secondBlock = BlockTables::new([4, 7], [4, 8, 11], vec![0, 1, 2, 3, 4, 5, ROUX_M, ROUX_M + 1, ROUX_M + 2]);
+
+        // The edges that a turn brings to a place came from the place
+        // that its cubies tell, flipped as they tell.
+        let moves = roux_turn_cubies();
+        let lseMove: Vec<[u16; 6]> = (0 .. 720 * 64).map(|ind| {
+            let (perm, flips) = (perm_of_rank(ind / 64, 6), ind % 64);
+            LSE_TURNS.map(|turn| {
+                let cubies = &moves[turn];
+                let mut next = [0; 6];
+                let mut nextFlips = 0;
+                for (pos, &place) in LSE_PLACES.iter().enumerate()
+                {
+                    let from = LSE_PLACES.iter().position(|&known| known == cubies.ep[place] as usize).unwrap();
+                    next[pos] = perm[from];
+                    nextFlips |= ((flips >> from & 1) ^ cubies.eo[place] as usize) << pos;
+                }
+                (64 * perm_rank(&next) + nextFlips) as u16
+            })
+        }).collect();
+
+        let lseDistances = prune_table(720 * 64 * 16, 0, 6, &|ind, mov| lse_turned(&lseMove, ind, mov));
+
+        RouxTables { firstBlock, secondBlock, lseMove, lseDistances }
+
+    } /* ::new() */
+
+}   /* impl RouxTables */
+
+
+/// Returns the index of the last six edges in the RouxTables: how they
+/// are arranged among LSE_PLACES and flipped, then how many quarter turns
+/// of M the centers and of U the corners are away from home.
+fn lse_index (cubies: &CubieCube, centers: usize, corners: usize)
+-> usize
+{
+    let perm: Vec<u8> = LSE_PLACES.iter().map(|&place| {
+        LSE_PLACES.iter().position(|&known| known == cubies.ep[place] as usize).unwrap() as u8
+    }).collect();
+    let flips = LSE_PLACES.iter().enumerate().fold(0, |flips, (pos, &place)| flips | (cubies.eo[place] as usize) << pos);
+
+    16 * (64 * perm_rank(&perm) + flips) + 4 * centers + corners
+
+}   /* lse_index() */
+
+
+/// Returns the index of the last six edges after the turn `mov` of
+/// LSE_TURNS, see lse_index(): a turn of U quarters the corners, one of M
+/// the centers.
+fn lse_turned (lseMove: &[[u16; 6]], ind: usize, mov: usize)
+-> usize
+{
+    let (edges, centers, corners) = (ind / 16, ind / 4 % 4, ind % 4);
+    let quarters = LSE_TURNS[mov] % 3 + 1;
+    let (centers, corners) = if mov < 3 { (centers, (corners + quarters) % 4) }
+                             else       { ((centers + quarters) % 4, corners) };
+
+    16 * lseMove[edges][mov] as usize + 4 * centers + corners
+
+}   /* lse_turned() */
+
+
+/// Returns the tables of solve_roux(), worked out once with `std`.
+fn roux_tables ()
+-> Arc<RouxTables>
+{
+    #[cfg(feature = "std")]
+    {
+        static TABLES: OnceLock<Arc<RouxTables>> = OnceLock::new();
+        TABLES.get_or_init(|| Arc::new(RouxTables::new())).clone()
+    }
+    #[cfg(not(feature = "std"))]
+    Arc::new(RouxTables::new())
+
+}   /* roux_tables() */
+
+
+/// Returns the cubies of a 3x3x3 cube whose left and right centers are in
+/// their places, as roux_turn_cubies() turns them, and how many quarter
+/// turns of M the other centers are away from home.
+fn roux_cubies (cube: &Cube)
+-> Result<(CubieCube, usize), CubusError>
+{
+    let undo = movevec_inverted(&movevec_of_face('M', 1, 1, 2)?);
+    let mut turned = cube.clone();
+    for centers in 0 .. 4
+    {
+        if let Some(cubies) = turned.to_cubies()
+        {
+            // The edges as the M turns that the centers tell left them.
+            let slices = roux_turn_cubies();
+            return Ok((if centers == 0 { cubies } else { cubies.compose(&slices[ROUX_M + centers - 1]) }, centers));
+        }
+        turned.apply_moves(&undo)?;
+    }
+
+    Err(CubusError::Unreachable(vec!["the left and right centers are away from their places".to_string()]))
+
+}   /* roux_cubies() */
+
+
+/// A step of ROUX_STEPS as a Phase, which goes by the tables of
+/// solve_roux().  The first brings the centers to their places by the
+/// shortest rotation.
+struct RouxPhase
+{
+    step:   usize,                  // Index in ROUX_STEPS.
+    goal:   Vec<bool>,
+    moves:  Vec<Move>,
+    tables: Arc<RouxTables>
+
+}   /* RouxPhase */
+
+impl Phase for RouxPhase
+{
+    fn name (&self)
+    -> &str
+    {
+        ROUX_STEPS[self.step].0
+    }
+
+    fn note (&self)
+    -> &str
+    {
+        ROUX_STEPS[self.step].1
+    }
+
+    fn goal (&self)
+    -> &[bool]
+    {
+        &self.goal
+    }
+
+    fn moves (&self)
+    -> &[Move]
+    {
+        &self.moves
+    }
+
+    fn solve (&self, cube: &Cube)
+    -> Result<Vec<Move>, CubusError>
+    {
+        cube.check_reachable(&Cube::new(3))?;
+        if self.step == 0
+        {
+            let (rotation, cubies) = cubies_with_centers_home(cube)?;
+            let turns = self.tables.firstBlock.solve(&cubies, [5, 6], [6, 9, 10]);
+            return Ok([rotation, movevec_of_roux_turns(&face_turns_merged(&turns))].concat());
+        }
+        if !stickers_home(cube, &roux_phase_goal(self.step - 1))
+        {
+            return Err(CubusError::PhaseFailed(self.name().to_string()));
+        }
+        let (cubies, centers) = roux_cubies(cube)?;
+        let moves = roux_turn_cubies();
+        let apply = |cubies: &CubieCube, turns: &[usize]| turns.iter().fold(*cubies, |cubies, &turn| cubies.compose(&moves[turn]));
+
+        let turns = match self.step
+        {
+            1   =>  self.tables.secondBlock.solve(&cubies, [4, 7], [4, 8, 11]),
+            2   =>  {
+                // The top corners turned up, then placed; the blocks keep in
+                // place, as the algorithms keep the first two layers.
+                let cornersUp = |cubies: &CubieCube| cubies.co[.. 4] == [0; 4];
+                let cornersHome = |cubies: &CubieCube| cornersUp(cubies) && cubies.cp[.. 4] == [0, 1, 2, 3];
+                let mut cornerTurns = macro_search(&cubies, &lbl_macros(&["R U R' U R U2 R'"], 1, false), 4, &cornersUp).unwrap();
+                cornerTurns.extend(macro_search(&apply(&cubies, &cornerTurns), &lbl_macros(&["R' F R' B2 R F' R' B2 R2"], 1, false), 4,
+                                                &cornersHome).unwrap());
+                cornerTurns
+            },
+            _   =>  {
+                // The last six edges, downhill in their table.
+                let mut ind = lse_index(&cubies, centers, 0);
+                let mut lseTurns: Vec<usize> = vec![];
+                while self.tables.lseDistances[ind] != 0
+                {
+                    let mov = (0 .. 6).find(|&mov| self.tables.lseDistances[lse_turned(&self.tables.lseMove, ind, mov)] < self.tables.lseDistances[ind])
+                                      .unwrap();
+                    lseTurns.push(LSE_TURNS[mov]);
+                    ind = lse_turned(&self.tables.lseMove, ind, mov);
+                }
+                lseTurns
+            }
+        };
+
+        Ok(movevec_of_roux_turns(&face_turns_merged(&turns)))
+    }
+
+}   /* impl Phase for RouxPhase */
+
+
+/// Returns the goal of the step of ROUX_STEPS with the given index: the
+/// first block, both blocks, those and the top corners, or all stickers.
+fn roux_phase_goal (step: usize)
+-> Vec<bool>
+{
+    let goals = ["L, DL, FL, BL, DFL, DBL", "L, DL, FL, BL, DFL, DBL, R, DR, FR, BR, DFR, DBR",
+                 "L, DL, FL, BL, DFL, DBL, R, DR, FR, BR, DFR, DBR, UFL, UFR, UBL, UBR", "LL"];
+
+    sticker_mask(goals[step], 3).unwrap()
+
+}   /* roux_phase_goal() */
+
+
+/// Returns the phases of solve_roux(), one for each step of ROUX_STEPS.
+/// The tables take a moment to work out, once with `std`.
+pub fn roux_phases ()
+-> Vec<Box<dyn Phase>>
+{
+    let tables = roux_tables();
+    let moves = ["R L U D F B x y z", "R U M", "R L U D F B", "M U"];
+
+    moves.iter().enumerate().map(|(step, moves)| {
+        Box::new(RouxPhase { step, goal: roux_phase_goal(step), moves: phase_moves(moves), tables: tables.clone() }) as Box<dyn Phase>
+    }).collect()
+
+}   /* roux_phases() */
+
+
+/// Returns the solution of a 3x3x3 cube by the Roux method, in the steps
+/// of ROUX_STEPS, by solve_phases() with roux_phases(): a rotation that
+/// brings the centers to their places and the first block, as short as
+/// can be, then the second block as short as R, U and M turns make it, the
+/// top corners by two algorithms with turns of the top face between, and
+/// the last six edges as short as M and U turns make them.  Steps that
+/// there is nothing to do for are left empty.  The tables take a moment
+/// to work out, once with `std`.
+pub fn solve_roux (cube: &Cube)
+-> Result<Vec<SolveStep>, CubusError>
+{
+    solve_phases(cube, &roux_phases())
+
+}   /* solve_roux() */
+
+
+/// The steps of solve_zz(), each with what it does.
+const ZZ_STEPS: [(&str, &str); 6] = [
+    ("EOLine",          "Orient all edges, so that no F or B turn is needed after, and place the DF and DB edges."),
+    ("Left block",      "Build a 1x2x3 block on the left with L, U and R turns, which keep the edges oriented."),
+    ("Right block",     "Build the same block on the right with R and U turns only."),
+    LBL_STEPS[4],
+    LBL_STEPS[5],
+    LBL_STEPS[6]
+];
+
+/// The face turns of the left block of solve_zz(): U, R and L, each way.
+const ZZ_LEFT_TURNS: [usize; 9] = [0, 1, 2, 3, 4, 5, 12, 13, 14];
+
+
+/// The tables of solve_zz(): those of the EOLine, of the flip and of the
+/// DF and DB edges, see edge_slots_index(), after each face turn and how
+/// many face turns each takes at least to get home, and those of both blocks.
+struct ZzTables
+{
+    flipMove:    Vec<[u16; 18]>,
+    lineMove:    Vec<[u16; 18]>,
+    flipPrune:   Vec<u8>,
+    linePrune:   Vec<u8>,
+    leftBlock:   BlockTables,
+    rightBlock:  BlockTables
+
+}   /* ZzTables */
+
+impl ZzTables
+{
+    /// ZzTables constructor.
+    fn new ()
+    -> ZzTables
+    {
+        let moves = face_turn_cubies();
+        let flipMove: Vec<[u16; 18]> = (0 .. 2048).map(|flip| {
+            let mut cubies = CubieCube::new();
+            cubies.set_flip(flip);
+            core::array::from_fn(|mov| cubies.compose(&moves[mov]).flip() as u16)
+        }).collect();
+        let slotMove: [[u8; 18]; 24] = edge_slot_moves(&moves);
+        let lineMove: Vec<[u16; 18]> = (0 .. 132 * 4).map(|ind| {
+            let slots = edge_slots_of_index::<2>(ind);
+            core::array::from_fn(|mov| edge_slots_index(&slots.map(|slot| slotMove[slot as usize][mov])) as u16)
+        }).collect();
+        let flipPrune = prune_table(2048, 0, 18, &|ind, mov| flipMove[ind][mov] as usize);
+        let linePrune = prune_table(132 * 4, edge_slots_index(&[10, 14]), 18, &|ind, mov| lineMove[ind][mov] as usize);
+
+        ZzTables {
+            flipMove, lineMove, flipPrune, linePrune,
+            leftBlock:  BlockTables::new([5, 6], [6, 9, 10], ZZ_LEFT_TURNS.to_vec()),
+            rightBlock: BlockTables::new([4, 7], [4, 8, 11], ZZ_LEFT_TURNS[.. 6].to_vec())
+        }
+
+    } /* ::new() */
+
+    /// Returns the fewest face turns that orient all edges and bring DF
+    /// and DB home from where the cubies have them, by IDA* bounded by how
+    /// far the flip alone and the two edges alone are from home.
+    fn solve_eoline (&self, cubies: &CubieCube)
+    -> Vec<usize>
+    {
+        fn deepen (tables: &ZzTables, flip: usize, line: usize, togo: usize, path: &mut Vec<usize>)
+        -> bool
+        {
+            if togo < (tables.flipPrune[flip] as usize).max(tables.linePrune[line] as usize)
+            {
+                return false;
+            }
+            if togo == 0
+            {
+                return true;
+            }
+            for turn in 0 .. 18
+            {
+                if !face_turn_may_follow(path, turn)
+                {
+                    continue;
+                }
+                path.push(turn);
+                if deepen(tables, tables.flipMove[flip][turn] as usize, tables.lineMove[line][turn] as usize, togo - 1, path)
+                {
+                    return true;
+                }
+                path.pop();
+            }
+
+            false
+        }
+
+        let edgeSlot = |edge: u8| {
+            let place = cubies.ep.iter().position(|&piece| piece == edge).unwrap();
+            (2 * place) as u8 + cubies.eo[place]
+        };
+        let line = edge_slots_index(&[edgeSlot(5), edgeSlot(7)]);
+        let mut path: Vec<usize> = vec![];
+        let mut togo = 0;
+        while !deepen(self, cubies.flip(), line, togo, &mut path)
+        {
+            togo += 1;
+        }
+
+        path
+
+    } /* .solve_eoline() */
+
+}   /* impl ZzTables */
+
+
+/// Returns the places, named as in EDGE_NAMES, of the edges of a 3x3x3
+/// cube that are misoriented with respect to its front and back faces:
+/// those that turns of the other faces cannot bring home unflipped.  The
+/// cube is first turned with its centers to their places, as solve_zz()
+/// does.
+pub fn misoriented_edges (cube: &Cube)
+-> Result<Vec<&'static str>, CubusError>
+{
+    cube.check_reachable(&Cube::new(3))?;
+    let (_, cubies) = cubies_with_centers_home(cube)?;
+
+    Ok((0 .. 12).filter(|&place| cubies.eo[place] != 0).map(|place| EDGE_NAMES[place]).collect())
+
+}   /* misoriented_edges() */
+
+
+/// Returns the tables of solve_zz(), worked out once with `std`.
+fn zz_tables ()
+-> Arc<ZzTables>
+{
+    #[cfg(feature = "std")]
+    {
+        static TABLES: OnceLock<Arc<ZzTables>> = OnceLock::new();
+        TABLES.get_or_init(|| Arc::new(ZzTables::new())).clone()
+    }
+    #[cfg(not(feature = "std"))]
+    Arc::new(ZzTables::new())
+
+}   /* zz_tables() */
+
+
+/// A step of ZZ_STEPS as a Phase, which goes by the tables of solve_zz().
+/// The first brings the centers to their places by the shortest rotation,
+/// and all keep the edges oriented after.
+struct ZzPhase
+{
+    step:   usize,                  // Index in ZZ_STEPS.
+    goal:   Vec<bool>,
+    moves:  Vec<Move>,
+    tables: Arc<ZzTables>
+
+}   /* ZzPhase */
+
+impl Phase for ZzPhase
+{
+    fn name (&self)
+    -> &str
+    {
+        ZZ_STEPS[self.step].0
+    }
+
+    fn note (&self)
+    -> &str
+    {
+        ZZ_STEPS[self.step].1
+    }
+
+    fn goal (&self)
+    -> &[bool]
+    {
+        &self.goal
+    }
+
+    fn moves (&self)
+    -> &[Move]
+    {
+        &self.moves
+    }
+
+    /// Tells whether the stickers of the goal show the colors of the
+    /// ordered cube and all edges are oriented.
+    fn is_done (&self, cube: &Cube)
+    -> bool
+    {
+        stickers_home(cube, &self.goal) && cube.to_cubies().is_some_and(|cubies| cubies.eo == [0; 12])
+    }
+
+    fn solve (&self, cube: &Cube)
+    -> Result<Vec<Move>, CubusError>
+    {
+        cube.check_reachable(&Cube::new(3))?;
+        if self.step == 0
+        {
+            let (rotation, cubies) = cubies_with_centers_home(cube)?;
+            return Ok([rotation, movevec_of_face_turns(&face_turns_merged(&self.tables.solve_eoline(&cubies)))].concat());
+        }
+        let previous = ZzPhase { step: self.step - 1, goal: zz_phase_goal(self.step - 1), moves: vec![], tables: self.tables.clone() };
+        let cubies = match cube.to_cubies()
+        {
+            Some(cubies) if previous.is_done(cube)
+                    =>  cubies,
+            _       =>  return Err(CubusError::PhaseFailed(self.name().to_string()))
+        };
+
+        let turns = match self.step
+        {
+            1   =>  self.tables.leftBlock.solve(&cubies, [5, 6], [6, 9, 10]),
+            2   =>  self.tables.rightBlock.solve(&cubies, [4, 7], [4, 8, 11]),
+            _   =>  {
+                // The last layer by its algorithms, which keep the first two layers.
+                let topFace = |cubies: &CubieCube| cubies.co[.. 4] == [0; 4];
+                let topCorners = |cubies: &CubieCube| topFace(cubies) && cubies.cp[.. 4] == [0, 1, 2, 3];
+                let solved = |cubies: &CubieCube| cubies.is_solved();
+                let goals: [&dyn Fn(&CubieCube) -> bool; 3] = [&topFace, &topCorners, &solved];
+                macro_search(&cubies, &lbl_macros(&[LBL_ALGORITHMS[self.step - 2]], 1, false), 4, goals[self.step - 3]).unwrap()
+            }
+        };
+
+        Ok(movevec_of_face_turns(&face_turns_merged(&turns)))
+    }
+
+}   /* impl Phase for ZzPhase */
+
+
+/// Returns the goal of the step of ZZ_STEPS with the given index: the
+/// line of DF and DB, with the left block, the first two layers, those
+/// and the top face, also the top corners, or all stickers.
+fn zz_phase_goal (step: usize)
+-> Vec<bool>
+{
+    let goals = ["D, DF, DB", "D, DF, DB, L, DL, FL, BL, DFL, DBL", "F2L", "OLL", "OLL, UFR, URB, UBL, ULF", "LL"];
+
+    sticker_mask(goals[step], 3).unwrap()
+
+}   /* zz_phase_goal() */
+
+
+/// Returns the phases of solve_zz(), one for each step of ZZ_STEPS.  The
+/// tables take a moment to work out, once with `std`.
+pub fn zz_phases ()
+-> Vec<Box<dyn Phase>>
+{
+    let tables = zz_tables();
+    let moves = ["R L U D F B x y z", "U R L", "U R", "R L U D F B", "R L U D F B", "R L U D F B"];
+
+    moves.iter().enumerate().map(|(step, moves)| {
+        Box::new(ZzPhase { step, goal: zz_phase_goal(step), moves: phase_moves(moves), tables: tables.clone() }) as Box<dyn Phase>
+    }).collect()
+
+}   /* zz_phases() */
+
+
+/// Returns the solution of a 3x3x3 cube by the ZZ method, in the steps of
+/// ZZ_STEPS, by solve_phases() with zz_phases(): a rotation that brings
+/// the centers to their places and the EOLine, as short as can be, which
+/// orients all edges, see misoriented_edges(), then the blocks of the
+/// first two layers on the left and right without rotations, each as
+/// short as the turns it may use make it, and the last layer by the
+/// algorithms of solve_layer_by_layer(), whose top cross the EOLine leaves
+/// done.  Steps that there is nothing to do for are left empty.  The
+/// tables take a moment to work out, once with `std`.
+pub fn solve_zz (cube: &Cube)
+-> Result<Vec<SolveStep>, CubusError>
+{
+    solve_phases(cube, &zz_phases())
+
+}   /* solve_zz() */
+
+
+/*  ––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––  *
+ *
+ *      Scrambling
+ */
+
+
+/// A small xorshift64* pseudo-random number generator.
+pub struct Rng
+{
+    state: u64
+
+}   /* Rng */
+
+impl Rng
+{
+    /// Rng constructor.  Equal seeds produce equal number sequences.
+    pub fn new (seed: u64)
+    -> Rng
+    {
+        // The state must never be zero.
+        Rng { state: seed ^ 0x9E37_79B9_7F4A_7C15 | 1 }
+
+    }   /* ::new() */
+
+    /// Rng seeded from the system clock.
+    #[cfg(feature = "std")]
+    pub fn from_clock ()
+    -> Rng
+    {
+        let elapsed = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or(Duration::new(0, 0));
+        Rng::new(elapsed.as_secs() ^ ((elapsed.subsec_nanos() as u64) << 32))
+
+    }   /* ::from_clock() */
+
+    /// Returns the next pseudo-random number.
+    pub fn next_u64 (&mut self)
+    -> u64
+    {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+
+    }   /* .next_u64() */
+
+    /// Returns a pseudo-random number in the range 0 .. bound.
+    pub fn below (&mut self, bound: usize)
+    -> usize
+    {
+        (self.next_u64() % bound as u64) as usize
+
+    }   /* .below() */
+
+}   /* impl Rng */
+
+
+/// Returns `length` random moves for a cube of the given size, pruned by
+/// the rules of find_moves(): no move undoes its predecessor, no layer turns
+/// the same way thrice in a row, half turns turn the positive way, and no
+/// run of moves turns every layer of an axis alike, which would only turn
+/// the whole cube.  Equal seeds of the Rng yield equal moves.
+pub fn random_movevec (size: Coord, length: usize, rng: &mut Rng)
+-> Vec<Move>
+{
+    let axmax = size as usize - 1;
+    let movSet = Cube::new(size).legal_moves();
+
+    let mut moves: Vec<Move> = Vec::with_capacity(length);
+    while moves.len() < length
+    {
+        let mov = movSet[rng.below(movSet.len())];
+
+        let movNum = moves.len();
+        if movNum > 0
+        {
+            let move1 = &moves[movNum - 1];
+            if mov.axval == move1.axval && mov.axdir == invert_axis(move1.axdir)
+            {
+                continue;
+            }
+
+            if movNum > 1 && mov.ident == move1.ident && mov.ident == moves[movNum - 2].ident
+            {
+                continue;
+            }
+
+            if mov.ident == move1.ident && mov.axdir.is_ascii_lowercase()
+            {
+                continue;
+            }
+        }
+
+        if 0 < axmax && axmax <= movNum
+        {
+            let tail = &moves[movNum - axmax ..];
+            if tail.iter().all(|known| known.axdir == mov.axdir)
+            {
+                let mut layers: Vec<Coord> = tail.iter().map(|known| known.axval).chain([mov.axval]).collect();
+                layers.sort();
+                layers.dedup();
+                if layers.len() == size as usize
+                {
+                    continue;
+                }
+            }
+        }
+
+        moves.push(mov);
+    }
+
+    moves
+
+}   /* random_movevec() */
+
+
+/// Performs `walks` random walks of `steps` moves each, starting from the
+/// ordered state, and returns the mean number of misplaced stickers after
+/// each step, beginning with step 0.
+pub fn random_walk_distances (size: Coord, steps: usize, walks: usize, rng: &mut Rng)
+-> Vec<f64>
+{
+    let home = Cube::new(size);
+
+    let mut sums: Vec<usize> = vec![0; steps + 1];
+    for _ in 0 .. walks
+    {
+        let moves = random_movevec(size, steps, rng);
+        let mut cube = home.clone();
+        sums[0] += cube.sticker_distance(&home);
+        for (ind, mov) in moves.iter().enumerate()
+        {
+            cube.apply_checked_moves(&[*mov]);
+            sums[ind + 1] += cube.sticker_distance(&home);
+        }
+    }
+
+    sums.iter().map(|&sum| sum as f64 / walks.max(1) as f64).collect()
+
+}   /* random_walk_distances() */
+
+
+/*  ––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––  *
+ *
+ *      Output Functions
+ */
+
+
+/// The ways of rendering face colors, from richest to plainest.
+#[derive(Eq, PartialEq, Copy, Clone)]
+pub enum RenderStyle
+{
+    TrueColor,  // 24-bit colors, including a real orange.
+    Color256,   // The xterm 256-color palette.
+    Color16,    // The basic VT100 colors, with cyan for orange.
+    Ascii       // No colors; stickers show their color's initial letter.
+
+}   /* RenderStyle */
+
+impl RenderStyle
+{
+    /// Picks the richest style that the terminal announces by the
+    /// environment variables COLORTERM and TERM.  NO_COLOR, an unset
+    /// TERM, or TERM=dumb select Ascii.
+    #[cfg(feature = "std")]
+    pub fn detect ()
+    -> RenderStyle
+    {
+        let colorTerm = env::var("COLORTERM").unwrap_or_default();
+        let term = env::var("TERM").unwrap_or_default();
+
+        if env::var_os("NO_COLOR").is_some() || term.is_empty() || term == "dumb"
+        {
+            RenderStyle::Ascii
+        }
+        else if colorTerm == "truecolor" || colorTerm == "24bit"
+        {
+            RenderStyle::TrueColor
+        }
+        else if term.contains("256color")
+        {
+            RenderStyle::Color256
+        }
+        else
+        {
+            RenderStyle::Color16
+        }
+
+    } /* ::detect() */
+
+    /// Returns the style of the given name: ‘truecolor’, ‘256’, ‘16’ or
+    /// ‘ascii’.
+    pub fn of_name (name: &str)
+    -> Option<RenderStyle>
+    {
+        match name
+        {
+            "truecolor" =>  Some(RenderStyle::TrueColor),
+            "256"       =>  Some(RenderStyle::Color256),
+            "16"        =>  Some(RenderStyle::Color16),
+            "ascii"     =>  Some(RenderStyle::Ascii),
+            _           =>  None
+        }
+
+    } /* ::of_name() */
+
+    /// Returns the control sequence that selects the given color.
+    pub fn attrs (&self, name: Huename)
+    -> String
+    {
+        self.attrs_of(name, false)
+
+    } /* .attrs() */
+
+    /// Returns the control sequence that selects the given color for faint
+    /// stickers, in the foreground only, so that their fill lets the
+    /// background show through.
+    pub fn faint_attrs (&self, name: Huename)
+    -> String
+    {
+        self.attrs_of(name, true)
+
+    } /* .faint_attrs() */
+
+    /// Returns the control sequence for a solid or faint sticker.
+    fn attrs_of (&self, name: Huename, faint: bool)
+    -> String
+    {
+        let (red, green, blue) = name.rgb();
+        let (index, vt100) = match name
+        {
+            Huename::RD  => (160, 1),
+            Huename::OR  => (202, 6),
+            Huename::WT  => (231, 7),
+            Huename::YL  => (220, 3),
+            Huename::GN  => ( 28, 2),
+            Huename::BL  => ( 25, 4)
+        };
+
+        match *self
+        {
+            RenderStyle::TrueColor if faint
+                    =>  format!("\x1B[0;38;2;{};{};{}m", red, green, blue),
+            RenderStyle::TrueColor
+                    =>  format!("\x1B[0;38;2;{r};{g};{b};48;2;{r};{g};{b}m", r = red, g = green, b = blue),
+            RenderStyle::Color256 if faint
+                    =>  format!("\x1B[0;38;5;{}m", index),
+            RenderStyle::Color256
+                    =>  format!("\x1B[0;38;5;{i};48;5;{i}m", i = index),
+            RenderStyle::Color16 if faint
+                    =>  format!("\x1B[0;3{}m", vt100),
+            RenderStyle::Color16
+                    =>  name.vt100_attrs().to_string(),
+            RenderStyle::Ascii
+                    =>  "\x1B[0m".to_string()
+        }
+
+    } /* .attrs_of() */
+
+    /// Returns `len` characters that fill a sticker of the given color.
+    pub fn fill (&self, name: Huename, len: usize)
+    -> String
+    {
+        let chr = if *self == RenderStyle::Ascii { name.letter() } else { '█' };
+
+        core::iter::repeat_n(chr, len).collect()
+
+    } /* .fill() */
+
+    /// Returns `len` characters that fill a faint sticker of the given color:
+    /// a light shade, or in Ascii style the color's lowercase letter.
+    pub fn faint_fill (&self, name: Huename, len: usize)
+    -> String
+    {
+        let chr = if *self == RenderStyle::Ascii { name.letter().to_ascii_lowercase() } else { '░' };
+
+        core::iter::repeat_n(chr, len).collect()
+
+    } /* .faint_fill() */
+
+}   /* impl RenderStyle */
+
+
+/// Returns a stream that writes output to the terminal.
+#[cfg(feature = "std")]
+fn tty_out ()
+-> io::Result<File>
+{
+    OpenOptions::new().write(true).open("/dev/tty")
+
+}   /* tty_out() */
+
+
+/// Saves the VT100 cursor position.
+#[cfg(feature = "std")]
+fn tty_save (out: &mut dyn Write)
+{
+    write!(out, "\x1B7");
+
+}   /* tty_save() */
+
+
+/// Restores the VT100 cursor position.
+#[cfg(feature = "std")]
+fn tty_load (out: &mut dyn Write)
+{
+    write!(out, "\x1B8");
+
+}   /* tty_load() */
+
+
+/// Writes output to the terminal at the given position.
+#[cfg(feature = "std")]
+fn tty_put_at (out: &mut dyn Write, row: i16, col: i16, text: &str)
+{
+    write!(out, "\x1B[{};{}f{}", row, col, text);
+
+}   /* tty_put_at() */
+
+
+/// Draws a single cube brick to the terminal as a character graphic.
+/// Faint bricks are drawn with faint stickers.
+#[cfg(feature = "std")]
+#[allow(clippy::identity_op, reason = "the offsets of each face line up, zeros included")]
+fn draw_brick (tty: &mut dyn Write, brick: &Brick, axmax: Coord, row: i16, col: i16, style: RenderStyle,
+               isFaint: bool)
+{
+    let put = |tty: &mut dyn Write, row: i16, col: i16, style: RenderStyle, name: Huename, len: usize| {
+        if isFaint
+        {
+            write!(tty, "\x1B7\x1B[{};{}f{}{}\x1B8", row, col, style.faint_attrs(name), style.faint_fill(name, len));
+        }
+        else
+        {
+            write!(tty, "\x1B7\x1B[{};{}f{}{}\x1B8", row, col, style.attrs(name), style.fill(name, len));
+        }
+    };
+
+    let axmax = axmax  as i16;
+
+    let brickLoc = &brick.curLoc;
+    let brickHue = &brick.curHue;
+
+    let posX = brickLoc.x as i16;
+    let posY = brickLoc.y as i16;
+    let posZ = brickLoc.z as i16;
+
+    let bRow = -4 * posY +  2 * posZ + 4 * axmax + row + 1;
+    let bCol =  9 * posX + -3 * posZ + 3 * axmax + col + 1;
+
+    if posZ == axmax
+    {
+        let name = brickHue.zpos;
+        put(tty, bRow + 2, bCol +  0, style, name, 9);
+        put(tty, bRow + 3, bCol +  0, style, name, 9);
+        put(tty, bRow + 4, bCol +  0, style, name, 9);
+        put(tty, bRow + 5, bCol +  0, style, name, 9);
+    }
+
+    if posY == axmax
+    {
+        let name = brickHue.ypos;
+        put(tty, bRow + 0, bCol +  2, style, name, 9);
+        put(tty, bRow + 1, bCol +  1, style, name, 9);
+    }
+
+    if posX == axmax
+    {
+        let name = brickHue.xpos;
+        put(tty, bRow + 0, bCol + 11, style, name, 1);
+        put(tty, bRow + 1, bCol + 10, style, name, 2);
+        put(tty, bRow + 2, bCol +  9, style, name, 3);
+        put(tty, bRow + 3, bCol +  9, style, name, 3);
+        put(tty, bRow + 4, bCol +  9, style, name, 2);
+        put(tty, bRow + 5, bCol +  9, style, name, 1);
+    }
+
+}   /* draw_brick() */
+
+
+/// Draws a cube to the terminal as a character graphic.
+#[cfg(feature = "std")]
+pub fn draw_cube (cube: &Cube, row: i16, col: i16, style: RenderStyle)
+-> Result<(), CubusError>
+{
+    draw_cube_to(&mut tty_out()?, cube, row, col, style);
+
+    Ok(())
+
+}   /* draw_cube() */
+
+
+/// Writes the terminal character graphic of a cube to the given stream.
+#[cfg(feature = "std")]
+pub fn draw_cube_to (out: &mut dyn Write, cube: &Cube, row: i16, col: i16, style: RenderStyle)
+{
+    let boxH    = (2 + 4) * cube.size as i16;
+
+    // «Clear Screen» «Reset Attributes»
+    tty_put_at(out, boxH + row + 2, 0, "\x1B[2J\x1B[0m");
+
+    tty_save(out);
+    draw_bricks(out, cube, &|_, _| false, row, col, style);
+    draw_cube_marks(out, cube, row, col, style);
+    tty_load(out);
+
+}   /* draw_cube_to() */
+
+
+/// How much of a cube's picture a drawing shows, from the full view down
+/// to what little output huge cubes can afford per frame.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum RenderDetail
+{
+    Full,       // The oblique view of draw_cube_to().
+    Coarse,     // The net of all faces, one character cell per sticker.
+    Summary     // How many stickers of each color every face shows.
+
+}   /* RenderDetail */
+
+
+/// Writes the terminal graphic of a cube at the given detail.
+#[cfg(feature = "std")]
+pub fn draw_cube_detailed_to (out: &mut dyn Write, cube: &Cube, row: i16, col: i16, style: RenderStyle,
+                              detail: RenderDetail)
+{
+    match detail
+    {
+        RenderDetail::Full      =>  draw_cube_to(out, cube, row, col, style),
+        RenderDetail::Coarse    =>  draw_cube_coarse_to(out, cube, row, col, style),
+        RenderDetail::Summary   =>  draw_cube_summary_to(out, cube, row, col, style)
+    }
+
+}   /* draw_cube_detailed_to() */
+
+
+/// Draws a cube to the terminal, see draw_cube_within_to().
+#[cfg(feature = "std")]
+pub fn draw_cube_within (cube: &Cube, row: i16, col: i16, style: RenderStyle, budget: usize)
+-> Result<RenderDetail, CubusError>
+{
+    Ok(draw_cube_within_to(&mut tty_out()?, cube, row, col, style, budget))
+
+}   /* draw_cube_within() */
+
+
+/// Writes the terminal graphic of a cube in at most `budget` bytes if it
+/// can, which caps what a frame costs the terminal: at full detail if that
+/// fits, else as the coarse net of its faces, else as the color counts of
+/// its faces, which fit any cube in a few hundred bytes.  Returns the
+/// detail that was drawn.
+#[cfg(feature = "std")]
+pub fn draw_cube_within_to (out: &mut dyn Write, cube: &Cube, row: i16, col: i16, style: RenderStyle,
+                            budget: usize)
+-> RenderDetail
+{
+    for detail in [RenderDetail::Full, RenderDetail::Coarse]
+    {
+        let mut frame: Vec<u8> = vec![];
+        draw_cube_detailed_to(&mut frame, cube, row, col, style, detail);
+        if frame.len() <= budget
+        {
+            out.write_all(&frame);
+            return detail;
+        }
+    }
+
+    draw_cube_summary_to(out, cube, row, col, style);
+
+    RenderDetail::Summary
+
+}   /* draw_cube_within_to() */
+
+
+/// Writes the net of a cube's faces as Display prints it, Up above Left,
+/// Front, Right and Back, and Down below, two characters per sticker.  A
+/// row's stickers share one cursor move, and a color's control sequence
+/// is only repeated where the color changes.
+#[cfg(feature = "std")]
+fn draw_cube_coarse_to (out: &mut dyn Write, cube: &Cube, row: i16, col: i16, style: RenderStyle)
+{
+    let size  = cube.size as i16;
+    let faceW = 2 * size + 1;
+    let boxH  = 3 * size + 2;
+
+    // «Clear Screen» «Reset Attributes»
+    tty_put_at(out, boxH + row + 2, 0, "\x1B[2J\x1B[0m");
+
+    tty_save(out);
+    for (face, faceRow, faceCol) in [(Face::Up,    0,            1),
+                                     (Face::Left,  size + 1,     0),
+                                     (Face::Front, size + 1,     1),
+                                     (Face::Right, size + 1,     2),
+                                     (Face::Back,  size + 1,     3),
+                                     (Face::Down,  2 * size + 2, 1)]
+    {
+        let names: Vec<Huename> = cube.stickers(face).collect();
+        for (line, rowNames) in names.chunks(size as usize).enumerate()
+        {
+            let mut text = String::new();
+            let mut prev: Option<Huename> = None;
+            for &name in rowNames.iter()
+            {
+                if prev != Some(name)
+                {
+                    text.push_str(&style.attrs(name));
+                    prev = Some(name);
+                }
+                text.push_str(&style.fill(name, 2));
+            }
+            text.push_str("\x1B[0m");
+            tty_put_at(out, row + faceRow + line as i16, col + faceCol * faceW, &text);
+        }
+    }
+    tty_load(out);
+
+}   /* draw_cube_coarse_to() */
+
+
+/// Writes a line per face that counts its stickers of each color, the
+/// most frequent first.
+#[cfg(feature = "std")]
+fn draw_cube_summary_to (out: &mut dyn Write, cube: &Cube, row: i16, col: i16, style: RenderStyle)
+{
+    // «Clear Screen» «Reset Attributes»
+    tty_put_at(out, row + 8, 0, "\x1B[2J\x1B[0m");
+
+    tty_save(out);
+    tty_put_at(out, row, col, &format!("\x1B[0m{}x{}x{} cube, stickers per face and color:",
+                                       cube.size, cube.size, cube.size));
+    for (line, (label, face)) in [("Up   ", Face::Up), ("Left ", Face::Left), ("Front", Face::Front),
+                                  ("Right", Face::Right), ("Back ", Face::Back), ("Down ", Face::Down)]
+                                 .into_iter().enumerate()
+    {
+        let mut counts: Vec<(Huename, usize)> = vec![];
+        for name in cube.stickers(face)
+        {
+            match counts.iter_mut().find(|(other, _)| *other == name)
+            {
+                Some((_, count))    =>  *count += 1,
+                None                =>  counts.push((name, 1))
+            }
+        }
+        counts.sort_by_key(|&(_, count)| Reverse(count));
+
+        let mut text = format!("\x1B[0m{}", label);
+        for (name, count) in counts
+        {
+            text.push_str(&format!("  {}{}\x1B[0m {}", style.attrs(name), style.fill(name, 2), count));
+        }
+        tty_put_at(out, row + 1 + line as i16, col, &text);
+    }
+    tty_load(out);
+
+}   /* draw_cube_summary_to() */
+
+
+/// Draws a cube to the terminal, see draw_cube_highlighted_to().
+#[cfg(feature = "std")]
+pub fn draw_cube_highlighted (cube: &Cube, pieces: &[&str], row: i16, col: i16, style: RenderStyle)
+-> Result<(), CubusError>
+{
+    draw_cube_highlighted_to(&mut tty_out()?, cube, pieces, row, col, style)
+
+}   /* draw_cube_highlighted() */
+
+
+/// Writes the terminal character graphic of a cube with all bricks faint
+/// but the named pieces, wherever they went.  Pieces are named by the
+/// faces of their home places, in any order, e.g. UFR or UF; on larger
+/// cubes a name covers all the edge or center bricks of its faces.
+#[cfg(feature = "std")]
+pub fn draw_cube_highlighted_to (out: &mut dyn Write, cube: &Cube, pieces: &[&str], row: i16, col: i16,
+                                 style: RenderStyle)
+-> Result<(), CubusError>
+{
+    let axmax = cube.size - 1;
+    let home  = Cube::new(cube.size);
+    let boxH  = (2 + 4) * cube.size as i16;
+
+    let names: Vec<String> = pieces.iter().map(|piece| piece_name_checked(piece, cube.size)).collect::<Result<_, _>>()?;
+    let isLit: Vec<bool> = home.bricks.iter().map(|brick| names.contains(&piece_name(&brick.curLoc, axmax))).collect();
+
+    // «Clear Screen» «Reset Attributes»
+    tty_put_at(out, boxH + row + 2, 0, "\x1B[2J\x1B[0m");
+
+    tty_save(out);
+    draw_bricks(out, cube, &|ind, _| !isLit[ind], row, col, style);
+    draw_cube_marks(out, cube, row, col, style);
+    tty_load(out);
+
+    Ok(())
+
+}   /* draw_cube_highlighted_to() */
+
+
+/// Writes a frame of the move `mov` turning the cube `before`, at the
+/// fraction `part` of its way.  The move must suit the cube.  The bricks of the turning layer are
+/// shifted from their old places towards their new ones, showing the old
+/// stickers up to half way and the new ones after.
+#[cfg(feature = "std")]
+pub fn draw_cube_turning_to (out: &mut dyn Write, before: &Cube, mov: &Move, part: f64,
+                             row: i16, col: i16, style: RenderStyle)
+{
+    let after = before.copy_with_checked_moves(&[*mov]);
+    let axmax = before.size - 1;
+    let boxH  = (2 + 4) * before.size as i16;
+
+    // The screen position of a brick, relative to that of the origin.
+    let placeOf = |loc: &Loc| -> (f64, f64) {
+        ((-4 * loc.y as i16 + 2 * loc.z as i16) as f64, (9 * loc.x as i16 - 3 * loc.z as i16) as f64)
+    };
+
+    // «Clear Screen» «Reset Attributes»
+    tty_put_at(out, boxH + row + 2, 0, "\x1B[2J\x1B[0m");
+
+    tty_save(out);
+    let isVisible = |loc: &Loc| loc.x == axmax || loc.y == axmax || loc.z == axmax;
+    for brick in before.bricks.iter()
+    {
+        if !loc_in_layer(&brick.curLoc, mov.axdir, mov.axval) && isVisible(&brick.curLoc)
+        {
+            draw_brick(out, brick, axmax, row, col, style, false);
+        }
+    }
+
+    // The turning layer goes on top, so that it covers what it passes.
+    for (old, new) in before.bricks.iter().zip(after.bricks.iter())
+    {
+        if !loc_in_layer(&old.curLoc, mov.axdir, mov.axval)
+        {
+            continue;
+        }
+
+        let (oldRow, oldCol) = placeOf(&old.curLoc);
+        let (newRow, newCol) = placeOf(&new.curLoc);
+        let (brick, shift) = if part < 0.5 { (old, part) } else { (new, part - 1.0) };
+        if isVisible(&brick.curLoc)
+        {
+            let dRow = ((newRow - oldRow) * shift).round() as i16;
+            let dCol = ((newCol - oldCol) * shift).round() as i16;
+            draw_brick(out, brick, axmax, row + dRow, col + dCol, style, false);
+        }
+    }
+
+    draw_cube_marks(out, before, row, col, style);
+    tty_load(out);
+
+}   /* draw_cube_turning_to() */
+
+
+/// Writes the marks around a cube's picture: padlocks for locked layers
+/// and the compass.
+#[cfg(feature = "std")]
+fn draw_cube_marks (out: &mut dyn Write, cube: &Cube, row: i16, col: i16, style: RenderStyle)
+{
+    // Mark locked layers: X below the front face, Y left of it, Z to the
+    // right of the right face.
+    let axmax = cube.size as i16 - 1;
+    for &(axis, axval) in cube.locks.iter()
+    {
+        let axval = axval as i16;
+        let (lockRow, lockCol) = match axis
+        {
+            'X' =>  (6 * axmax + row + 7, 9 * axval + col + 4),
+            'Y' =>  (-4 * axval + 6 * axmax + row + 4, col - 1),
+            _   =>  (2 * axval + 4 * axmax + row + 4, 12 * axmax + col + 14)
+        };
+        tty_put_at(out, lockRow, lockCol, if style == RenderStyle::Ascii {"\x1B[0mL"} else {"\x1B[0m🔒"});
+    }
+
+    // The compass names the colors that the visible faces belong to, to the
+    // right of the top face, so that whole-cube rotations stay traceable.
+    if let Some(colors) = cube.orientation()
+    {
+        for (line, &(label, face)) in [("Up   ", 2), ("Front", 4), ("Right", 0)].iter().enumerate()
+        {
+            let name = colors[face];
+            tty_put_at(out, row + line as i16, 12 * axmax + col + 17,
+                       &format!("\x1B[0m{} {}{}\x1B[0m", label, style.attrs(name), style.fill(name, 2)));
+        }
+    }
+
+}   /* draw_cube_marks() */
+
+
+/// Draws the visible bricks of a cube.  Those for whose index and brick
+/// `isFaint` holds are drawn faint, which highlights the others.
+#[cfg(feature = "std")]
+fn draw_bricks (out: &mut dyn Write, cube: &Cube, isFaint: &dyn Fn(usize, &Brick) -> bool,
+                row: i16, col: i16, style: RenderStyle)
+{
+    let axmax = cube.size - 1;
+    for (ind, brick) in cube.bricks.iter().enumerate()
+    {
+        if brick.curLoc.x == axmax
+        || brick.curLoc.y == axmax
+        || brick.curLoc.z == axmax
+        {
+            draw_brick(out, brick, axmax, row, col, style, isFaint(ind, brick));
+        }
+    }
+
+}   /* draw_bricks() */
+
+
+/// Draws cubes side by side to the terminal, see draw_cube_row_to().
+#[cfg(feature = "std")]
+pub fn draw_cube_row (cubes: &[(&str, &Cube)], start: &Cube, row: i16, col: i16, style: RenderStyle)
+-> Result<(), CubusError>
+{
+    draw_cube_row_to(&mut tty_out()?, cubes, start, row, col, style)
+
+}   /* draw_cube_row() */
+
+
+/// Writes the terminal character graphics of the given cubes side by side,
+/// each below its title.  Bricks that are where and as they are in the
+/// `start` cube are drawn faint, which highlights what moves did.
+#[cfg(feature = "std")]
+pub fn draw_cube_row_to (out: &mut dyn Write, cubes: &[(&str, &Cube)], start: &Cube, row: i16, col: i16,
+                         style: RenderStyle)
+-> Result<(), CubusError>
+{
+    let axmax = start.size as i16 - 1;
+    let boxH  = (2 + 4) * start.size as i16;
+    let boxW  = 12 * axmax + 18;
+
+    if let Some((_, cube)) = cubes.iter().find(|(_, cube)| cube.size != start.size)
+    {
+        return Err(CubusError::SizeMismatch(start.size, cube.size));
+    }
+
+    // «Clear Screen» «Reset Attributes»
+    tty_put_at(out, boxH + row + 3, 0, "\x1B[2J\x1B[0m");
+
+    tty_save(out);
+    for (ind, (title, cube)) in cubes.iter().enumerate()
+    {
+        let cubeCol = col + ind as i16 * boxW;
+        tty_put_at(out, row, cubeCol + 1, &format!("\x1B[0m{}", title));
+        draw_bricks(out, cube, &|ind, brick| start.bricks[ind] == *brick, row + 1, cubeCol, style);
+    }
+    tty_load(out);
+
+    Ok(())
+
+}   /* draw_cube_row_to() */
+
+
+/// Returns the top layer as seen from above, as top_view_string() draws
+/// it: rows of size + 2 cells, the stickers of the top face inside and the
+/// side stickers of its bricks round them, with no cell in the corners.
+/// Fails when a brick lies off the cube or a top place holds none.
+pub fn top_view_grid (cube: &Cube)
+-> Result<Vec<Vec<Option<Huename>>>, CubusError>
+{
+    let size = cube.size;
+    if !(1 ..= 10).contains(&size)
+    {
+        return Err(CubusError::InvalidSize(size));
+    }
+    let axmax = size - 1;
+    if cube.bricks.iter().any(|brick| axmax < brick.curLoc.x || axmax < brick.curLoc.y || axmax < brick.curLoc.z)
+    {
+        return Err(CubusError::InvalidBricks);
+    }
+    let brickAt = cube.bricks_by_loc();
+    let mut hues: Vec<Hue> = vec![];
+    for z in 0 ..= axmax
+    {
+        for x in 0 ..= axmax
+        {
+            hues.push(brickAt[loc_index(&Loc { x, y: axmax, z }, size)].ok_or(CubusError::InvalidBricks)?.curHue);
+        }
+    }
+    let hueAt = |x: Coord, z: Coord| -> Hue { hues[z as usize * size as usize + x as usize] };
+
+    let mut grid: Vec<Vec<Option<Huename>>> = vec_of_size(size as usize + 2, vec_of_size(size as usize + 2, None));
+    for ind in 0 ..= axmax
+    {
+        let cell = ind as usize + 1;
+        grid[0][cell] = Some(hueAt(ind, 0).zneg);
+        grid[cell][0] = Some(hueAt(0, ind).xneg);
+        grid[cell][size as usize + 1] = Some(hueAt(axmax, ind).xpos);
+        grid[size as usize + 1][cell] = Some(hueAt(ind, axmax).zpos);
+        for x in 0 ..= axmax
+        {
+            grid[cell][x as usize + 1] = Some(hueAt(x, ind).ypos);
+        }
+    }
+
+    Ok(grid)
+
+}   /* top_view_grid() */
+
+
+/// Returns the text of a top view, see top_view_grid(), one letter per
+/// sticker and `blank` for cells without one other than the corners.
+fn top_view_text (grid: &[Vec<Option<Huename>>], blank: char)
+-> Vec<String>
+{
+    let last = grid.len() - 1;
+    let letterAt = |row: usize, col: usize| -> char {
+        match grid[row][col]
+        {
+            _ if (row == 0 || row == last) && (col == 0 || col == last)
+                        =>  ' ',
+            Some(name)  =>  name.letter(),
+            None        =>  blank
+        }
+    };
+
+    (0 ..= last).map(|row| {
+        let mut line = String::new();
+        line.push(letterAt(row, 0));
+        line.push(' ');
+        for col in 1 .. last
+        {
+            line.push(' ');
+            line.push(letterAt(row, col));
+        }
+        if 0 < row && row < last
+        {
+            line.push_str("  ");
+            line.push(letterAt(row, last));
+        }
+        line
+    }).collect()
+
+}   /* top_view_text() */
+
+
+/// Returns the top layer as seen from above, one letter per sticker, with
+/// the side stickers of its bricks as strips around it.  The back strip
+/// comes first, the front strip last.
+pub fn top_view_string (cube: &Cube)
+-> Result<String, CubusError>
+{
+    Ok(top_view_text(&top_view_grid(cube)?, ' ').iter().map(|line| format!("{}\n", line)).collect())
+
+}   /* top_view_string() */
+
+
+/// Returns the top views of the cases that the algorithms of a collection
+/// solve, see alg_collection_of_json(), in the colors of the scheme: each
+/// the top layer after the inverse of its algorithm.  Where the top face
+/// is not all of one color, as in OLL cases, only the stickers of that
+/// color show, and the others are None.
+fn alg_sheet_views (collection: &[(String, String)], size: Coord, scheme: &ColorScheme)
+-> Result<Vec<Vec<Vec<Option<Huename>>>>, CubusError>
+{
+    let top = top_view_grid(&scheme.recolored(&Cube::try_new(size)?))?[1][1];
+
+    collection.iter().map(|(_, algorithm)| {
+        let moves = movevec_of_singmaster(algorithm, size - 1)?;
+        let mut grid = top_view_grid(&scheme.recolored(&Cube::new(size).copy_with_moves(&movevec_inverted(&moves))?))?;
+        let isOriented = grid[1 ..= size as usize].iter().all(|row| row[1 ..= size as usize].iter().all(|&cell| cell == top));
+        if !isOriented
+        {
+            grid.iter_mut().flatten().filter(|cell| **cell != top).for_each(|cell| *cell = None);
+        }
+        Ok(grid)
+    }).collect()
+
+}   /* alg_sheet_views() */
+
+
+/// Returns a printable sheet of the cases that the algorithms of a
+/// collection solve, see alg_collection_of_json(), as plain text: each
+/// case's top view as top_view_string() draws it, with `.` for stickers
+/// masked as alg_sheet_views() tells, and its name and algorithm beside.
+pub fn alg_sheet_text (collection: &[(String, String)], size: Coord, scheme: &ColorScheme)
+-> Result<String, CubusError>
+{
+    let width = 2 * size as usize + 5;
+
+    let mut sheet = String::new();
+    for ((name, algorithm), grid) in collection.iter().zip(alg_sheet_views(collection, size, scheme)?)
+    {
+        for (row, line) in top_view_text(&grid, '.').iter().enumerate()
+        {
+            let beside = match row
+            {
+                0   =>  name.as_str(),
+                1   =>  algorithm.as_str(),
+                _   =>  ""
+            };
+            sheet.push_str(format!("{:<width$}   {}", line, beside).trim_end());
+            sheet.push('\n');
+        }
+        sheet.push('\n');
+    }
+
+    Ok(sheet)
+
+}   /* alg_sheet_text() */
+
+
+/// Returns a printable sheet of the cases that the algorithms of a
+/// collection solve, see alg_collection_of_json(), as an HTML page: a
+/// grid of the cases' top views, drawn as for alg_sheet_text() with grey
+/// for masked stickers, each with its name and algorithm beside it.
+pub fn alg_sheet_html (collection: &[(String, String)], size: Coord, scheme: &ColorScheme)
+-> Result<String, CubusError>
+{
+    let last = size as usize + 1;
+    let (strip, square, gap) = (6, 20, 2);
+    let startOf = |cell: usize| if cell == 0 {0} else {strip + gap + (cell - 1) * (square + gap)};
+    let extentOf = |cell: usize| if cell == 0 || cell == last {strip} else {square};
+    let total = startOf(last) + strip;
+
+    let mut html = String::from("<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>Algorithms</title>
+<style>
+body { font-family: sans-serif; }
+.sheet { display: grid; grid-template-columns: repeat(auto-fill, minmax(24em, 1fr)); gap: 1.5em; }
+figure { display: flex; align-items: center; gap: 1em; margin: 0; break-inside: avoid; }
+</style>
+</head>
+<body>
+<div class=\"sheet\">
+");
+    for ((name, algorithm), grid) in collection.iter().zip(alg_sheet_views(collection, size, scheme)?)
+    {
+        html.push_str(&format!("<figure><svg width=\"{t}\" height=\"{t}\" viewBox=\"0 0 {t} {t}\">", t = total));
+        for (row, cells) in grid.iter().enumerate()
+        {
+            for (col, cell) in cells.iter().enumerate()
+            {
+                if (row == 0 || row == last) && (col == 0 || col == last)
+                {
+                    continue;
+                }
+                let fill = match cell
+                {
+                    Some(name)  =>  { let (red, green, blue) = name.rgb(); format!("#{:02x}{:02x}{:02x}", red, green, blue) },
+                    None        =>  "#c0c0c0".to_string()
+                };
+                html.push_str(&format!("<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" stroke=\"black\"/>",
+                                       startOf(col), startOf(row), extentOf(col), extentOf(row), fill));
+            }
+        }
+        html.push_str(&format!("</svg><figcaption><b>{}</b><br><code>{}</code></figcaption></figure>\n",
+                               html_escaped(name), html_escaped(algorithm)));
+    }
+    html.push_str("</div>\n</body>\n</html>\n");
+
+    Ok(html)
+
+}   /* alg_sheet_html() */
+
+
+/// Returns the given text with the characters that HTML reserves escaped.
+fn html_escaped (text: &str)
+-> String
+{
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+
+}   /* html_escaped() */
+
+
+/// Returns the cube as given and after each of the given moves.
+pub fn cubevec_of_moves (cube: &Cube, moves: &[Move])
+-> Result<Vec<Cube>, CubusError>
+{
+    movevec_check(moves, cube.size - 1)?;
+
+    let mut cubes: Vec<Cube> = Vec::with_capacity(moves.len() + 1);
+    cubes.push(cube.clone());
+    for ind in 0 .. moves.len()
+    {
+        let next = cubes[ind].copy_with_checked_moves(&moves[ind ..= ind]);
+        cubes.push(next);
+    }
+
+    Ok(cubes)
+
+}   /* cubevec_of_moves() */
+
+
+/// Returns the sticker distance, see Cube::sticker_distance(), between the
+/// states that two move sequences pass through from the given cube, for the
+/// start and after each move, where the shorter sequence stays at its end.
+pub fn trace_distances (cube: &Cube, lhs: &[Move], rhs: &[Move])
+-> Result<Vec<usize>, CubusError>
+{
+    let lhsCubes = cubevec_of_moves(cube, lhs)?;
+    let rhsCubes = cubevec_of_moves(cube, rhs)?;
+    let stateAt = |cubes: &[Cube], step: usize| cubes[step.min(cubes.len() - 1)].clone();
+
+    Ok((0 ..= lhs.len().max(rhs.len())).map(|step| stateAt(&lhsCubes, step).sticker_distance(&stateAt(&rhsCubes, step)))
+                                       .collect())
+
+}   /* trace_distances() */
+
+
+/// Writes one rendered frame file per move into the given directory.
+/// Frame 0 shows the cube as given, frame k the cube after the first k moves.
+#[cfg(feature = "std")]
+pub fn write_frames (dir: &str, cube: &Cube, moves: &[Move], style: RenderStyle)
+-> Result<(), CubusError>
+{
+    let cubes = cubevec_of_moves(cube, moves)?;
+    fs::create_dir_all(dir)?;
+
+    for (ind, cube) in cubes.iter().enumerate()
+    {
+        let mut file = File::create(Path::new(dir).join(format!("frame-{:03}.txt", ind)))?;
+        draw_cube_to(&mut file, cube, 1, 2, style);
+        writeln!(file, "{}", string_of_movevec(&moves[.. ind]))?;
+    }
+
+    Ok(())
+
+}   /* write_frames() */
+
+
+/// Returns the given text as a quoted JSON string.
+pub fn json_string (text: &str)
+-> String
+{
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for chr in text.chars()
+    {
+        match chr
+        {
+            '"'     =>  json.push_str("\\\""),
+            '\\'    =>  json.push_str("\\\\"),
+            '\n'    =>  json.push_str("\\n"),
+            '\r'    =>  json.push_str("\\r"),
+            '\t'    =>  json.push_str("\\t"),
+            _ if (chr as u32) < 0x20
+                    =>  json.push_str(&format!("\\u{:04x}", chr as u32)),
+            _       =>  json.push(chr)
+        }
+    }
+    json.push('"');
+
+    json
+
+}   /* json_string() */
+
+
+/// Reads the JSON text of an algorithm collection, see
+/// alg_collection_of_json(), one character at a time.
+struct JsonReader<'a>
+{
+    chars:    core::iter::Peekable<core::str::Chars<'a>>,
+    position: usize                 // Position of the next character, from 1.
+
+}   /* JsonReader */
+
+impl JsonReader<'_>
+{
+    /// Skips whitespace and returns the next character, if any, unread.
+    fn peek (&mut self)
+    -> Option<char>
+    {
+        while self.chars.next_if(|chr| chr.is_ascii_whitespace()).is_some()
+        {
+            self.position += 1;
+        }
+
+        self.chars.peek().copied()
+
+    } /* .peek() */
+
+    /// Returns the error that the next character is not what was due.
+    fn unexpected (&mut self, expected: &str)
+    -> CubusError
+    {
+        CubusError::Unexpected(self.position, self.chars.peek().copied(), expected.to_string())
+
+    } /* .unexpected() */
+
+    /// Reads the given character after any whitespace.
+    fn expect (&mut self, chr: char)
+    -> Result<(), CubusError>
+    {
+        if self.peek() != Some(chr)
+        {
+            return Err(self.unexpected(&format!("'{}'", chr)));
+        }
+        self.chars.next();
+        self.position += 1;
+
+        Ok(())
+
+    } /* .expect() */
+
+    /// Tells whether another element of an array or object follows, after
+    /// reading the comma before it or the given closing bracket.
+    fn has_next (&mut self, close: char, isFirst: bool)
+    -> Result<bool, CubusError>
+    {
+        if self.peek() == Some(close)
+        {
+            self.expect(close)?;
+            return Ok(false);
+        }
+        if !isFirst
+        {
+            self.expect(',')?;
+        }
+
+        Ok(true)
+
+    } /* .has_next() */
+
+    /// Reads a string after any whitespace.
+    fn string (&mut self)
+    -> Result<String, CubusError>
+    {
+        if self.peek() != Some('"')
+        {
+            return Err(self.unexpected("a string"));
+        }
+        self.chars.next();
+        self.position += 1;
+
+        let mut string = String::new();
+        loop
+        {
+            let Some(chr) = self.chars.next_if(|&chr| chr >= ' ') else { return Err(self.unexpected("'\"'")) };
+            self.position += 1;
+            match chr
+            {
+                '"'     =>  break,
+                '\\'    =>  {
+                    let Some(letter) = self.chars.next_if(|&chr| "\"\\/bfnrtu".contains(chr))
+                                    else { return Err(self.unexpected("an escape sequence")) };
+                    self.position += 1;
+                    string.push(match letter
+                    {
+                        'b'     =>  '\u{8}',
+                        'f'     =>  '\u{c}',
+                        'n'     =>  '\n',
+                        'r'     =>  '\r',
+                        't'     =>  '\t',
+                        'u'     =>  self.hex_char()?,
+                        _       =>  letter
+                    });
+                },
+                _       =>  string.push(chr)
+            }
+        }
+
+        Ok(string)
+
+    } /* .string() */
+
+    /// Reads a whole number that is not negative.
+    fn number (&mut self)
+    -> Result<usize, CubusError>
+    {
+        self.peek();
+        let digits: String = self.chars.clone().take_while(char::is_ascii_digit).collect();
+        match digits.parse::<usize>()
+        {
+            Ok(number)  =>  {
+                self.chars.nth(digits.len() - 1);
+                self.position += digits.len();
+                Ok(number)
+            },
+            Err(_)      =>  Err(self.unexpected("a whole number"))
+        }
+
+    } /* .number() */
+
+    /// Reads the four hex digits of a character escaped as `\u`.
+    fn hex_char (&mut self)
+    -> Result<char, CubusError>
+    {
+        let digits: String = self.chars.clone().take(4).take_while(|chr| chr.is_ascii_hexdigit()).collect();
+        match u32::from_str_radix(&digits, 16).ok().filter(|_| digits.len() == 4).and_then(char::from_u32)
+        {
+            Some(chr)   =>  {
+                self.chars.nth(3);
+                self.position += 4;
+                Ok(chr)
+            },
+            None        =>  Err(self.unexpected("four hex digits of a character"))
+        }
+
+    } /* .hex_char() */
+
+}   /* impl JsonReader */
+
+
+/// Returns the names and algorithms of a collection in JSON, either an
+/// object of algorithms by name, e.g. `{"T-perm": "R U R' U' ..."}`, or
+/// an array of objects with a `name` and an `alg` or `moves` member, all
+/// strings, in the order they are given.
+pub fn alg_collection_of_json (text: &str)
+-> Result<Vec<(String, String)>, CubusError>
+{
+    let mut reader = JsonReader { chars: text.chars().peekable(), position: 1 };
+    let mut collection: Vec<(String, String)> = vec![];
+    match reader.peek()
+    {
+        Some('{')   =>  {
+            reader.expect('{')?;
+            while reader.has_next('}', collection.is_empty())?
+            {
+                let name = reader.string()?;
+                reader.expect(':')?;
+                collection.push((name, reader.string()?));
+            }
+        },
+        Some('[')   =>  {
+            reader.expect('[')?;
+            while reader.has_next(']', collection.is_empty())?
+            {
+                reader.expect('{')?;
+                let (mut name, mut algorithm) = (None, None);
+                let mut isFirst = true;
+                while reader.has_next('}', isFirst)?
+                {
+                    let key = reader.string()?;
+                    reader.expect(':')?;
+                    let value = reader.string()?;
+                    match key.as_str()
+                    {
+                        "name"          =>  name = Some(value),
+                        "alg" | "moves" =>  algorithm = Some(value),
+                        _               =>  ()
+                    }
+                    isFirst = false;
+                }
+                match (name, algorithm)
+                {
+                    (Some(name), Some(algorithm))
+                            =>  collection.push((name, algorithm)),
+                    _       =>  return Err(CubusError::Unexpected(reader.position - 1, Some('}'),
+                                                                  "a \"name\" and an \"alg\" member before".to_string()))
+                }
+            }
+        },
+        _           =>  return Err(reader.unexpected("'{' or '['"))
+    }
+    if reader.peek().is_some()
+    {
+        return Err(reader.unexpected("the end"));
+    }
+
+    Ok(collection)
+
+}   /* alg_collection_of_json() */
+
+
+/// Returns the frames that show the cube's moves one per second, each with
+/// the time in seconds when it shows, and `fps` frames per second in all,
+/// those in between showing each move turning.  Under each picture are the
+/// caption, if any, and the moves so far.
+#[cfg(feature = "std")]
+fn animation_frames (cube: &Cube, moves: &[Move], caption: &str, style: RenderStyle, fps: u32)
+-> Result<Vec<(f64, String)>, CubusError>
+{
+    let cubes = cubevec_of_moves(cube, moves)?;
+    let fps = fps.max(1);
+    let caption = if caption.is_empty() { String::new() } else { format!("{}\r\n", caption) };
+
+    let mut frames: Vec<(f64, String)> = vec![];
+    for (ind, cube) in cubes.iter().enumerate()
+    {
+        let mut frame: Vec<u8> = vec![];
+        draw_cube_to(&mut frame, cube, 1, 2, style);
+        write!(frame, "{}{}\r\n", caption, string_of_movevec(&moves[.. ind]));
+        frames.push((ind as f64, String::from_utf8_lossy(&frame).into_owned()));
+
+        if ind == moves.len()
+        {
+            break;
+        }
+        for step in 1 .. fps
+        {
+            let part = step as f64 / fps as f64;
+            let mut frame: Vec<u8> = vec![];
+            draw_cube_turning_to(&mut frame, cube, &moves[ind], part, 1, 2, style);
+            write!(frame, "{}{}\r\n", caption, string_of_movevec(&moves[.. ind]));
+            frames.push((ind as f64 + part, String::from_utf8_lossy(&frame).into_owned()));
+        }
+    }
+
+    Ok(frames)
+
+}   /* animation_frames() */
+
+
+/// Writes an asciinema v2 recording that plays the cube's frames one move
+/// per second, as written by write_frames(), to the given file.  With more
+/// than one frame per second, `fps`, the frames in between show each move
+/// turning.
+#[cfg(feature = "std")]
+pub fn write_cast (path: &str, cube: &Cube, moves: &[Move], style: RenderStyle, fps: u32)
+-> Result<(), CubusError>
+{
+    write_cast_captioned(path, cube, moves, "", style, fps)
+
+}   /* write_cast() */
+
+
+/// Writes the recording of write_cast() with a caption above the moves of
+/// every frame and at the start of its title.
+#[cfg(feature = "std")]
+pub fn write_cast_captioned (path: &str, cube: &Cube, moves: &[Move], caption: &str, style: RenderStyle, fps: u32)
+-> Result<(), CubusError>
+{
+    let frames = animation_frames(cube, moves, caption, style, fps)?;
+    let size = cube.size as i16;
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or(0);
+    let title = if caption.is_empty() { string_of_movevec(moves) } else { format!("{} {}", caption, string_of_movevec(moves)) };
+
+    // The width leaves room for the compass.
+    let mut file = File::create(path)?;
+    writeln!(file, "{{\"version\": 2, \"width\": {}, \"height\": {}, \"timestamp\": {}, \"title\": {}}}",
+             12 * size + 16, 6 * size + 4 + !caption.is_empty() as i16, timestamp, json_string(&title))?;
+
+    for (time, frame) in frames.iter()
+    {
+        writeln!(file, "[{:.3}, \"o\", {}]", time, json_string(frame))?;
+    }
+
+    Ok(())
+
+}   /* write_cast_captioned() */
+
+
+/// Plays the frames of write_cast_captioned() in the terminal as they
+/// come due.
+#[cfg(feature = "std")]
+pub fn play_moves (cube: &Cube, moves: &[Move], caption: &str, style: RenderStyle, fps: u32)
+-> Result<(), CubusError>
+{
+    let frames = animation_frames(cube, moves, caption, style, fps)?;
+    let mut tty = tty_out()?;
+    let mut shown = 0.0;
+    for (time, frame) in frames.iter()
+    {
+        thread::sleep(Duration::from_secs_f64(time - shown));
+        shown = *time;
+        tty.write_all(frame.as_bytes())?;
+        tty.flush()?;
+    }
+
+    Ok(())
+
+}   /* play_moves() */
+
+
+/// The names of the milestones of a layer-by-layer solve of a 3x3x3 cube,
+/// by how far solved_report() says it got, see SolvedReport::stage().
+pub const SOLVE_STAGE_NAMES: [&str; 8] = ["Start", "Cross", "F2L 1/4", "F2L 2/4", "F2L 3/4", "F2L 4/4", "OLL", "PLL"];
+
+
+/// Returns how far a 3x3x3 cube got in a layer-by-layer solve, as an index
+/// into SOLVE_STAGE_NAMES, or 0 for other cube sizes.
+fn solve_stage (cube: &Cube)
+-> usize
+{
+    cube.solved_report().map_or(0, |report| report.stage())
+
+}   /* solve_stage() */
+
+
+/// Divides a solution of the given cube into the phases of a layer-by-layer
+/// solve, each named by the milestone that it reaches: Cross, F2L 1/4 to
+/// 4/4, OLL and PLL, or a range of them, e.g. "OLL to PLL", where moves
+/// reach several at once.  Moves after the last milestone reached make a
+/// phase named "Rest".  On other than 3x3x3 cubes, the whole solution is
+/// one phase named "Solution".  No moves make no phases.
+pub fn solution_phases (cube: &Cube, solution: &[Move])
+-> Result<Vec<(String, Vec<Move>)>, CubusError>
+{
+    if solution.is_empty()
+    {
+        return Ok(vec![]);
+    }
+    if cube.size != 3
+    {
+        return Ok(vec![("Solution".to_string(), solution.to_vec())]);
+    }
+
+    let mut phases: Vec<(String, Vec<Move>)> = vec![];
+    let mut cube = cube.clone();
+    let mut best = solve_stage(&cube);
+    let mut moves: Vec<Move> = vec![];
+    for mov in solution.iter()
+    {
+        cube.apply_moves(core::slice::from_ref(mov))?;
+        moves.push(*mov);
+
+        let stage = solve_stage(&cube);
+        if stage > best
+        {
+            let name = if stage == best + 1 { SOLVE_STAGE_NAMES[stage].to_string() }
+                       else                 { format!("{} to {}", SOLVE_STAGE_NAMES[best + 1], SOLVE_STAGE_NAMES[stage]) };
+            phases.push((name, core::mem::take(&mut moves)));
+            best = stage;
+        }
+    }
+    if !moves.is_empty()
+    {
+        phases.push(("Rest".to_string(), moves));
+    }
+
+    Ok(phases)
+
+}   /* solution_phases() */
+
+
+/// Lays out the pages of a practice sheet top to bottom, starting a new
+/// page where the next item would not fit.
+#[cfg(feature = "pdf")]
+struct SheetWriter
+{
+    pages: Vec<pdf_writer::Content>,
+    top:   f32      // Where the next item goes, from the bottom of the page.
+
+}   /* SheetWriter */
+
+#[cfg(feature = "pdf")]
+impl SheetWriter
+{
+    // A4 in points, with margins of about 2 cm.
+    const WIDTH:  f32 = 595.0;
+    const HEIGHT: f32 = 842.0;
+    const MARGIN: f32 = 56.0;
+
+    /// Starts a sheet with an empty first page.
+    fn new ()
+    -> SheetWriter
+    {
+        SheetWriter { pages: vec![pdf_writer::Content::new()], top: SheetWriter::HEIGHT - SheetWriter::MARGIN }
+
+    } /* ::new() */
+
+    /// Makes room for an item of the given height, on a new page if need be,
+    /// and returns where its top goes.
+    fn room (&mut self, height: f32)
+    -> f32
+    {
+        if self.top - height < SheetWriter::MARGIN && self.top < SheetWriter::HEIGHT - SheetWriter::MARGIN
+        {
+            self.pages.push(pdf_writer::Content::new());
+            self.top = SheetWriter::HEIGHT - SheetWriter::MARGIN;
+        }
+        let top = self.top;
+        self.top -= height;
+
+        top
+
+    } /* .room() */
+
+    /// Writes a line of text whose baseline lies `size` below `top`, in the
+    /// font resource F1 (Helvetica-Bold) or F2 (Courier).  Characters that
+    /// the fonts' standard encoding lacks show as `?`.
+    fn text (&mut self, left: f32, top: f32, font: &[u8], size: f32, text: &str)
+    {
+        let bytes: Vec<u8> = text.chars().map(|chr| if chr.is_ascii() { chr as u8 } else { b'?' }).collect();
+        self.pages.last_mut().unwrap()
+            .begin_text()
+            .set_font(pdf_writer::Name(font), size)
+            .next_line(left, top - size)
+            .show(pdf_writer::Str(&bytes))
+            .end_text();
+
+    } /* .text() */
+
+    /// Returns moves broken into lines between moves where they would
+    /// overrun the given width in Courier of the given size.
+    fn lines_of_moves (width: f32, size: f32, text: &str)
+    -> Vec<String>
+    {
+        // Courier advances 0.6 em per character.
+        let perLine = ((width / (0.6 * size)) as usize).max(1);
+        let mut lines: Vec<String> = vec![String::new()];
+        for word in text.split_whitespace()
+        {
+            let line = lines.last_mut().unwrap();
+            if !line.is_empty() && line.len() + 1 + word.len() > perLine
+            {
+                lines.push(word.to_string());
+            }
+            else
+            {
+                if !line.is_empty()
+                {
+                    line.push(' ');
+                }
+                line.push_str(word);
+            }
+        }
+
+        lines
+
+    } /* ::lines_of_moves() */
+
+    /// Writes moves in Courier, in the lines of lines_of_moves(), and
+    /// returns their height.
+    fn moves_text (&mut self, left: f32, top: f32, width: f32, size: f32, text: &str)
+    -> f32
+    {
+        let lines = SheetWriter::lines_of_moves(width, size, text);
+        for (ind, line) in lines.iter().enumerate()
+        {
+            self.text(left, top - ind as f32 * 1.25 * size, b"F2", size, line);
+        }
+
+        lines.len() as f32 * 1.25 * size
+
+    } /* .moves_text() */
+
+    /// Draws the net of a cube's faces as Display prints it, Up above Left,
+    /// Front, Right and Back, and Down below, with stickers of the given
+    /// width, and returns its height.
+    fn net (&mut self, cube: &Cube, left: f32, top: f32, sticker: f32)
+    -> f32
+    {
+        let size = cube.size as f32;
+        let gap = sticker / 2.0;
+        let content = self.pages.last_mut().unwrap();
+        content.set_stroke_gray(0.0).set_line_width(0.5);
+        for (face, faceRow, faceCol) in [(Face::Up,    0.0, 1.0), (Face::Left,  1.0, 0.0), (Face::Front, 1.0, 1.0),
+                                         (Face::Right, 1.0, 2.0), (Face::Back,  1.0, 3.0), (Face::Down,  2.0, 1.0)]
+        {
+            let names: Vec<Huename> = cube.stickers(face).collect();
+            for (ind, name) in names.into_iter().enumerate()
+            {
+                let (row, col) = ((ind / cube.size as usize) as f32, (ind % cube.size as usize) as f32);
+                let (red, green, blue) = name.rgb();
+                content.set_fill_rgb(red as f32 / 255.0, green as f32 / 255.0, blue as f32 / 255.0)
+                       .rect(left + faceCol * (size * sticker + gap) + col * sticker,
+                             top - faceRow * (size * sticker + gap) - (row + 1.0) * sticker,
+                             sticker, sticker)
+                       .fill_nonzero_and_stroke();
+            }
+        }
+
+        3.0 * (size * sticker + gap)
+
+    } /* .net() */
+
+    /// Draws a line for handwriting across the page, at the given height.
+    fn rule (&mut self, top: f32)
+    {
+        self.pages.last_mut().unwrap()
+            .set_stroke_gray(0.6)
+            .set_line_width(0.5)
+            .move_to(SheetWriter::MARGIN, top)
+            .line_to(SheetWriter::WIDTH - SheetWriter::MARGIN, top)
+            .stroke();
+
+    } /* .rule() */
+
+    /// Returns the PDF document of the pages written.
+    fn finish (self)
+    -> Vec<u8>
+    {
+        let catalogId = pdf_writer::Ref::new(1);
+        let treeId    = pdf_writer::Ref::new(2);
+        let boldId    = pdf_writer::Ref::new(3);
+        let monoId    = pdf_writer::Ref::new(4);
+        let pageIds: Vec<pdf_writer::Ref> = (0 .. self.pages.len()).map(|ind| pdf_writer::Ref::new(5 + 2 * ind as i32)).collect();
+
+        let mut pdf = pdf_writer::Pdf::new();
+        pdf.catalog(catalogId).pages(treeId);
+        pdf.pages(treeId).kids(pageIds.iter().copied()).count(pageIds.len() as i32);
+        pdf.type1_font(boldId).base_font(pdf_writer::Name(b"Helvetica-Bold"));
+        pdf.type1_font(monoId).base_font(pdf_writer::Name(b"Courier"));
+        for (content, pageId) in self.pages.into_iter().zip(pageIds)
+        {
+            let contentId = pdf_writer::Ref::new(pageId.get() + 1);
+            let mut page = pdf.page(pageId);
+            page.media_box(pdf_writer::Rect::new(0.0, 0.0, SheetWriter::WIDTH, SheetWriter::HEIGHT))
+                .parent(treeId)
+                .contents(contentId);
+            page.resources().fonts().pair(pdf_writer::Name(b"F1"), boldId).pair(pdf_writer::Name(b"F2"), monoId);
+            drop(page);
+            pdf.stream(contentId, &content.finish());
+        }
+
+        pdf.finish()
+
+    } /* .finish() */
+
+}   /* impl SheetWriter */
+
+
+/// Returns a printable PDF practice sheet, A4 in size: the scramble as
+/// given, the net of the cube it leaves, which is the cube given, and the
+/// solution divided into phases by solution_phases(), each beside the net
+/// of the cube after it.  `format` writes the moves of a phase.  Without a
+/// solution, the sheet has ruled lines to write one on.  The cube's colors
+/// are those of `scheme`.
+#[cfg(feature = "pdf")]
+pub fn practice_sheet_pdf (cube: &Cube, scramble: &str, solution: &[Move], scheme: &ColorScheme,
+                           format: &dyn Fn(&[Move]) -> String)
+-> Result<Vec<u8>, CubusError>
+{
+    let phases = solution_phases(cube, solution)?;
+    let left  = SheetWriter::MARGIN;
+    let width = SheetWriter::WIDTH - 2.0 * SheetWriter::MARGIN;
+
+    let mut sheet = SheetWriter::new();
+    let top = sheet.room(36.0);
+    sheet.text(left, top, b"F1", 18.0, &format!("Practice sheet, {}x{}x{} cube", cube.size, cube.size, cube.size));
+
+    let top = sheet.room(18.0);
+    sheet.text(left, top, b"F1", 12.0, "Scramble");
+    let lineNum = SheetWriter::lines_of_moves(width, 10.0, scramble).len();
+    let top = sheet.room(lineNum as f32 * 12.5 + 12.0);
+    sheet.moves_text(left, top, width, 10.0, scramble);
+
+    // The net is 180 points high whatever the cube's size.
+    let sticker = 180.0 / (3.0 * cube.size as f32 + 1.0);
+    let top = sheet.room(180.0 + 24.0);
+    sheet.net(&scheme.recolored(cube), left, top, sticker);
+
+    let top = sheet.room(24.0);
+    sheet.text(left, top, b"F1", 12.0, "Solution");
+    if phases.is_empty()
+    {
+        for _ in 0 .. 10
+        {
+            let top = sheet.room(28.0);
+            sheet.rule(top - 24.0);
+        }
+    }
+
+    // Each phase beside the net of the cube after it, 72 points high.
+    let sticker = 72.0 / (3.0 * cube.size as f32 + 1.0);
+    let netW = 4.0 * cube.size as f32 * sticker + 1.5 * sticker;
+    let mut after = cube.clone();
+    for (name, moves) in phases.iter()
+    {
+        after.apply_moves(moves)?;
+        let movesStr = format(moves);
+        let lineNum = SheetWriter::lines_of_moves(width - netW - 12.0, 10.0, &movesStr).len();
+        let top = sheet.room((lineNum as f32 * 12.5 + 18.0).max(72.0) + 12.0);
+        sheet.net(&scheme.recolored(&after), left, top, sticker);
+        sheet.text(left + netW + 12.0, top, b"F1", 11.0,
+                   &format!("{} ({} HTM)", name, MoveMetrics::of_movevec(moves, cube.size).htm));
+        sheet.moves_text(left + netW + 12.0, top - 18.0, width - netW - 12.0, 10.0, &movesStr);
+    }
+
+    Ok(sheet.finish())
+
+}   /* practice_sheet_pdf() */
+
+
+/// Returns a table of how often each layer of a cube of the given size is
+/// turned either way by the given moves, with totals per axis.  Rotations
+/// of the whole cube are counted apart, in a row of their own if any.
+pub fn turn_count_table (moves: &[Move], size: Coord)
+-> String
+{
+    let mut table = String::from("Layer   +90°   -90°\n");
+    for axis in ['X', 'Y', 'Z']
+    {
+        let mut axisSum = [0, 0];
+        for axval in 0 .. size
+        {
+            let count = |axdir: char| moves.iter().filter(|mov| mov.axdir == axdir && mov.axval == axval).count();
+            let counts = [count(axis), count(axis.to_ascii_lowercase())];
+            table.push_str(&format!("{}{}     {:>5}  {:>5}\n", axis, axval, counts[0], counts[1]));
+            axisSum[0] += counts[0];
+            axisSum[1] += counts[1];
+        }
+        let count = |axdir: char| moves.iter().filter(|mov| mov.axdir == axdir && mov.is_rotation()).count();
+        let counts = [count(axis), count(axis.to_ascii_lowercase())];
+        if counts != [0, 0]
+        {
+            table.push_str(&format!("{}*     {:>5}  {:>5}\n", axis, counts[0], counts[1]));
+        }
+        table.push_str(&format!("{}      {:>5}  {:>5}\n", axis, axisSum[0], axisSum[1]));
+    }
+
+    table
+
+}   /* turn_count_table() */
+
+
+/// The length of a move sequence in the metrics of speedcubers.  Each
+/// counts the turns that a run of moves about one axis nets, where a turn
+/// is a block of adjacent layers turned alike.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub struct MoveMetrics
+{
+    pub htm: usize,     // Half-turn metric: outer block turns of any angle.
+    pub qtm: usize,     // Quarter-turn metric: outer block quarter turns.
+    pub stm: usize,     // Slice-turn metric: block turns of any angle.
+    pub etm: usize      // Execution metric: block turns, whole-cube ones too.
+
+}   /* MoveMetrics */
+
+impl MoveMetrics
+{
+    /// Measures the given moves of a cube of the given size.  Moves about
+    /// the same axis in a row make a single step, whose turns are blocks of
+    /// layers with the same net turn.  An inner block takes two outer
+    /// blocks in HTM and QTM, which turn the whole cube alongside, and a
+    /// half turn two quarter turns in QTM.  A step with a rotation may leave
+    /// a whole-cube turn out of its blocks in STM, and in ETM counts it as
+    /// one more turn.  A block of all layers is a whole-cube turn too.
+    pub fn of_movevec (moves: &[Move], size: Coord)
+    -> MoveMetrics
+    {
+        let axmax = size - 1;
+        let mut metrics = MoveMetrics { htm: 0, qtm: 0, stm: 0, etm: 0 };
+
+        let mut start = 0;
+        while start < moves.len()
+        {
+            let axis = moves[start].axdir.to_ascii_uppercase();
+            let mut end = start;
+            let mut turns = vec![0_u8; size as usize];
+            let mut isTurned = false;
+            while end < moves.len() && moves[end].axdir.to_ascii_uppercase() == axis
+            {
+                let mov = &moves[end];
+                let layers = if mov.is_rotation() { 0 ..= axmax } else { mov.axval ..= mov.axval };
+                for axval in layers
+                {
+                    let turn = &mut turns[axval as usize];
+                    *turn = (*turn + if mov.axdir == axis {1} else {3}) % 4;
+                }
+                isTurned |= mov.is_rotation();
+                end += 1;
+            }
+
+            // The blocks of layers that turn alike, if the whole cube turns
+            // by `offset` quarter turns alongside, as (first, last, turn).
+            let blocks = |offset: u8| {
+                let mut blocks: Vec<(usize, usize, u8)> = vec![];
+                for (axval, &turn) in turns.iter().enumerate()
+                {
+                    let turn = (turn + 4 - offset) % 4;
+                    match blocks.last_mut()
+                    {
+                        Some((_, last, other)) if *last + 1 == axval && *other == turn
+                                =>  *last = axval,
+                        _       =>  if turn != 0 { blocks.push((axval, axval, turn)); }
+                    }
+                }
+                blocks
+            };
+            let outerCost = |blocks: &[(usize, usize, u8)], isQuarter: bool| -> usize {
+                blocks.iter().map(|&(first, last, turn)| {
+                    let quarters = if isQuarter && turn == 2 {2} else {1};
+                    if first == 0 || last == axmax as usize { quarters } else { 2 * quarters }
+                }).sum()
+            };
+
+            // Slices only go with a whole-cube turn where the step has one.
+            // A block of all layers is a whole-cube turn, free in STM.
+            let offsets = if isTurned { 0 .. 4 } else { 0 .. 1 };
+            let sliceCount = |blocks: &[(usize, usize, u8)]| blocks.iter().filter(|&&(first, last, _)| last - first < axmax as usize).count();
+            metrics.htm += (0 .. 4).map(|offset| outerCost(&blocks(offset), false)).min().unwrap();
+            metrics.qtm += (0 .. 4).map(|offset| outerCost(&blocks(offset), true)).min().unwrap();
+            metrics.stm += offsets.clone().map(|offset| sliceCount(&blocks(offset))).min().unwrap();
+            metrics.etm += offsets.map(|offset| blocks(offset).len() + (offset != 0) as usize).min().unwrap();
+            start = end;
+        }
+
+        metrics
+
+    } /* ::of_movevec() */
+
+    /// Returns the metrics as a single line of text.
+    pub fn as_string (&self)
+    -> String
+    {
+        format!("{} HTM, {} QTM, {} STM, {} ETM", self.htm, self.qtm, self.stm, self.etm)
+
+    } /* .as_string() */
+
+}   /* impl MoveMetrics */
+
+
+/// Returns the lowercase hexadecimal representation of the given bytes.
+pub fn hex_of_bytes (bytes: &[u8])
+-> String
+{
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+
+}   /* hex_of_bytes() */
+
+
+/// Returns the bytes represented by the given hexadecimal string.
+pub fn bytes_of_hex (hex: &str)
+-> Option<Vec<u8>>
+{
+    if hex.len() % 2 != 0 || !hex.is_ascii()
+    {
+        return None;
+    }
+
+    (0 .. hex.len()).step_by(2)
+                    .map(|ind| u8::from_str_radix(&hex[ind .. ind + 2], 16).ok())
+                    .collect()
+
+}   /* bytes_of_hex() */
+
+
+/// Returns the product of two elements of GF(256) modulo the polynomial
+/// x⁸ + x⁴ + x³ + x² + 1 of QR codes.
+fn qr_gf_mul (lhs: u8, rhs: u8)
+-> u8
+{
+    let mut product: u16 = 0;
+    for bit in (0 .. 8).rev()
+    {
+        product = (product << 1) ^ ((product >> 7) * 0x11D);
+        product ^= ((rhs as u16 >> bit) & 1) * lhs as u16;
+    }
+
+    product as u8
+
+}   /* qr_gf_mul() */
+
+
+/// Returns the Reed-Solomon error correction codewords of the given data
+/// codewords, `count` of them.
+fn qr_ec_codewords (data: &[u8], count: usize)
+-> Vec<u8>
+{
+    // The generator polynomial (x - α⁰)·…·(x - αⁿ⁻¹), without its leading 1.
+    let mut divisor = vec![0u8; count];
+    divisor[count - 1] = 1;
+    let mut root: u8 = 1;
+    for _ in 0 .. count
+    {
+        for ind in 0 .. count
+        {
+            divisor[ind] = qr_gf_mul(divisor[ind], root);
+            if ind + 1 < count
+            {
+                divisor[ind] ^= divisor[ind + 1];
+            }
+        }
+        root = qr_gf_mul(root, 0x02);
+    }
+
+    let mut remainder = vec![0u8; count];
+    for &byte in data.iter()
+    {
+        let factor = byte ^ remainder.remove(0);
+        remainder.push(0);
+        for (rem, &coef) in remainder.iter_mut().zip(divisor.iter())
+        {
+            *rem ^= qr_gf_mul(coef, factor);
+        }
+    }
+
+    remainder
+
+}   /* qr_ec_codewords() */
+
+
+/// Tells whether QR mask pattern `mask` inverts the module at column `x`
+/// and row `y`.
+fn qr_mask_inverts (mask: usize, x: usize, y: usize)
+-> bool
+{
+    match mask
+    {
+        0   =>  (x + y) % 2 == 0,
+        1   =>  y % 2 == 0,
+        2   =>  x % 3 == 0,
+        3   =>  (x + y) % 3 == 0,
+        4   =>  (x / 3 + y / 2) % 2 == 0,
+        5   =>  x * y % 2 + x * y % 3 == 0,
+        6   =>  (x * y % 2 + x * y % 3) % 2 == 0,
+        _   =>  ((x + y) % 2 + x * y % 3) % 2 == 0
+    }
+
+}   /* qr_mask_inverts() */
+
+
+/// Returns the columns, rows and colors of the modules that carry the
+/// format information of level L with mask pattern `mask`, in both copies,
+/// on a QR symbol of the given size, and of the dark module beside them.
+fn qr_format_modules (mask: usize, size: usize)
+-> Vec<(usize, usize, bool)>
+{
+    let data = 0b01 << 3 | mask;
+    let mut rem = data;
+    for _ in 0 .. 10
+    {
+        rem = (rem << 1) ^ ((rem >> 9) * 0x537);
+    }
+    let bits = (data << 10 | rem) ^ 0x5412;
+    let bit = |ind: usize| (bits >> ind) & 1 != 0;
+
+    let mut modules: Vec<(usize, usize, bool)> = vec![];
+    modules.extend((0 ..= 5).map(|ind| (8, ind, bit(ind))));
+    modules.extend([(8, 7, bit(6)), (8, 8, bit(7)), (7, 8, bit(8))]);
+    modules.extend((9 .. 15).map(|ind| (14 - ind, 8, bit(ind))));
+    modules.extend((0 .. 8).map(|ind| (size - 1 - ind, 8, bit(ind))));
+    modules.extend((8 .. 15).map(|ind| (8, size - 15 + ind, bit(ind))));
+    modules.push((8, size - 8, true));
+
+    modules
+
+}   /* qr_format_modules() */
+
+
+/// Returns the penalty of a QR symbol for runs of five or more modules
+/// alike, 2x2 blocks alike, and an imbalance of dark and light modules.
+/// The penalty for finder-like patterns is left out, which only makes the
+/// chosen mask less than ideal, never the symbol unreadable.
+#[allow(clippy::needless_range_loop, reason = "a line is a row or a column, as isColumn tells")]
+fn qr_penalty (modules: &[Vec<bool>])
+-> usize
+{
+    let size = modules.len();
+    let mut penalty = 0;
+    for isColumn in [false, true]
+    {
+        for line in 0 .. size
+        {
+            let mut run = 1;
+            for ind in 1 .. size
+            {
+                let module = |ind: usize| if isColumn { modules[ind][line] } else { modules[line][ind] };
+                if module(ind) == module(ind - 1)
+                {
+                    run += 1;
+                    penalty += match run { 5 => 3, 6 .. => 1, _ => 0 };
+                }
+                else
+                {
+                    run = 1;
+                }
+            }
+        }
+    }
+    for y in 1 .. size
+    {
+        for x in 1 .. size
+        {
+            let module = modules[y][x];
+            if modules[y - 1][x] == module && modules[y][x - 1] == module && modules[y - 1][x - 1] == module
+            {
+                penalty += 3;
+            }
+        }
+    }
+    let dark = modules.iter().flatten().filter(|&&module| module).count();
+    let total = size * size;
+    penalty += 10 * ((dark * 20).abs_diff(total * 10) / total);
+
+    penalty
+
+}   /* qr_penalty() */
+
+
+/// Returns the modules of a QR code that encodes the given bytes, row by
+/// row with dark modules as true and without the quiet zone around them.
+/// The code is of the smallest version from 1 to 10 that holds the bytes
+/// in byte mode at error correction level L, with the mask of the least
+/// penalty.
+pub fn qr_modules (data: &[u8])
+-> Result<Vec<Vec<bool>>, CubusError>
+{
+    // Total codewords, error correction codewords per block, blocks and
+    // alignment pattern positions of versions 1 to 10 at level L.
+    const VERSIONS: [(usize, usize, usize, &[usize]); 10] = [
+        (26,  7, 1, &[]),        (44, 10, 1, &[6, 18]),  (70, 15, 1, &[6, 22]),
+        (100, 20, 1, &[6, 26]),  (134, 26, 1, &[6, 30]), (172, 18, 2, &[6, 34]),
+        (196, 20, 2, &[6, 22, 38]), (242, 24, 2, &[6, 24, 42]),
+        (292, 30, 2, &[6, 26, 46]), (346, 18, 4, &[6, 28, 50])
+    ];
+    let fits = |&(_, (total, ecLen, blocks, _)): &(usize, (usize, usize, usize, &[usize]))| {
+        let countBits = if total < 346 {8} else {16};
+        4 + countBits + 8 * data.len() <= 8 * (total - ecLen * blocks)
+    };
+    let Some((ind, (total, ecLen, blocks, aligns))) = VERSIONS.into_iter().enumerate().find(fits)
+    else
+    {
+        return Err(CubusError::QrOverflow(data.len()));
+    };
+    let version = ind + 1;
+    let size = 17 + 4 * version;
+
+    // Mode, count, data, terminator, and pad bytes fill the data codewords.
+    let dataLen = total - ecLen * blocks;
+    let mut bits: Vec<bool> = vec![];
+    let mut push = |value: usize, count: usize| bits.extend((0 .. count).rev().map(|bit| (value >> bit) & 1 != 0));
+    push(0b0100, 4);
+    push(data.len(), if version < 10 {8} else {16});
+    data.iter().for_each(|&byte| push(byte as usize, 8));
+    let mut codewords: Vec<u8> = bits.chunks(8).map(|chunk| {
+        chunk.iter().enumerate().fold(0u8, |byte, (bit, &isSet)| byte | ((isSet as u8) << (7 - bit)))
+    }).collect();
+    codewords.extend([0xEC, 0x11].into_iter().cycle().take(dataLen - codewords.len()));
+
+    // The data splits into blocks, the last ones a codeword longer, whose
+    // codewords and then error correction codewords are interleaved.
+    let shortLen = dataLen / blocks;
+    let longCount = dataLen % blocks;
+    let mut start = 0;
+    let mut dataBlocks: Vec<&[u8]> = vec![];
+    for block in 0 .. blocks
+    {
+        let len = shortLen + (block >= blocks - longCount) as usize;
+        dataBlocks.push(&codewords[start .. start + len]);
+        start += len;
+    }
+    let ecBlocks: Vec<Vec<u8>> = dataBlocks.iter().map(|block| qr_ec_codewords(block, ecLen)).collect();
+    let mut stream: Vec<u8> = Vec::with_capacity(total);
+    for ind in 0 ..= shortLen
+    {
+        stream.extend(dataBlocks.iter().filter_map(|block| block.get(ind)));
+    }
+    for ind in 0 .. ecLen
+    {
+        stream.extend(ecBlocks.iter().map(|block| block[ind]));
+    }
+
+    // Finder, timing and alignment patterns, version and format information.
+    let mut modules = vec![vec![false; size]; size];
+    let mut isFunction = vec![vec![false; size]; size];
+    let mut set = |modules: &mut Vec<Vec<bool>>, x: usize, y: usize, isDark: bool| {
+        modules[y][x] = isDark;
+        isFunction[y][x] = true;
+    };
+    for ind in 0 .. size
+    {
+        set(&mut modules, 6, ind, ind % 2 == 0);
+        set(&mut modules, ind, 6, ind % 2 == 0);
+    }
+    for (cx, cy) in [(3, 3), (size - 4, 3), (3, size - 4)]
+    {
+        for dy in -4i32 ..= 4
+        {
+            for dx in -4i32 ..= 4
+            {
+                let (x, y) = (cx as i32 + dx, cy as i32 + dy);
+                if 0 <= x && x < size as i32 && 0 <= y && y < size as i32
+                {
+                    let dist = dx.abs().max(dy.abs());
+                    set(&mut modules, x as usize, y as usize, dist != 2 && dist != 4);
+                }
+            }
+        }
+    }
+    let last = aligns.len().saturating_sub(1);
+    for (row, &cy) in aligns.iter().enumerate()
+    {
+        for (col, &cx) in aligns.iter().enumerate()
+        {
+            if (row, col) == (0, 0) || (row, col) == (0, last) || (row, col) == (last, 0)
+            {
+                continue;
+            }
+            for dy in -2i32 ..= 2
+            {
+                for dx in -2i32 ..= 2
+                {
+                    set(&mut modules, (cx as i32 + dx) as usize, (cy as i32 + dy) as usize, dx.abs().max(dy.abs()) != 1);
+                }
+            }
+        }
+    }
+    if version >= 7
+    {
+        let mut rem = version;
+        for _ in 0 .. 12
+        {
+            rem = (rem << 1) ^ ((rem >> 11) * 0x1F25);
+        }
+        let bits = version << 12 | rem;
+        for bit in 0 .. 18
+        {
+            let isDark = (bits >> bit) & 1 != 0;
+            let (far, near) = (size - 11 + bit % 3, bit / 3);
+            set(&mut modules, far, near, isDark);
+            set(&mut modules, near, far, isDark);
+        }
+    }
+
+    for (x, y, isDark) in qr_format_modules(0, size)
+    {
+        set(&mut modules, x, y, isDark);
+    }
+
+    // The codewords zigzag up and down two columns at a time from the right,
+    // skipping the vertical timing pattern.
+    let mut ind = 0;
+    let mut right = size - 1;
+    while right >= 1
+    {
+        if right == 6
+        {
+            right = 5;
+        }
+        for vert in 0 .. size
+        {
+            for x in [right, right - 1]
+            {
+                let isUpward = (right + 1) & 2 == 0;
+                let y = if isUpward { size - 1 - vert } else { vert };
+                if !isFunction[y][x] && ind < 8 * stream.len()
+                {
+                    modules[y][x] = (stream[ind >> 3] >> (7 - (ind & 7))) & 1 != 0;
+                    ind += 1;
+                }
+            }
+        }
+        if right < 2
+        {
+            break;
+        }
+        right -= 2;
+    }
+
+    // The mask of the least penalty goes with its format information.
+    let masked = |mask: usize| -> Vec<Vec<bool>> {
+        let mut masked = modules.clone();
+        for (y, row) in masked.iter_mut().enumerate()
+        {
+            for (x, module) in row.iter_mut().enumerate()
+            {
+                *module ^= !isFunction[y][x] && qr_mask_inverts(mask, x, y);
+            }
+        }
+        for (x, y, isDark) in qr_format_modules(mask, size)
+        {
+            masked[y][x] = isDark;
+        }
+        masked
+    };
+
+    Ok((0 .. 8).map(masked).min_by_key(|masked| qr_penalty(masked)).unwrap())
+
+}   /* qr_modules() */
+
+
+/// The width in modules of the light quiet zone round a QR code.
+const QR_QUIET: usize = 4;
+
+
+/// Tells whether the module at x, y of a QR code of qr_modules() is light,
+/// counting from the outer corner of its quiet zone.
+fn qr_is_light (modules: &[Vec<bool>], x: usize, y: usize)
+-> bool
+{
+    let size = modules.len();
+
+    x < QR_QUIET || y < QR_QUIET || size + QR_QUIET <= x || size + QR_QUIET <= y || !modules[y - QR_QUIET][x - QR_QUIET]
+
+}   /* qr_is_light() */
+
+
+/// Returns the QR code of qr_modules() in block characters, two rows of
+/// modules to a line, inside a quiet zone of four modules.  Light modules
+/// are drawn and dark ones left blank, so that the code reads on a dark
+/// terminal background, or with `isDarkOnLight` the other way round, for
+/// a light one.
+pub fn qr_string (modules: &[Vec<bool>], isDarkOnLight: bool)
+-> String
+{
+    let extent = modules.len() + 2 * QR_QUIET;
+    let isDrawn = |x: usize, y: usize| y < extent && qr_is_light(modules, x, y) != isDarkOnLight;
+
+    let mut string = String::new();
+    for y in (0 .. extent).step_by(2)
+    {
+        for x in 0 .. extent
+        {
+            string.push(match (isDrawn(x, y), isDrawn(x, y + 1))
+            {
+                (true, true)    =>  '█',
+                (true, false)   =>  '▀',
+                (false, true)   =>  '▄',
+                (false, false)  =>  ' '
+            });
+        }
+        string.push('\n');
+    }
+
+    string
+
+}   /* qr_string() */
+
+
+/// Returns the CRC-32 of the given bytes, which ends each PNG chunk.
+fn crc32 (bytes: &[u8])
+-> u32
+{
+    let mut crc = !0u32;
+    for &byte in bytes.iter()
+    {
+        crc ^= byte as u32;
+        for _ in 0 .. 8
+        {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+
+    !crc
+
+}   /* crc32() */
+
+
+/// Returns the given bytes as a zlib stream of stored deflate blocks, which
+/// leave them uncompressed, as the image data of a PNG file.
+fn zlib_stored (bytes: &[u8])
+-> Vec<u8>
+{
+    let mut stream: Vec<u8> = vec![0x78, 0x01];
+    let mut blocks = bytes.chunks(0xFFFF).peekable();
+    if blocks.peek().is_none()
+    {
+        stream.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+    }
+    while let Some(block) = blocks.next()
+    {
+        let len = block.len() as u16;
+        stream.push(blocks.peek().is_none() as u8);
+        stream.extend_from_slice(&len.to_le_bytes());
+        stream.extend_from_slice(&(!len).to_le_bytes());
+        stream.extend_from_slice(block);
+    }
+
+    // The Adler-32 checksum of the uncompressed bytes.
+    let (mut lo, mut hi) = (1u32, 0u32);
+    for &byte in bytes.iter()
+    {
+        lo = (lo + byte as u32) % 65521;
+        hi = (hi + lo) % 65521;
+    }
+    stream.extend_from_slice(&((hi << 16) | lo).to_be_bytes());
+
+    stream
+
+}   /* zlib_stored() */
+
+
+/// Returns the QR code of qr_modules() as a PNG image in black and white,
+/// `scale` pixels to a module, inside a quiet zone of four modules.
+pub fn qr_png (modules: &[Vec<bool>], scale: usize)
+-> Vec<u8>
+{
+    let extent = modules.len() + 2 * QR_QUIET;
+    let width = extent * scale;
+
+    // Each row of 8-bit gray pixels follows a byte that filters it by none.
+    let mut pixels: Vec<u8> = Vec::with_capacity(width * (width + 1));
+    for y in 0 .. width
+    {
+        pixels.push(0);
+        pixels.extend((0 .. width).map(|x| if qr_is_light(modules, x / scale, y / scale) {0xFF} else {0x00}));
+    }
+
+    let mut header: Vec<u8> = vec![];
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&[8, 0, 0, 0, 0]);
+
+    let mut png: Vec<u8> = b"\x89PNG\r\n\x1A\n".to_vec();
+    for (kind, data) in [(b"IHDR", header), (b"IDAT", zlib_stored(&pixels)), (b"IEND", vec![])]
+    {
+        let mut chunk: Vec<u8> = kind.to_vec();
+        chunk.extend_from_slice(&data);
+        png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        png.extend_from_slice(&chunk);
+        png.extend_from_slice(&crc32(&chunk).to_be_bytes());
+    }
+
+    png
+
+}   /* qr_png() */
+
+
+/// How CubeWidget draws a cube.
+#[cfg(feature = "cubus-egui")]
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum CubeView
+{
+    Net,                            // The faces unfolded, Up above Left, Front, Right and Back, Down below.
+    Isometric                       // The Up, Front and Right faces seen from above the front right corner.
+
+}   /* CubeView */
+
+
+/// An egui widget that draws a cube as a net or isometric and turns clicks
+/// on its stickers into moves: a click turns the face of the sticker
+/// clockwise, a secondary click counterclockwise.  show() returns the
+/// moves, which the app applies to its cube, e.g.
+///
+/// ```ignore
+/// let (_, moves) = CubeWidget::new(&cube).view(CubeView::Isometric).show(ui);
+/// cube.apply_moves(&moves)?;
+/// ```
+#[cfg(feature = "cubus-egui")]
+pub struct CubeWidget<'a>
+{
+    cube:    &'a Cube,
+    view:    CubeView,
+    sticker: f32                    // Edge length of a sticker, in points.
+
+}   /* CubeWidget */
+
+#[cfg(feature = "cubus-egui")]
+impl<'a> CubeWidget<'a>
+{
+    /// CubeWidget constructor, drawing the cube as a net with stickers of
+    /// 16 points.
+    pub fn new (cube: &'a Cube)
+    -> CubeWidget<'a>
+    {
+        CubeWidget { cube, view: CubeView::Net, sticker: 16.0 }
+
+    } /* ::new() */
+
+    /// Returns the widget drawing the cube in the given view.
+    pub fn view (self, view: CubeView)
+    -> CubeWidget<'a>
+    {
+        CubeWidget { view, ..self }
+
+    } /* .view() */
+
+    /// Returns the widget drawing stickers of the given edge length.
+    pub fn sticker_size (self, sticker: f32)
+    -> CubeWidget<'a>
+    {
+        CubeWidget { sticker, ..self }
+
+    } /* .sticker_size() */
+
+    /// Returns the size that the widget takes.
+    fn size (&self)
+    -> egui::Vec2
+    {
+        let edge = self.cube.size as f32 * self.sticker;
+        match self.view
+        {
+            CubeView::Net       =>  egui::vec2(4.0 * edge + 1.5 * self.sticker, 3.0 * edge + self.sticker),
+            CubeView::Isometric =>  egui::vec2(2.0 * edge * 0.866, 2.0 * edge)
+        }
+
+    } /* .size() */
+
+    /// Returns the face, color and corners of each sticker that the widget
+    /// draws in the given rectangle.
+    fn quads (&self, rect: egui::Rect)
+    -> Vec<(Face, Huename, [egui::Pos2; 4])>
+    {
+        let size = self.cube.size;
+        let axmax = size - 1;
+        let unit = self.sticker;
+        let gap = unit / 2.0;
+        let edge = size as f32 * unit;
+        let brickAt = self.cube.bricks_by_loc();
+
+        let mut quads = vec![];
+        match self.view
+        {
+            CubeView::Net       =>  {
+                for (face, faceRow, faceCol) in [(Face::Up,    0.0, 1.0), (Face::Left,  1.0, 0.0), (Face::Front, 1.0, 1.0),
+                                                 (Face::Right, 1.0, 2.0), (Face::Back,  1.0, 3.0), (Face::Down,  2.0, 1.0)]
+                {
+                    for (ind, name) in self.cube.stickers(face).enumerate()
+                    {
+                        let (row, col) = ((ind / size as usize) as f32, (ind % size as usize) as f32);
+                        let min = rect.min + egui::vec2(faceCol * (edge + gap) + col * unit, faceRow * (edge + gap) + row * unit);
+                        quads.push((face, name, [min, min + egui::vec2(unit, 0.0), min + egui::vec2(unit, unit), min + egui::vec2(0.0, unit)]));
+                    }
+                }
+            },
+            CubeView::Isometric =>  {
+                // X points right and down, Z left and down, Y up.
+                let origin = rect.min + egui::vec2(edge * 0.866, edge);
+                let point = |x: f32, y: f32, z: f32| origin + egui::vec2(0.866 * (x - z), 0.5 * (x + z) - y) * unit;
+                let top = size as f32;
+                for face in [Face::Up, Face::Front, Face::Right]
+                {
+                    for row in 0 ..= axmax
+                    {
+                        for col in 0 ..= axmax
+                        {
+                            let loc = face_loc(face as usize, row, col, axmax);
+                            let name = hue_faces(&brickAt[loc_index(&loc, size)].unwrap().curHue)[face as usize];
+                            let (x, y, z) = (loc.x as f32, loc.y as f32, loc.z as f32);
+                            quads.push((face, name, match face
+                            {
+                                Face::Up    =>  [point(x, top, z), point(x + 1.0, top, z), point(x + 1.0, top, z + 1.0), point(x, top, z + 1.0)],
+                                Face::Front =>  [point(x, y, top), point(x + 1.0, y, top), point(x + 1.0, y + 1.0, top), point(x, y + 1.0, top)],
+                                _           =>  [point(top, y, z), point(top, y, z + 1.0), point(top, y + 1.0, z + 1.0), point(top, y + 1.0, z)]
+                            }));
+                        }
+                    }
+                }
+            }
+        }
+
+        quads
+
+    } /* .quads() */
+
+    /// Returns the moves of a click at the given position among the
+    /// stickers: a turn of the face of the sticker there, counterclockwise
+    /// for a secondary click, or none off the stickers.
+    fn moves_at (&self, quads: &[(Face, Huename, [egui::Pos2; 4])], pos: egui::Pos2, isSecondary: bool)
+    -> Vec<Move>
+    {
+        let isInside = |points: &[egui::Pos2; 4]| {
+            let sides: Vec<f32> = (0 .. 4).map(|ind| (points[(ind + 1) % 4] - points[ind]).rot90().dot(pos - points[ind])).collect();
+            sides.iter().all(|&side| side >= 0.0) || sides.iter().all(|&side| side <= 0.0)
+        };
+
+        match quads.iter().find(|(_, _, points)| isInside(points))
+        {
+            Some(&(face, _, _)) =>  {
+                let turn = movevec_of_face("RLUDFB".chars().nth(face as usize).unwrap(), 1, 1, self.cube.size - 1).unwrap();
+                if isSecondary { movevec_inverted(&turn) } else { turn }
+            },
+            None                =>  vec![]
+        }
+
+    } /* .moves_at() */
+
+    /// Draws the cube and returns the widget's response and the moves that
+    /// a click on it makes, see CubeWidget.
+    pub fn show (self, ui: &mut egui::Ui)
+    -> (egui::Response, Vec<Move>)
+    {
+        let (rect, response) = ui.allocate_exact_size(self.size(), egui::Sense::click());
+        let quads = self.quads(rect);
+        if ui.is_rect_visible(rect)
+        {
+            let painter = ui.painter();
+            for (_, name, points) in quads.iter()
+            {
+                let (red, green, blue) = name.rgb();
+                painter.add(egui::Shape::convex_polygon(points.to_vec(), egui::Color32::from_rgb(red, green, blue),
+                                                        egui::Stroke::new(1.0, egui::Color32::BLACK)));
+            }
+        }
+
+        let moves = match response.interact_pointer_pos()
+        {
+            Some(pos) if response.clicked() || response.secondary_clicked()
+                    =>  self.moves_at(&quads, pos, response.secondary_clicked()),
+            _       =>  vec![]
+        };
+
+        (response, moves)
+
+    } /* .show() */
+
+}   /* impl CubeWidget */
+
+#[cfg(feature = "cubus-egui")]
+impl egui::Widget for CubeWidget<'_>
+{
+    fn ui (self, ui: &mut egui::Ui)
+    -> egui::Response
+    {
+        self.show(ui).0
+    }
+
+}   /* impl egui::Widget for CubeWidget */
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    /// Returns a cube of the given size scrambled by `length` random moves.
+    fn scrambled (size: Coord, length: usize, seed: u64)
+    -> Cube
+    {
+        Cube::new(size).copy_with_moves(&random_movevec(size, length, &mut Rng::new(seed))).unwrap()
+
+    }   /* scrambled() */
+
+    /// Unpacking a packed cube returns the cube, for every size.
+    #[test]
+    fn packed_round_trip ()
+    {
+        for size in 1 ..= 10
+        {
+            let cube = if size == 1 { Cube::new(1) } else { scrambled(size, 40, size as u64) };
+            let packed = cube.to_packed();
+            assert_eq!(packed.len(), Cube::packed_len(size).unwrap());
+            assert!(Cube::from_packed(size, &packed) == Some(cube));
+        }
+        assert!(Cube::packed_len(3).unwrap() <= 16);
+
+    }   /* packed_round_trip() */
+
+    /// Sizes beyond 1 to 10 and bricks off the cube fail with errors
+    /// rather than panics.
+    #[test]
+    fn bad_sizes_refused ()
+    {
+        for size in [0, 11, 255]
+        {
+            assert!(matches!(Cube::try_new(size), Err(CubusError::InvalidSize(_))));
+            assert!(Cube::packed_len(size).is_err());
+            assert!(Cube::random(size, &mut Rng::new(1)).is_err());
+            assert!(Cube::from_packed(size, &[0]).is_none());
+        }
+        assert!(Cube::try_new(10).is_ok());
+
+        let mut cube = Cube::new(3);
+        assert!(top_view_grid(&cube).is_ok());
+        cube.size = 0;
+        assert!(matches!(top_view_grid(&cube), Err(CubusError::InvalidSize(0))));
+        cube.size = 3;
+        cube.bricks[0].curLoc.y = 3;
+        assert!(matches!(top_view_grid(&cube), Err(CubusError::InvalidBricks)));
+        cube.bricks.truncate(cube.bricks.len() - 1);
+        cube.bricks[0].curLoc.y = 0;
+        assert!(matches!(top_view_grid(&cube), Err(CubusError::InvalidBricks)));
+
+    }   /* bad_sizes_refused() */
+
+    /// Random states up to 3x3x3 are reachable and differ from draw to
+    /// draw; larger cubes are refused.
+    #[test]
+    fn random_states_reachable ()
+    {
+        let mut rng = Rng::new(9);
+        for size in 1 ..= 3
+        {
+            let cubes: Vec<Cube> = (0 .. 30).map(|_| Cube::random(size, &mut rng).unwrap()).collect();
+            assert!(cubes.iter().all(|cube| cube.check_reachable(&Cube::new(size)).is_ok()));
+            assert!(size == 1 || cubes.iter().skip(1).any(|cube| *cube != cubes[0]));
+        }
+        assert!(matches!(Cube::random(4, &mut rng), Err(CubusError::NoRandomState(4))));
+
+    }   /* random_states_reachable() */
+
+    /// Cubes, bricks and moves come back from bincode as they went, and
+    /// deserializing refuses what the constructors and moves cannot make.
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip ()
+    {
+        let mut cube = scrambled(4, 30, 3);
+        cube.lock_layer('y', 1);
+        let bytes = bincode::serialize(&cube).unwrap();
+        let back: Cube = bincode::deserialize(&bytes).unwrap();
+        assert!(back == cube && back.locks == cube.locks);
+        let moves = random_movevec(4, 20, &mut Rng::new(4));
+        let back: Vec<Move> = bincode::deserialize(&bincode::serialize(&moves).unwrap()).unwrap();
+        assert!(back == moves && back.iter().zip(moves.iter()).all(|(lhs, rhs)| lhs.ident == rhs.ident));
+        let back: Brick = bincode::deserialize(&bincode::serialize(&cube.bricks[5]).unwrap()).unwrap();
+        assert!(back == cube.bricks[5]);
+
+        assert!(bincode::deserialize::<Move>(&bincode::serialize(&Move::new('Q', 0)).unwrap()).is_err());
+        assert!(bincode::deserialize::<Move>(&bincode::serialize(&Move::new('x', 10)).unwrap()).is_err());
+        let mut brick = Brick::new(0, 0, 0);
+        brick.curHue.xneg = brick.curHue.xpos;
+        assert!(bincode::deserialize::<Brick>(&bincode::serialize(&brick).unwrap()).is_err());
+        let mut bad = cube.clone();
+        bad.bricks[1].curLoc = bad.bricks[0].curLoc;
+        assert!(bincode::deserialize::<Cube>(&bincode::serialize(&bad).unwrap()).is_err());
+        let mut bad = cube.clone();
+        bad.size = 0;
+        assert!(bincode::deserialize::<Cube>(&bincode::serialize(&bad).unwrap()).is_err());
+        let mut bad = cube.clone();
+        bad.locks.push(('X', 4));
+        assert!(bincode::deserialize::<Cube>(&bincode::serialize(&bad).unwrap()).is_err());
+
+    }   /* serde_round_trip() */
+
+    /// Cubes and move sequences survive protobuf encoding in the bytes of
+    /// proto/cubus.proto, and decoding refuses what moves cannot make.
+    #[cfg(feature = "protobuf")]
+    #[test]
+    fn protobuf_round_trip ()
+    {
+        let mut cube = scrambled(4, 30, 5);
+        cube.lock_layer('z', 2);
+        let back = Cube::from_protobuf(&cube.to_protobuf()).unwrap();
+        assert!(back == cube && back.locks == cube.locks);
+        let moves = MoveSequence::scramble(5, 25, 6);
+        assert!(MoveSequence::from_protobuf(&moves.to_protobuf()).unwrap() == moves);
+
+        assert_eq!(MoveSequence::new(vec![Move::new('X', 0)]).to_protobuf(), [0x0A, 0x03, 0x0A, 0x01, 0x58]);
+        assert_eq!(MoveSequence::new(vec![Move::new('y', ALL_LAYERS)]).to_protobuf(),
+                   [0x0A, 0x06, 0x0A, 0x01, 0x79, 0x10, 0xFF, 0x01]);
+        assert_eq!(Cube::new(1).to_protobuf(),
+                   [0x08, 0x01, 0x12, 0x10, 0x0A, 0x00, 0x12, 0x0C,
+                    0x08, 0x01, 0x10, 0x02, 0x18, 0x03, 0x20, 0x04, 0x28, 0x05, 0x30, 0x06]);
+
+        assert!(matches!(Cube::from_protobuf(&[0x0A]), Err(CubusError::InvalidProtobuf(_))));
+        assert!(matches!(MoveSequence::from_protobuf(&[0x0A, 0x03, 0x0A, 0x01, 0x51]), Err(CubusError::InvalidAxis('Q'))));
+        assert!(matches!(MoveSequence::from_protobuf(&[0x0A, 0x02, 0x10, 0x01]), Err(CubusError::InvalidProtobuf(_))));
+        let mut bad = cube.clone();
+        bad.bricks[1].curLoc = bad.bricks[0].curLoc;
+        assert!(matches!(Cube::from_protobuf(&bad.to_protobuf()), Err(CubusError::InvalidBricks)));
+        let mut bad = cube.clone();
+        bad.locks.push(('X', 4));
+        assert!(matches!(Cube::from_protobuf(&bad.to_protobuf()), Err(CubusError::InvalidLayer('X', 4))));
+        let mut bytes = Cube::new(1).to_protobuf();
+        bytes[9] = 0x07;
+        assert!(matches!(Cube::from_protobuf(&bytes), Err(CubusError::InvalidProtobuf(_))));
+
+    }   /* protobuf_round_trip() */
+
+    /// Bytes of the wrong length or size, or beyond the last state, unpack
+    /// to no cube.
+    #[test]
+    fn packed_rejects_bad_encodings ()
+    {
+        let packed = scrambled(3, 20, 1).to_packed();
+        assert!(Cube::from_packed(3, &packed[1 ..]).is_none());
+        assert!(Cube::from_packed(4, &packed).is_none());
+        assert!(Cube::from_packed(3, &vec![0xFF; packed.len()]).is_none());
+
+    }   /* packed_rejects_bad_encodings() */
+
+    /// Returns the facelets of a cube in the letters U, R, F, D, L, B of the
+    /// faces whose home colors they show, as from_facelet_string() reads
+    /// them, rather than in the color letters of facelets().
+    fn face_letters (cube: &Cube)
+    -> String
+    {
+        cube.facelets().chars().map(|chr| match chr
+        {
+            'W' =>  'U',
+            'G' =>  'F',
+            'Y' =>  'D',
+            'O' =>  'L',
+            _   =>  chr
+        }).collect()
+
+    }   /* face_letters() */
+
+    /// Returns the cube of the solved facelets of the given size with the
+    /// letters at some places changed.
+    fn cube_of_changed_facelets (size: Coord, changes: &[(usize, char)])
+    -> Cube
+    {
+        let mut letters: Vec<char> = face_letters(&Cube::new(size)).chars().collect();
+        for &(ind, letter) in changes
+        {
+            letters[ind] = letter;
+        }
+
+        Cube::from_facelet_string(&letters.into_iter().collect::<String>()).unwrap()
+
+    }   /* cube_of_changed_facelets() */
+
+    /// Reading the facelets of a cube returns a cube with the same ones.
+    #[test]
+    fn facelets_round_trip ()
+    {
+        for size in 1 ..= 6
+        {
+            let cube = if size == 1 { Cube::new(1) } else { scrambled(size, 40, size as u64) };
+            let facelets = face_letters(&cube);
+            assert_eq!(facelets.len(), 6 * (size as usize).pow(2));
+            assert_eq!(face_letters(&Cube::from_facelet_string(&facelets).unwrap()), facelets);
+        }
+        assert!(matches!(Cube::from_facelet_string("UUUU"), Err(CubusError::FaceletCount(4))));
+        assert!(matches!(Cube::from_facelet_string(&"X".repeat(54)), Err(CubusError::InvalidFacelet('X'))));
+
+    }   /* facelets_round_trip() */
+
+    /// A twisted corner, a flipped edge and two swapped edges are each out
+    /// of reach of an ordered cube, while scrambles are not.
+    #[test]
+    fn check_reachable_rejects ()
+    {
+        let home = Cube::new(3);
+        assert!(scrambled(3, 30, 2).check_reachable(&home).is_ok());
+        assert!(home.check_reachable(&scrambled(3, 30, 3)).is_ok());
+        assert!(scrambled(5, 60, 4).check_reachable(&Cube::new(5)).is_ok());
+
+        let unreachable = [
+            cube_of_changed_facelets(3, &[(8, 'F'), (9, 'U'), (20, 'R')]),
+            cube_of_changed_facelets(3, &[(7, 'F'), (19, 'U')]),
+            cube_of_changed_facelets(3, &[(10, 'F'), (19, 'R')]),
+            cube_of_changed_facelets(2, &[(3, 'F'), (4, 'U'), (9, 'R')])
+        ];
+        for cube in unreachable.iter()
+        {
+            assert!(matches!(cube.check_reachable(&Cube::new(cube.size)), Err(CubusError::Unreachable(_))));
+        }
+        assert!(matches!(home.check_reachable(&Cube::new(4)), Err(CubusError::SizeMismatch(3, 4))));
+
+        let mut swapped = home.clone();
+        swapped.bricks[0].curLoc = swapped.bricks[1].curLoc;
+        assert!(matches!(swapped.check_reachable(&home), Err(CubusError::InvalidBricks)));
+
+    }   /* check_reachable_rejects() */
+
+    /// Moves printed in either notation read back as moves that leave the
+    /// cube the same, and in the notation of layers as the same moves.
+    #[test]
+    fn notation_round_trip ()
+    {
+        for size in 2 ..= 7
+        {
+            let axmax = size - 1;
+            let moves = random_movevec(size, 60, &mut Rng::new(size as u64));
+            let cube = Cube::new(size).copy_with_moves(&moves).unwrap();
+
+            let string = string_of_movevec(&moves);
+            assert!(movevec_of_string(&string, axmax).unwrap() == moves);
+            let seq: MoveSequence = string.parse().unwrap();
+            assert_eq!(seq.to_string(), string);
+
+            let singmaster = movevec_of_singmaster(&singmaster_string_of_movevec(&moves, axmax), axmax).unwrap();
+            assert!(Cube::new(size).copy_with_moves(&singmaster).unwrap() == cube);
+        }
+        assert!(movevec_of_singmaster("R U2 F' 2-3Rw x", 4).unwrap()
+             == movevec_of_string("x4y4y4Z4x3x2x*", 4).unwrap());
+
+    }   /* notation_round_trip() */
+
+    /// Commutators, conjugates and powers expand to the moves they stand
+    /// for, also nested.
+    #[test]
+    fn brackets_expand ()
+    {
+        let moves = |string: &str| string_of_movevec(&movevec_of_string(string, 2).unwrap());
+        assert_eq!(moves("[X0, Y2]"), "X0Y2x0y2");
+        assert_eq!(moves("[X0: Y2]"), "X0Y2x0");
+        assert_eq!(moves("(X0: Y2)"), "X0Y2x0");
+        assert_eq!(moves("[X0: [Y2, Z1]]"), "X0Y2Z1y2z1x0");
+        assert_eq!(moves("(X0Y1)^3 2Z0"), "X0Y1X0Y1X0Y1Z0Z0");
+        assert_eq!(moves("X0 # Y1\nZ2"), "X0Z2");
+        assert_eq!(singmaster_string_of_movevec(&movevec_of_singmaster("[R, U]", 2).unwrap(), 2), "R U R' U'");
+        assert!(matches!(movevec_of_string("[X0, Y2", 2), Err(CubusError::UnbalancedParens)));
+        assert!(matches!(movevec_of_string("[X0, Y2, Z1]", 2), Err(CubusError::Unexpected(..))));
+
+    }   /* brackets_expand() */
+
+    /// Moves in outer parentheses go on the inverse and come last,
+    /// inverted; inner parentheses and comments keep their meaning.
+    #[test]
+    fn niss_switches_to_inverse ()
+    {
+        let parse = |string: &str| movevec_of_singmaster(string, 2);
+        let moves = |string: &str| singmaster_string_of_movevec(&movevec_of_niss(string, &parse).unwrap(), 2);
+        assert_eq!(moves("R U (F D)"), "R U D' F'");
+        assert_eq!(moves("(F) R (D') U"), "R U D F'");
+        assert_eq!(moves("R (F (U R)2)"), "R R' U' R' U' F'");
+        assert_eq!(moves("R # (U)\n(F)"), "R F'");
+        assert_eq!(moves("R U"), "R U");
+        assert!(matches!(movevec_of_niss("R (U", &parse), Err(CubusError::UnbalancedParens)));
+        assert!(matches!(movevec_of_niss("R U) F", &parse), Err(CubusError::UnbalancedParens)));
+
+        // A skeleton found on the inverse solves the scramble when its
+        // moves come last, inverted.
+        let scramble = movevec_of_singmaster("F R U'", 2).unwrap();
+        let cube = Cube::new(3).copy_with_moves(&scramble).unwrap();
+        assert!(cube.copy_with_moves(&movevec_of_niss("U (F) R'", &parse).unwrap()).unwrap() == Cube::new(3));
+
+    }   /* niss_switches_to_inverse() */
+
+    /// Each metric counts what speedcubers count: half turns as one or two
+    /// quarters, slices as one or two outer turns, rotations only in ETM.
+    #[test]
+    fn metrics_count_turns ()
+    {
+        let metrics = |algorithm: &str, size: Coord| {
+            let MoveMetrics { htm, qtm, stm, etm } = MoveMetrics::of_movevec(&movevec_of_singmaster(algorithm, size - 1).unwrap(), size);
+            [htm, qtm, stm, etm]
+        };
+        assert_eq!(metrics("R U R' U'", 3), [4, 4, 4, 4]);
+        assert_eq!(metrics("R U2 D' B D'", 3), [5, 6, 5, 5]);
+        assert_eq!(metrics("R2", 3), [1, 2, 1, 1]);
+        assert_eq!(metrics("M", 3), [2, 2, 1, 1]);
+        assert_eq!(metrics("M2", 3), [2, 4, 1, 1]);
+        assert_eq!(metrics("Rw", 3), [1, 1, 1, 1]);
+        assert_eq!(metrics("Rw2", 4), [1, 2, 1, 1]);
+        assert_eq!(metrics("2-3Rw", 5), [2, 2, 1, 1]);
+        assert_eq!(metrics("x", 3), [0, 0, 0, 1]);
+        assert_eq!(metrics("x R", 3), [1, 1, 1, 2]);
+        assert_eq!(metrics("R R'", 3), [0, 0, 0, 0]);
+        assert_eq!(metrics("R M' L'", 3), [0, 0, 0, 1]);
+
+        let mut rng = Rng::new(17);
+        for size in 2 ..= 5
+        {
+            let moves = random_movevec(size, 30, &mut rng);
+            let MoveMetrics { htm, qtm, stm, etm } = MoveMetrics::of_movevec(&moves, size);
+            assert!(htm <= qtm && qtm <= 2 * moves.len() && stm <= etm && etm <= moves.len());
+            assert!(MoveMetrics::of_movevec(&movevec_inverted(&moves), size) == MoveMetrics { htm, qtm, stm, etm });
+        }
+
+    }   /* metrics_count_turns() */
+
+    /// Composing the cubes that two sequences make equals playing the
+    /// sequences one after the other.
+    #[test]
+    fn compose_equals_concatenation ()
+    {
+        let mut rng = Rng::new(5);
+        for size in 2 ..= 5
+        {
+            let first = random_movevec(size, 25, &mut rng);
+            let second = random_movevec(size, 25, &mut rng);
+            let home = Cube::new(size);
+            let lhs = home.copy_with_moves(&first).unwrap();
+            let rhs = home.copy_with_moves(&second).unwrap();
+            assert!(lhs.compose(&rhs).unwrap() == home.copy_with_moves(&[first.clone(), second].concat()).unwrap());
+            assert!(lhs.compose(&home.copy_with_moves(&movevec_inverted(&first)).unwrap()).unwrap() == home);
+        }
+
+    }   /* compose_equals_concatenation() */
+
+    /// Cubies taken from face-turned cubes go back to the same stickers, and
+    /// follow the same moves; a refused move leaves the cubies untouched.
+    #[test]
+    fn cubies_round_trip ()
+    {
+        let mut rng = Rng::new(7);
+        for _ in 0 .. 20
+        {
+            let moves: Vec<Move> = random_movevec(3, 40, &mut rng).into_iter()
+                                       .filter(|mov| mov.axval != 1 && !mov.is_rotation()).collect();
+            let cube = Cube::new(3).copy_with_moves(&moves).unwrap();
+            let cubies = cube.to_cubies().unwrap();
+            assert!(Cube::from_cubies(&cubies).unwrap().facelets() == cube.facelets());
+            let mut played = CubieCube::new();
+            played.apply_moves(&moves).unwrap();
+            assert!(played == cubies);
+            assert!(cubies.compose(&cubies.inverse()).is_solved());
+        }
+        assert!(Cube::new(4).to_cubies().is_none());
+        assert!(Cube::new(3).copy_with_moves(&[Move::new('X', 1)]).unwrap().to_cubies().is_none());
+
+        let mut cubies = CubieCube::of_move(&Move::new('X', 0)).unwrap();
+        let before = cubies;
+        let moves = [Move::new('Y', 0), Move::new('Z', 2), Move::new('X', 1)];
+        assert!(matches!(cubies.apply_moves(&moves), Err(CubusError::CenterMove('X', 1))));
+        assert!(cubies == before);
+        let mut bad = CubieCube::new();
+        bad.cp[0] = 1;
+        assert!(matches!(Cube::from_cubies(&bad), Err(CubusError::InvalidBricks)));
+
+    }   /* cubies_round_trip() */
+
+    /// A piece is found by its colors in any order, home or scrambled, and
+    /// the brick found shows those colors on the outer faces of its place.
+    #[test]
+    fn find_piece_locates_bricks ()
+    {
+        use Huename::*;
+
+        let home = Cube::new(3);
+        let edge = home.find_piece(&[GN, WT]);
+        assert!(edge.len() == 1 && edge[0].curLoc == Loc { x: 1, y: 2, z: 2 });
+        let corner = home.find_piece(&[RD, WT, GN]);
+        assert!(corner.len() == 1 && corner[0].curLoc == Loc { x: 2, y: 2, z: 2 });
+        assert!(home.find_piece(&[WT, YL]).is_empty());
+        assert!(home.find_piece(&[WT, GN, BL]).is_empty());
+
+        let cube = scrambled(3, 30, 11);
+        for colors in [&[WT][..], &[WT, GN], &[YL, BL, OR], &[RD, GN]]
+        {
+            let found = cube.find_piece(colors);
+            assert_eq!(found.len(), 1);
+            let isOut = loc_outward(&found[0].curLoc, 2);
+            let faces = hue_faces(&found[0].curHue);
+            let mut shown: Vec<u8> = (0 .. 6).filter(|&face| isOut[face]).map(|face| faces[face] as u8).collect();
+            let mut wanted: Vec<u8> = colors.iter().map(|&name| name as u8).collect();
+            shown.sort();
+            wanted.sort();
+            assert_eq!(shown, wanted);
+        }
+
+        let big = scrambled(4, 30, 12);
+        assert_eq!(big.find_piece(&[WT, GN]).len(), 2);
+        assert_eq!(big.find_piece(&[WT]).len(), 4);
+        assert_eq!(big.find_piece(&[WT, GN, RD]).len(), 1);
+
+    }   /* find_piece_locates_bricks() */
+
+    /// Cycles of well-known algorithms come out as their pieces move, and
+    /// the cycles of any face-turned cube tell the order of its permutation.
+    #[test]
+    fn cycles_decompose ()
+    {
+        let cyclesOf = |algorithm: &str| -> Vec<String> {
+            let moves = movevec_of_singmaster(algorithm, 2).unwrap();
+            Cube::new(3).copy_with_moves(&moves).unwrap().cycles().unwrap().iter().map(|cycle| cycle.as_string()).collect()
+        };
+        assert_eq!(cyclesOf("U"), ["(UBL UBR UFR UFL)", "(UB UR UF UL)"]);
+        assert_eq!(cyclesOf("R U R' U' R' F R2 U' R' U' R U R' F'"), ["(UBR UFR)", "(UL UR)"]);
+        assert_eq!(cyclesOf("R U R' U R U2 R'"), ["(UBL UFR)-", "(UBR UFL)+", "(UB UL UR)"]);
+        assert_eq!(cyclesOf("M2 U M2 U2 M2 U M2"), ["(UB UF)", "(UL UR)"]);
+        assert!(cyclesOf("(R U R' U')6").is_empty());
+        assert!(Cube::new(4).cycles().is_none());
+
+        let gcd = |mut lhs: usize, mut rhs: usize| { while rhs != 0 { (lhs, rhs) = (rhs, lhs % rhs); } lhs };
+        let mut rng = Rng::new(13);
+        for _ in 0 .. 20
+        {
+            let moves: Vec<Move> = random_movevec(3, 12, &mut rng).into_iter()
+                                       .filter(|mov| mov.axval != 1 && !mov.is_rotation()).collect();
+            let cube = Cube::new(3).copy_with_moves(&moves).unwrap();
+            let order = cube.cycles().unwrap().iter().fold(1, |order, cycle| {
+                let len = cycle.pieces.len() * if cycle.twist == 0 { 1 } else { cycle.modulus as usize };
+                order / gcd(order, len) * len
+            });
+            let cubies = cube.to_cubies().unwrap();
+            let power = |count: usize| (0 .. count).fold(CubieCube::new(), |power, _| power.compose(&cubies));
+            assert!(power(order).is_solved());
+            assert!([2, 3, 5, 7, 11].iter().all(|&prime| order % prime != 0 || !power(order / prime).is_solved()));
+        }
+
+    }   /* cycles_decompose() */
+
+    /// Two-phase solutions take scrambles home within the target length,
+    /// also from cubes turned as a whole.
+    #[test]
+    fn two_phase_solves ()
+    {
+        for seed in 1 ..= 3
+        {
+            let mut cube = scrambled(3, 30, seed);
+            if seed == 3
+            {
+                cube.apply_moves(&[Move::rotation('x'), Move::rotation('Y')]).unwrap();
+            }
+            let (found, _) = solve_two_phase(&cube, 22, None).unwrap();
+            let moves = found.unwrap();
+            assert!(cube.copy_with_moves(&moves).unwrap().is_solved());
+            assert!(MoveMetrics::of_movevec(&moves, 3).htm <= 22);
+        }
+        assert!(solve_two_phase(&Cube::new(3), 22, None).unwrap().0.unwrap().is_empty());
+        assert!(matches!(solve_two_phase(&Cube::new(4), 22, None), Err(CubusError::SizeMismatch(4, 3))));
+
+    }   /* two_phase_solves() */
+
+    /// The steps of the layer-by-layer, Roux and ZZ solvers, joined, take
+    /// scrambles home.
+    #[test]
+    fn method_solvers_solve ()
+    {
+        let cubes = [scrambled(3, 30, 0), scrambled(3, 30, 1), scrambled(3, 30, 2)];
+        let solutions = [solve_layer_by_layer(&cubes[0]), solve_roux(&cubes[1]), solve_zz(&cubes[2])];
+        for (cube, steps) in cubes.iter().zip(solutions)
+        {
+            let parts: Vec<Vec<Move>> = steps.unwrap().into_iter().map(|step| step.moves).collect();
+            let (joined, _) = movevec_joined(&parts);
+            assert!(cube.copy_with_moves(&joined).unwrap().is_solved());
+            assert!(MoveMetrics::of_movevec(&joined, 3).htm <= parts.iter().map(|moves| MoveMetrics::of_movevec(moves, 3).htm).sum());
+        }
+
+    }   /* method_solvers_solve() */
+
+    /// Each phase of the layer-by-layer, Roux and ZZ methods meets its goal
+    /// where its step ends, and phases read from JSON solve by their own
+    /// moves or fail within their depth.
+    #[test]
+    fn phases_meet_goals ()
+    {
+        for phases in [layer_by_layer_phases(), roux_phases(), zz_phases()]
+        {
+            let mut cube = scrambled(3, 30, 3);
+            for (step, phase) in solve_phases(&cube.clone(), &phases).unwrap().iter().zip(phases.iter())
+            {
+                cube.apply_moves(&step.moves).unwrap();
+                assert!(phase.is_done(&cube), "{}", phase.name());
+            }
+        }
+
+        let phases = phases_of_json(r#"[{"name": "Two layers", "goal": "F2L", "moves": "R U", "depth": 4},
+                                        {"name": "Top", "note": "Turn the top.", "goal": "LL", "moves": "U"}]"#, 3).unwrap();
+        let cube = Cube::new(3).copy_with_moves(&movevec_of_singmaster("R U", 2).unwrap()).unwrap();
+        let steps = solve_phases(&cube, &phases).unwrap();
+        assert!(steps.len() == 2 && steps[0].moves.len() == 2 && steps[1].moves.is_empty() && steps[1].note == "Turn the top.");
+        assert!(cube.copy_with_moves(&steps[0].moves).unwrap().is_solved());
+
+        let shallow = phases_of_json(r#"[{"name": "All", "goal": "LL", "moves": "R U", "depth": 1}]"#, 3).unwrap();
+        assert!(matches!(solve_phases(&cube, &shallow), Err(CubusError::PhaseFailed(_))));
+        assert!(matches!(phases_of_json(r#"[{"name": "All", "moves": "R U"}]"#, 3), Err(CubusError::Unexpected(..))));
+
+    }   /* phases_meet_goals() */
+
+    /// Hints reveal a move, the rest of its step and the whole solution in
+    /// turn, follow the cube by state rather than by the moves made, and
+    /// keep what they revealed across moves off the plan and back.
+    #[test]
+    fn hints_reveal_progressively ()
+    {
+        let cube = scrambled(3, 30, 0);
+        let steps = solve_layer_by_layer(&cube).unwrap();
+        let all: Vec<Move> = steps.iter().flat_map(|step| step.moves.clone()).collect();
+        let mut plan = HintPlan::new(&cube, steps.clone()).unwrap();
+        assert!(plan.revealed().is_empty());
+
+        let first = plan.reveal(HintLevel::NextMove);
+        assert!(first.len() == 1);
+        assert!(first[0].1 == all[.. 1]);
+        let step = plan.reveal(HintLevel::NextStep);
+        assert!(step == vec![(steps[0].name.as_str(), steps[0].moves.clone())]);
+
+        // The first move made the other way round, thrice.
+        let mut turned = cube.clone();
+        let reverse = movevec_inverted(&all[.. 1]);
+        turned.apply_moves(&[reverse.clone(), reverse.clone(), reverse].concat()).unwrap();
+        assert!(plan.follow(&turned));
+        assert!(plan.done() == Some(1));
+        assert!(plan.revealed() == vec![(steps[0].name.as_str(), steps[0].moves[1 ..].to_vec())]);
+
+        // Off the plan, and back.
+        let off = turned.copy_with_moves(&movevec_of_singmaster("M", 2).unwrap()).unwrap();
+        assert!(!plan.follow(&off));
+        assert!(plan.reveal(HintLevel::Solution).is_empty());
+        assert!(plan.follow(&turned));
+        assert!(plan.revealed().len() == 1);
+
+        let rest = plan.reveal(HintLevel::Solution);
+        let left: Vec<Move> = rest.into_iter().flat_map(|(_, moves)| moves).collect();
+        assert!(left == all[1 ..]);
+        assert!(turned.copy_with_moves(&left).unwrap().is_solved());
+        assert!(plan.follow(&turned.copy_with_moves(&left).unwrap()));
+        assert!(plan.revealed().is_empty());
+
+    }   /* hints_reveal_progressively() */
+
+    /// Going back and playing other moves branches beside the old line,
+    /// the same moves again return to it, and the tree survives its text.
+    #[test]
+    fn move_tree_branches ()
+    {
+        let moves = |string: &str| movevec_of_string(string, 2).unwrap();
+        let mut tree = MoveTree::new();
+        assert!(tree.current() == 0 && tree.path().is_empty() && !tree.back());
+
+        assert_eq!(tree.play(moves("X0Y0")), 1);
+        assert_eq!(tree.play(moves("Z2")), 2);
+        assert!(tree.back() && tree.current() == 1 && !tree.has_branches());
+        assert_eq!(tree.play(moves("x0")), 3);
+        assert!(tree.has_branches() && tree.path() == moves("X0Y0x0"));
+        assert!(tree.back());
+        assert_eq!(tree.play(moves("Z2")), 2);
+        assert!(tree.path() == moves("X0Y0Z2"));
+        assert!(tree.goto(3) && tree.moves_of(3) == moves("x0").as_slice());
+        assert!(!tree.goto(4) && tree.current() == 3);
+        assert_eq!(tree.outline(), [(1, 0), (2, 1), (3, 1)]);
+
+        let text = tree.as_text(&|moves| string_of_movevec(moves));
+        assert_eq!(text, "X0Y0\n  Z2\n  x0\n");
+        let parse = |string: &str| movevec_of_string(string, 2);
+        let back = MoveTree::of_text(&text, &parse).unwrap();
+        assert!(back.current() == 0 && back.as_text(&|moves| string_of_movevec(moves)) == text);
+        assert!(matches!(MoveTree::of_text("X0\n    Y0\n", &parse), Err(CubusError::InvalidOutline(2))));
+
+    }   /* move_tree_branches() */
+
+    /// The breadth-first search over trails that share their moves finds
+    /// what it found when each trail held a copy of its moves, in the same
+    /// order and after as many exploratory moves, also with a Pruner.
+    #[test]
+    fn breadth_search_unchanged ()
+    {
+        let search = |size: Coord, moves: &str, maxLen: usize, pruner: Pruner| {
+            let dst = Cube::new(size).copy_with_moves(&movevec_of_string(moves, size - 1).unwrap()).unwrap();
+            find_moves_pruned(maxLen, &Cube::new(size), &dst, pruner, None).unwrap()
+        };
+        let noY = |stack: &[Move], mov: &Move| !stack.is_empty() && mov.axdir == 'Y';
+
+        assert!(search(3, "X0x2", 2, &|_, _| false) == (vec!["X0x2".to_string(), "x2X0".to_string()], 315));
+        assert!(search(2, "X0Y0x1", 3, &|_, _| false) == (vec!["X0Y0x1".to_string(), "x1z1X0".to_string()], 1206));
+        assert!(search(2, "X0Y0x1", 3, &noY) == (vec!["x1z1X0".to_string()], 854));
+        assert!(search(2, "X0X0Y0Y0", 4, &noY) == (vec!["X0X0y0y0".to_string()], 6667));
+        assert!(search(3, "X0Y1Z2x0", 4, &|_, _| false) == (vec!["X0Y1Z2x0".to_string()], 84735));
+
+    }   /* breadth_search_unchanged() */
+
+    /// A cancelled token stops the searches within one interval, with what
+    /// they found so far; clones share the flag, and timers set it.
+    #[test]
+    fn cancel_stops_searches ()
+    {
+        let token = CancelToken::new(100);
+        let clone = token.clone();
+        assert!(!token.is_cancelled() && !token.should_stop(100));
+        clone.cancel();
+        assert!(token.is_cancelled() && token.should_stop(200) && !token.should_stop(250));
+        assert!(!CancelToken::new(0).should_stop(7));
+
+        let scramble = movevec_of_string("X0Y1Z2x0", 2).unwrap();
+        let dst = Cube::new(3).copy_with_moves(&scramble).unwrap();
+        let (found, moveNum) = find_moves(4, &Cube::new(3), &dst, Some(&token)).unwrap();
+        assert!(found.is_empty() && 0 < moveNum && moveNum <= 100);
+        let (found, _) = find_moves_ida(4, &Cube::new(3), &dst, &MisplacedStickers, Some(&token)).unwrap();
+        assert!(found.is_none());
+        assert!(find_moves_ida(4, &Cube::new(3), &dst, &MisplacedStickers, None).unwrap().0.is_some());
+
+        let cube = scrambled(3, 25, 19);
+        let (solution, moveNum) = solve_two_phase(&cube, 0, Some(&token)).unwrap();
+        assert!(solution.is_none() && moveNum <= 100);
+        assert!(solve_two_phase(&cube, 30, None).unwrap().0.is_some());
+
+        let timer = cancel_after(Duration::from_millis(1));
+        std::thread::sleep(Duration::from_millis(200));
+        assert!(timer.is_cancelled());
+
+    }   /* cancel_stops_searches() */
+
+    /// Pruners see the moves so far, most recent first, and every sequence
+    /// found keeps to their rules; pruning all moves finds nothing.
+    #[test]
+    fn pruners_restrict_search ()
+    {
+        let dst = Cube::new(2).copy_with_moves(&movevec_of_string("X0Y0x1", 1).unwrap()).unwrap();
+        let search = |pruner: Pruner| find_moves_pruned(3, &Cube::new(2), &dst, pruner, None).unwrap();
+        let (all, allNum) = search(&|_, _| false);
+        assert!(all == ["X0Y0x1", "x1z1X0"]);
+
+        let (found, moveNum) = search(&|_, mov| mov.axdir.eq_ignore_ascii_case(&'Z'));
+        assert!(found == ["X0Y0x1"] && moveNum < allNum);
+        let (found, _) = search(&|stack, mov| stack.len() == 2 && stack[0] == Move::new('Y', 0) && mov.axdir == 'x');
+        assert!(found == ["x1z1X0"]);
+        let (found, _) = search(&|stack, _| stack.last() == Some(&Move::new('x', 1)));
+        assert!(found == ["X0Y0x1"]);
+        assert!(search(&|_, _| true).0.is_empty());
+
+    }   /* pruners_restrict_search() */
+
+    /// Returns pattern databases that tell 0 face turns for the arrangements
+    /// of the ordered cube and 1 for all others, which keeps searches short
+    /// enough to test but admissible.
+    fn coarse_databases ()
+    -> PatternDatabases
+    {
+        let mut bytes = DATABASE_MAGIC.to_vec();
+        bytes.resize(DATABASE_MAGIC.len() + CORNER_STATES.div_ceil(2) + 2 * EDGE_STATES.div_ceil(2), 0x11);
+
+        let home = CubieCube::new();
+        let slots = edge_slots_of_cubies(&home);
+        let homeInds = [2187 * perm_rank(&home.cp) + home.twist(), edge_slots_index(&slots[.. 6]), edge_slots_index(&slots[6 ..])];
+        let mut offset = DATABASE_MAGIC.len();
+        for (ind, states) in homeInds.into_iter().zip([CORNER_STATES, EDGE_STATES, EDGE_STATES])
+        {
+            bytes[offset + ind / 2] &= !(0xF << (4 * (ind % 2)));
+            offset += states.div_ceil(2);
+        }
+
+        PatternDatabases::with_bytes(DatabaseBytes::Owned(bytes))
+
+    }   /* coarse_databases() */
+
+    /// Optimal solutions of short scrambles take them home in no more face
+    /// turns than the scrambles, and the databases as a Heuristic estimate
+    /// no more than the moves left.
+    #[test]
+    fn optimal_solves ()
+    {
+        let databases = coarse_databases();
+        for scramble in ["R U F'", "L2 D B' U", "x R U"]
+        {
+            let cube = Cube::new(3).copy_with_moves(&movevec_of_singmaster(scramble, 2).unwrap()).unwrap();
+            let (found, _) = solve_optimal(&cube, &databases, 4, None).unwrap();
+            let moves = found.unwrap();
+            assert!(cube.copy_with_moves(&moves).unwrap().is_solved());
+            assert!(MoveMetrics::of_movevec(&moves, 3).htm <= 4);
+            assert!(databases.estimate(&cube, &Cube::new(3)) <= movevec_of_singmaster(scramble, 2).unwrap().len());
+        }
+        assert!(solve_optimal(&scrambled(3, 30, 1), &databases, 2, None).unwrap().0.is_none());
+
+    }   /* optimal_solves() */
+
+
+    /// Every bundled algorithm leaves the layers below the top in place,
+    /// but for the front right slot with F2L algorithms, and those of PLL
+    /// also leave the last layer oriented.
+    #[test]
+    fn algorithms_keep_solved_layers ()
+    {
+        for algorithm in ALGORITHMS.iter()
+        {
+            let axmax = algorithm.size - 1;
+            let home = Cube::new(algorithm.size);
+            let cube = home.copy_with_moves(&algorithm.movevec().unwrap()).unwrap();
+            let (homeAt, brickAt) = (home.bricks_by_loc(), cube.bricks_by_loc());
+            for brick in home.bricks.iter()
+            {
+                let Loc { x, y, z } = brick.curLoc;
+                let isSlot = algorithm.set == "F2L" && x == axmax && z == axmax;
+                if y < axmax && !isSlot
+                {
+                    let ind = loc_index(&brick.curLoc, algorithm.size);
+                    let (faces, homeFaces) = (hue_faces(&brickAt[ind].unwrap().curHue), hue_faces(&homeAt[ind].unwrap().curHue));
+                    let isOut = loc_outward(&brick.curLoc, axmax);
+                    assert!((0 .. 6).all(|face| !isOut[face] || faces[face] == homeFaces[face]),
+                            "{} moves {}", algorithm.name, piece_name(&brick.curLoc, axmax));
+                }
+            }
+            if algorithm.set == "PLL"
+            {
+                assert!(cube.solved_report().unwrap().oll, "{}", algorithm.name);
+            }
+        }
+
+    }   /* algorithms_keep_solved_layers() */
+
+
+    /// The setup of each last-layer case, from any side, is recognized as a
+    /// case of the same set, whose algorithm then takes it to the next
+    /// milestone.
+    #[test]
+    fn last_layer_cases_recognized ()
+    {
+        let turnU = Move::new('y', 2);
+        for algorithm in ALGORITHMS.iter().filter(|algorithm| algorithm.set == "OLL" || algorithm.set == "PLL")
+        {
+            for turns in 0 .. 4
+            {
+                let mut setup = vec![turnU; turns];
+                setup.extend(movevec_inverted(&algorithm.movevec().unwrap()));
+                let cube = Cube::new(3).copy_with_moves(&setup).unwrap();
+                let (found, before) = cube.last_layer_case().unwrap();
+                assert_eq!(found.set, algorithm.set);
+
+                let mut after = cube.copy_with_moves(&before).unwrap();
+                after.apply_moves(&found.movevec().unwrap()).unwrap();
+                let report = after.solved_report().unwrap();
+                assert!(report.oll && (found.set == "OLL" || (0 .. 4).any(|_| { after.apply_move(&turnU).unwrap(); after.is_solved() })),
+                        "{} for {}", found.name, algorithm.name);
+            }
+        }
+        assert!(Cube::new(3).last_layer_case().is_none());
+
+    }   /* last_layer_cases_recognized() */
+
+
+    /// Masks cover the stickers of their regions and pieces, and the
+    /// algorithms that solve a masked setup do so, shortest first.
+    #[test]
+    fn algorithms_solve_masks ()
+    {
+        let count = |mask: Vec<bool>| mask.iter().filter(|&&isCovered| isCovered).count();
+        assert_eq!(count(sticker_mask("F2L", 3).unwrap()), 33);
+        assert_eq!(count(sticker_mask("oll", 3).unwrap()), 42);
+        assert_eq!(count(sticker_mask("F2L,UFR,UF", 3).unwrap()), 38);
+        assert!(matches!(sticker_mask("F2L,UX", 3), Err(CubusError::UnknownPiece(_))));
+
+        let setup = movevec_inverted(&case_movevec("T-perm").unwrap());
+        let cube = Cube::new(3).copy_with_moves(&setup).unwrap();
+        let found = algorithms_solving(&cube, &sticker_mask("LL", 3).unwrap());
+        assert!(found.iter().any(|(algorithm, _)| algorithm.name == "T-perm"));
+        assert!(found.iter().all(|(_, moves)| cube.copy_with_moves(moves).unwrap().is_solved()));
+        let lengths: Vec<usize> = found.iter().map(|(_, moves)| MoveMetrics::of_movevec(moves, 3).stm).collect();
+        assert!(lengths.windows(2).all(|pair| pair[0] <= pair[1]));
+
+        let oriented = algorithms_solving(&cube, &sticker_mask("OLL", 3).unwrap());
+        assert!(oriented.len() > found.len() && oriented.iter().all(|(algorithm, _)| algorithm.set == "PLL"));
+
+    }   /* algorithms_solve_masks() */
+
+
+    /// Both shapes of an algorithm collection read alike, and the sheet of
+    /// an OLL case masks all but the top color.
+    #[test]
+    fn alg_collection_sheets ()
+    {
+        let byName = alg_collection_of_json(r#"{"Sune": "R U R' U R U2 R'", "T\u002dperm": "R U R' U' R' F R2 U' R' U' R U R' F'"}"#);
+        let listed = alg_collection_of_json(r#"[{"name": "Sune", "alg": "R U R' U R U2 R'"},
+                                                {"moves": "R U R' U' R' F R2 U' R' U' R U R' F'", "name": "T-perm"}]"#);
+        assert_eq!(byName.unwrap(), listed.unwrap());
+        for text in ["", "{\"Sune\": 1}", "[{\"name\": \"Sune\"}]", "{\"Sune\": \"R\",}", "{} {}"]
+        {
+            assert!(matches!(alg_collection_of_json(text), Err(CubusError::Unexpected(..))), "{}", text);
+        }
+
+        let collection = vec![("Sune".to_string(), "R U R' U R U2 R'".to_string())];
+        let sheet = alg_sheet_text(&collection, 3, &ColorScheme::western()).unwrap();
+        assert!(sheet.lines().next().unwrap().ends_with("   Sune"));
+        assert!(sheet.lines().take(5).all(|line| line.chars().take(11).all(|chr| " .W".contains(chr))));
+        assert!(alg_sheet_html(&collection, 3, &ColorScheme::western()).unwrap().contains("<b>Sune</b>"));
+
+    }   /* alg_collection_sheets() */
+
+
+    /// A QR code drawn dark on light is its light-on-dark drawing swapped,
+    /// and its PNG image holds the chunks and pixel rows due, in stored
+    /// blocks of at most 65535 bytes.
+    #[test]
+    fn qr_images ()
+    {
+        let modules = qr_modules(&[b'R'; 200]).unwrap();
+        let (light, dark) = (qr_string(&modules, false), qr_string(&modules, true));
+        let swapped: String = light.chars().map(|chr| match chr { '█' => ' ', ' ' => '█', '▀' => '▄', '▄' => '▀', _ => chr }).collect();
+        let lines = light.lines().count();
+        assert!(swapped.lines().zip(dark.lines()).take(lines - 1).all(|(lhs, rhs)| lhs == rhs));
+
+        let png = qr_png(&modules, 6);
+        let width = (modules.len() + 8) * 6;
+        assert_eq!(&png[.. 8], b"\x89PNG\r\n\x1A\n");
+        let mut chunks: Vec<(&[u8], &[u8])> = vec![];
+        let mut rest = &png[8 ..];
+        while !rest.is_empty()
+        {
+            let len = u32::from_be_bytes(rest[.. 4].try_into().unwrap()) as usize;
+            assert_eq!(crc32(&rest[4 .. 8 + len]).to_be_bytes(), rest[8 + len .. 12 + len]);
+            chunks.push((&rest[4 .. 8], &rest[8 .. 8 + len]));
+            rest = &rest[12 + len ..];
+        }
+        assert_eq!(chunks.iter().map(|(kind, _)| *kind).collect::<Vec<&[u8]>>(), [b"IHDR", b"IDAT", b"IEND"]);
+        assert_eq!(chunks[0].1[.. 4], (width as u32).to_be_bytes());
+
+        let mut stream = &chunks[1].1[2 ..];
+        let mut pixels: Vec<u8> = vec![];
+        loop
+        {
+            let len = u16::from_le_bytes([stream[1], stream[2]]) as usize;
+            assert_eq!(len as u16, !u16::from_le_bytes([stream[3], stream[4]]));
+            pixels.extend_from_slice(&stream[5 .. 5 + len]);
+            let isLast = stream[0] == 1;
+            stream = &stream[5 + len ..];
+            if isLast
+            {
+                break;
+            }
+        }
+        assert_eq!(stream.len(), 4);
+        assert_eq!(pixels.len(), width * (width + 1));
+        assert!(pixels.len() > 0xFFFF);
+        let pixelAt = |x: usize, y: usize| pixels[y * (width + 1) + 1 + x];
+        assert!(pixels.iter().step_by(width + 1).all(|&filter| filter == 0));
+        assert_eq!((pixelAt(0, 0), pixelAt(23, 24), pixelAt(24, 24), pixelAt(29, 29)), (0xFF, 0xFF, 0x00, 0x00));
+
+    }   /* qr_images() */
+
+    /// Clicks on the stickers of CubeWidget turn their faces, either way,
+    /// in the net and isometric, and clicks beside them nothing.
+    #[cfg(feature = "cubus-egui")]
+    #[test]
+    fn widget_clicks_turn_faces ()
+    {
+        let cube = Cube::new(3);
+        let turn = |text: &str| movevec_of_singmaster(text, 2).unwrap();
+        let rect = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(200.0, 200.0));
+
+        let net = CubeWidget::new(&cube).sticker_size(10.0);
+        let quads = net.quads(rect);
+        assert!(quads.len() == 54);
+        assert!(net.moves_at(&quads, egui::pos2(50.0, 15.0), false) == turn("U"));
+        assert!(net.moves_at(&quads, egui::pos2(50.0, 15.0), true) == turn("U'"));
+        assert!(net.moves_at(&quads, egui::pos2(120.0, 50.0), false) == turn("B"));
+        assert!(net.moves_at(&quads, egui::pos2(5.0, 5.0), false).is_empty());
+
+        let isometric = net.view(CubeView::Isometric);
+        let quads = isometric.quads(rect);
+        assert!(quads.len() == 27);
+        assert!(isometric.moves_at(&quads, egui::pos2(26.0, 15.0), false) == turn("U"));
+        assert!(isometric.moves_at(&quads, egui::pos2(13.0, 37.5), false) == turn("F"));
+        assert!(isometric.moves_at(&quads, egui::pos2(39.0, 37.5), true) == turn("R'"));
+
+    }   /* widget_clicks_turn_faces() */
+
+}   /* mod tests */
+
+
+/* ~ lib.rs ~ */
@@ -3,11 +3,11 @@
  *    main.rs
  *    ~~~~~~~
  *
- *    Simulation of Ernö Rubik’s Cube
+ *    Command line front end of the cube simulation
  *
  *    Project:            cubus
  *
- *    Target language:    Rust 1.57.0
+ *    Target language:    Rust 1.82.0
  *
  *    Text encoding:      UTF-8
  *
@@ -22,1026 +22,2213 @@
 #![allow(non_snake_case)]
 
 
-use std::collections::VecDeque;
-use std::vec::Vec;
 use std::env;
 
 use std::{io, process};
-use std::fs::{File, OpenOptions};
+use std::fs::{self, OpenOptions};
 use std::io::Write;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+use cubus::*;
 
-/*  ––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––  */
 
-
-/// A short unsigned integer type for cube-local coordinate values.
-/// Since the maximum cube size is 10, 4 bits would actually suffice.
-type Coord = u8;
-
-
-/// A type that designates a coordinate axis and a rotation direction.
-type Axis = char;
+/*  ––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––  *
+ *
+ *      Practice
+ */
 
 
-/// A brick location in a cube-local coordinate system.
-#[derive(Eq, PartialEq, Copy, Clone)]
-struct Loc
+/// Runs the program named after `event` in the hooks directory, if there
+/// is one, with CUBUS_EVENT and the given variables in its environment,
+/// and waits for it.  A hook that fails is reported, but training goes on.
+fn run_hook (hooksDir: Option<&str>, event: &str, vars: &[(&str, String)])
 {
-    x: Coord,
-    y: Coord,
-    z: Coord
+    let Some(dir) = hooksDir
+    else
+    {
+        return;
+    };
+    let path = format!("{}/{}", dir, event);
+    if !fs::metadata(&path).is_ok_and(|meta| meta.is_file())
+    {
+        return;
+    }
 
-}   /* Loc */
+    let mut command = process::Command::new(&path);
+    command.env("CUBUS_EVENT", event).stdin(process::Stdio::null());
+    for (name, value) in vars.iter()
+    {
+        command.env(name, value);
+    }
+    match command.status()
+    {
+        Ok(status) if status.success()
+                    =>  (),
+        Ok(status)  =>  { writeln!(io::stderr(), "cubus: hook {} {}", path, status); },
+        Err(error)  =>  { writeln!(io::stderr(), "cubus: hook {}: {}", path, error); }
+    }
 
-/// Component accessors.
-fn get_x (loc: &Loc) -> Coord { loc.x }
-fn get_y (loc: &Loc) -> Coord { loc.y }
-fn get_z (loc: &Loc) -> Coord { loc.z }
+}   /* run_hook() */
 
 
-/// Symbolic names for cube face colors.
-#[derive(Eq, PartialEq, Copy, Clone)]
-enum Huename
+/// Runs the practice loop of `cubus train`: shows a scrambled cube, waits
+/// for the user to solve a physical cube and press Enter, and reports the
+/// time taken.  Scrambles are random moves, or with a case setups of it,
+/// see drill_movevec().  Cubes are drawn in `budget` bytes where they can,
+/// see draw_cube_within_to().  Each scramble and time is appended to the
+/// log, if any.  The hooks `scramble`, `solve` and `best`, for a time
+/// below any logged for the size, run as each event happens.
+#[allow(clippy::too_many_arguments)]
+fn run_training (size: Coord, length: usize, case: Option<&str>, logPath: Option<&str>, hooksDir: Option<&str>,
+                 style: RenderStyle, budget: usize, scheme: &ColorScheme, axes: &AxisConvention, isSingmaster: bool,
+                 rng: &mut Rng)
 {
-    RD = 0x01,
-    OR = 0x02,
-    WT = 0x03,
-    YL = 0x04,
-    GN = 0x05,
-    BL = 0x06
+    let mut log = logPath.map(|path| exit_on_error_at(OpenOptions::new().create(true).append(true).open(path), path));
+    let mut best = logPath.map(read_solve_log).unwrap_or_default().iter()
+                          .filter(|record| record.size == size).map(|record| record.seconds)
+                          .fold(f64::INFINITY, f64::min);
 
-}   /* Huename */
-
-impl Huename
-{
-    /// Maps cube face color symbols to VT100 color control sequences.
-    fn vt100_attrs (&self)
-    -> &'static str
+    let stdin = io::stdin();
+    for scrambleNum in 1 ..
     {
-        // "\e[2;30;40m" : Black
-        // "\e[2;31;41m" : Red
-        // "\e[2;32;42m" : Green
-        // "\e[2;33;43m" : Yellow
-        // "\e[2;34;44m" : Blue
-        // "\e[2;35;45m" : Magenta
-        // "\e[2;36;46m" : Cyan
-        // "\e[1;37;47m" : White
-        match *self
+        let scramble = match case
         {
-            Huename::RD  => "\x1B[2;31;41m",
-            Huename::OR  => "\x1B[2;36;46m",    // Using Cyan for Orange.
-            Huename::WT  => "\x1B[1;37;47m",
-            Huename::YL  => "\x1B[2;33;43m",
-            Huename::GN  => "\x1B[2;32;42m",
-            Huename::BL  => "\x1B[2;34;44m"
+            Some(name)  =>  exit_on_error(drill_movevec(name, rng)),
+            None        =>  random_movevec(size, length, rng)
+        };
+        let scrambleStr = string_of_moves(&scramble, axes, isSingmaster, size - 1);
+        let cube = exit_on_error(Cube::new(size).copy_with_moves(&scramble));
+        exit_on_error(draw_cube_within(&scheme.recolored(&cube), 1, 2, style, budget));
+
+        println!("Scramble {}: {}", scrambleNum, scrambleStr);
+        let mut vars = vec![("CUBUS_SIZE", size.to_string()),
+                            ("CUBUS_SCRAMBLE", scrambleStr.clone()),
+                            ("CUBUS_STATE", hex_of_bytes(&cube.to_packed()))];
+        run_hook(hooksDir, "scramble", &vars);
+        print!("Press Enter when solved, or q and Enter to quit: ");
+        io::stdout().flush();
+
+        let started = Instant::now();
+        let mut answer = String::new();
+        if stdin.read_line(&mut answer).unwrap_or(0) == 0 || answer.trim() == "q"
+        {
+            break;
         }
+        let elapsed = started.elapsed().as_secs_f64();
+        println!("Time: {:.2} s", elapsed);
 
-    } /* .vt100_attrs() */
-
-}   /* impl Huename */
+        if let Some(file) = log.as_mut()
+        {
+            let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or(0);
+            writeln!(file, "{}\t{}\t{}\t{:.2}", timestamp, size, scrambleStr, elapsed);
+        }
 
+        vars.push(("CUBUS_TIME", format!("{:.2}", elapsed)));
+        run_hook(hooksDir, "solve", &vars);
+        if elapsed < best
+        {
+            if best.is_finite()
+            {
+                println!("New best for {}x{}x{}, {:.2} s faster", size, size, size, best - elapsed);
+                vars.push(("CUBUS_PREVIOUS_BEST", format!("{:.2}", best)));
+                run_hook(hooksDir, "best", &vars);
+            }
+            best = elapsed;
+        }
+    }
 
-/// Face color distributions for a cube or a brick.
-#[derive(Eq, Copy, Clone)]
-struct Hue
+}   /* run_training() */
+
+
+/// What to do next in the layer-by-layer method, by the stage that the
+/// solve has reached, see SolvedReport::stage().
+const LESSONS: [&str; 7] = [
+"Step 1, the cross.  Pick a face, white by custom, and bring its four
+edges around its center, each with its other color matching the center
+of the side face next to it.  Place one edge at a time: turn it into the
+top layer, above the side center of its other color, then turn that side
+face twice to bring it down.",
+"Step 2, the first two layers.  Hold the cube with the cross face down.
+Each slot between two side faces takes a corner of the cross face and the
+edge above it.  Bring a corner into the top layer above its slot at the
+front right, and repeat R U R' U' until it sits in place.  Then turn its
+edge to the top front, above the center of its front color, and insert
+it with U R U' R' U' F' U F to the right or U' L' U L U F U' F' to the
+left.",
+"Step 2 goes on: fill the next slot of the first two layers the same way.",
+"Step 2 goes on: two slots left.",
+"Step 2 goes on: one slot left.",
+"Step 3, the top face.  Make a cross of the top color with F R U R' U' F',
+holding a line of it from left to right, or an angle of it at the back
+left, and repeating as needed.  Then turn the top corners up with the
+Sune, R U R' U R U2 R', turning the top face in between, until the whole
+top face shows one color.",
+"Step 4, the top pieces.  Put the top corners in place with
+R' F R' B2 R F' R' B2 R2, which keeps the corner at the front left and
+cycles the other three; hold a corner that is already in place there.  Then
+cycle the top edges with R2 U R U R' U' R' U' R' U R', which keeps the
+back edge, until the cube is solved."];
+
+
+/// Returns the names of the pieces that the current step of the tutorial
+/// works on: the edges of the cross face, then its corners and the edges
+/// above them, then the top layer.  The cross face has the color `bottom`,
+/// or white before there is a cross.
+fn lesson_pieces (stage: usize, bottom: Huename)
+-> Vec<String>
 {
-    xpos: Huename,
-    xneg: Huename,
-    ypos: Huename,
-    yneg: Huename,
-    zpos: Huename,
-    zneg: Huename
-
-}   /* Hue */
+    let home = Cube::new(3);
+    let faceOf = |name: Huename| Face::ALL.into_iter().position(|face| home.sticker_at(face, 1, 1) == Some(name)).unwrap();
+    let letters = ['R', 'L', 'U', 'D', 'F', 'B'];
+    let down = faceOf(bottom);
+    let up = down ^ 1;
+    let sides: Vec<usize> = (0 .. 6).filter(|&face| face / 2 != down / 2).collect();
+    let corners = sides.iter().flat_map(|&lhs| sides.iter().filter(move |&&rhs| lhs / 2 < rhs / 2).map(move |&rhs| (lhs, rhs)));
+
+    match stage
+    {
+        0   =>  sides.iter().map(|&side| format!("{}{}", letters[down], letters[side])).collect(),
+        1 ..= 5
+            =>  corners.flat_map(|(lhs, rhs)| [format!("{}{}{}", letters[down], letters[lhs], letters[rhs]),
+                                               format!("{}{}", letters[lhs], letters[rhs])]).collect(),
+        _   =>  sides.iter().map(|&side| format!("{}{}", letters[up], letters[side]))
+                     .chain(corners.map(|(lhs, rhs)| format!("{}{}{}", letters[up], letters[lhs], letters[rhs])))
+                     .collect()
+    }
 
-impl PartialEq for Hue
+}   /* lesson_pieces() */
+
+
+/// Runs the tutorial of `cubus learn`: scrambles a 3x3x3 cube and walks
+/// through the layer-by-layer method, one step at a time.  Moves typed in
+/// Singmaster notation turn the cube on screen, and each time the tutorial
+/// tells what they reached or undid and picks the lesson up from where the
+/// cube is, also when moves went astray.  ‘hint’ highlights the pieces
+/// of the step, and ‘hint move’, ‘hint step’ and ‘hint all’ reveal the
+/// next move, the rest of the step or the whole solution of a HintPlan,
+/// which follows the moves typed.  ‘undo’ takes the last moves back, ‘lesson’ repeats the
+/// step's explanation, and q quits.  The moves are kept as a MoveTree:
+/// moves typed after ‘undo’ start a branch, ‘goto N’ returns to line N of
+/// the tree, ‘tree’ shows or hides it, and ‘save F’ and ‘load F’ write the
+/// scramble and the tree to the file F and read them back.
+fn run_learning (style: RenderStyle, scheme: &ColorScheme, rng: &mut Rng)
 {
-    fn eq (&self, other: &Hue)
-    -> bool
+    let colorName = |name: Huename| match scheme.paint(name)
     {
-        self.xpos == other.xpos
-     && self.ypos == other.ypos
-     && self.zpos == other.zpos
-    }
+        Huename::RD =>  "red",
+        Huename::OR =>  "orange",
+        Huename::WT =>  "white",
+        Huename::YL =>  "yellow",
+        Huename::GN =>  "green",
+        Huename::BL =>  "blue"
+    };
 
-    fn ne (&self, other: &Hue)
-    -> bool
+    // Face turns only, which leave the centers where the lessons expect them.
+    let outer: Vec<Move> = random_movevec(3, 50, rng).into_iter().filter(|mov| mov.axval != 1).collect();
+    let mut scramble = movevec_simplified(&outer);
+    scramble.truncate(25);
+    let mut scrambleStr = singmaster_string_of_movevec(&scramble, 2);
+    let mut start = exit_on_error(Cube::new(3).copy_with_moves(&scramble));
+    let mut cube = start.clone();
+    let mut report = cube.solved_report().unwrap();
+    let mut tree = MoveTree::new();
+    let mut notes: Vec<String> = vec![LESSONS[report.stage().min(6)].to_string()];
+    let mut isHinted = false;
+    let mut hints: Option<HintPlan> = None;
+    let mut showsTree = false;
+
+    let stdin = io::stdin();
+    loop
     {
-        self.xpos != other.xpos
-     || self.ypos != other.ypos
-     || self.zpos != other.zpos
+        let stage = report.stage();
+        let bottom = report.bottom.unwrap_or(Huename::WT);
+        if isHinted
+        {
+            let pieces = lesson_pieces(stage, bottom);
+            let pieces: Vec<&str> = pieces.iter().map(String::as_str).collect();
+            exit_on_error(draw_cube_highlighted(&scheme.recolored(&cube), &pieces, 1, 2, style));
+        }
+        else
+        {
+            exit_on_error(draw_cube(&scheme.recolored(&cube), 1, 2, style));
+        }
+
+        let solution = tree.path();
+        println!("Scramble: {}", scrambleStr);
+        println!("Moves so far: {}", singmaster_string_of_movevec(&solution, 2));
+        if let Some(plan) = &hints
+        {
+            for (name, moves) in plan.revealed()
+            {
+                println!("Hint, {}: {}", name, singmaster_string_of_movevec(&moves, 2));
+            }
+        }
+        println!("{}", report.as_string());
+        if showsTree || tree.has_branches()
+        {
+            println!("\nLines tried, * where you are:");
+            for (node, depth) in tree.outline()
+            {
+                println!("{} {:>3}  {}{}", if node == tree.current() {'*'} else {' '}, node,
+                         "  ".repeat(depth), singmaster_string_of_movevec(tree.moves_of(node), 2));
+            }
+        }
+        for note in notes.drain(..)
+        {
+            println!("\n{}", note);
+        }
+        if stage == 7
+        {
+            println!("\nSolved in {}.  Well done!", MoveMetrics::of_movevec(&solution, 3).as_string());
+        }
+
+        print!("\nMoves, or hint [move|step|all], undo, goto N, tree, save F, load F, lesson, q: ");
+        io::stdout().flush();
+        let mut answer = String::new();
+        if stdin.read_line(&mut answer).unwrap_or(0) == 0 || answer.trim() == "q"
+        {
+            break;
+        }
+
+        isHinted = false;
+        match answer.trim()
+        {
+            ""          =>  continue,
+            "hint"      =>  {
+                isHinted = true;
+                notes.push(format!("The highlighted pieces are those of this step, around the {} center.",
+                                   colorName(bottom)));
+                continue;
+            },
+            command if command.starts_with("hint ")
+                        =>  {
+                let level = match command["hint ".len() ..].trim()
+                {
+                    "move"  =>  HintLevel::NextMove,
+                    "step"  =>  HintLevel::NextStep,
+                    "all"   =>  HintLevel::Solution,
+                    _       =>  {
+                        notes.push("hint alone highlights the pieces of the step; hint move, hint step and hint all tell moves.".to_string());
+                        continue;
+                    }
+                };
+                // A new plan only when the cube left the old one, which keeps what it revealed.
+                if hints.as_ref().is_none_or(|plan| plan.done().is_none())
+                {
+                    match solve_layer_by_layer(&cube).and_then(|steps| HintPlan::new(&cube, steps))
+                    {
+                        Ok(plan)    =>  hints = Some(plan),
+                        Err(error)  =>  {
+                            notes.push(error.to_string());
+                            continue;
+                        }
+                    }
+                }
+                if hints.as_mut().unwrap().reveal(level).is_empty()
+                {
+                    notes.push("There is nothing left to do.".to_string());
+                }
+                continue;
+            },
+            "lesson"    =>  {
+                notes.push(LESSONS[stage.min(6)].to_string());
+                continue;
+            },
+            "tree"      =>  {
+                showsTree = !showsTree;
+                continue;
+            },
+            "undo"      =>  if !tree.back()
+            {
+                notes.push("There is nothing to undo.".to_string());
+                continue;
+            },
+            command if command.starts_with("goto ")
+                        =>  if !command["goto ".len() ..].trim().parse::<usize>().is_ok_and(|node| tree.goto(node))
+            {
+                notes.push("The tree has no such line; tree shows its numbers, 0 is the scramble.".to_string());
+                continue;
+            },
+            command if command.starts_with("save ")
+                        =>  {
+                let path = command["save ".len() ..].trim();
+                let text = format!("Scramble: {}\n{}", scrambleStr, tree.as_text(&|moves| singmaster_string_of_movevec(moves, 2)));
+                notes.push(match fs::write(path, text)
+                {
+                    Ok(())      =>  format!("Saved the scramble and the tree to {}.", path),
+                    Err(error)  =>  format!("{}: {}", path, error)
+                });
+                continue;
+            },
+            command if command.starts_with("load ")
+                        =>  {
+                let path = command["load ".len() ..].trim();
+                let loaded = fs::read_to_string(path).map_err(CubusError::from).and_then(|text| {
+                    let (first, rest) = text.split_once('\n').unwrap_or((&text, ""));
+                    let scramble = movevec_of_singmaster(first.strip_prefix("Scramble: ").unwrap_or(first), 2)?;
+                    Ok((singmaster_string_of_movevec(&scramble, 2), Cube::new(3).copy_with_moves(&scramble)?,
+                        MoveTree::of_text(rest, &|line| movevec_of_singmaster(line, 2))?))
+                });
+                match loaded
+                {
+                    Ok(state)   =>  {
+                        (scrambleStr, start, tree) = state;
+                        notes.push(format!("Loaded {}; goto N picks a line of its tree.", path));
+                        showsTree = true;
+                    },
+                    Err(error)  =>  {
+                        notes.push(format!("{}: {}", path, error));
+                        continue;
+                    }
+                }
+            },
+            movesStr    =>  match movevec_of_singmaster(movesStr, 2)
+            {
+                Ok(moves)   =>  { tree.play(moves); },
+                Err(error)  =>  {
+                    notes.push(format!("{}.  Type moves such as R U R' U', or lesson to read the step again.", error));
+                    continue;
+                }
+            }
+        }
+        cube = exit_on_error(start.copy_with_moves(&tree.path()));
+        if let Some(plan) = hints.as_mut()
+        {
+            let wasAhead = !plan.revealed().is_empty();
+            if !plan.follow(&cube) && wasAhead
+            {
+                notes.push("That left the hinted moves; undo goes back to them, or hint tells new ones from here.".to_string());
+            }
+        }
+
+        // Tell what the moves did, and adapt the lesson to where the cube is.
+        let before = report;
+        report = cube.solved_report().unwrap();
+        let newStage = report.stage();
+        if newStage > stage && newStage < 7
+        {
+            notes.push(format!("Well done, that reached {}.", SOLVE_STAGE_NAMES[newStage]));
+        }
+        else if newStage < stage
+        {
+            notes.push(format!("That undid {}; undo takes the moves back.", SOLVE_STAGE_NAMES[stage]));
+        }
+        if newStage != 0 && before.bottom.is_some() && report.bottom != before.bottom
+        {
+            notes.push(format!("You are building on the {} cross now.", colorName(report.bottom.unwrap())));
+        }
+        if newStage != stage && newStage < 7
+        {
+            notes.push(LESSONS[newStage].to_string());
+        }
     }
 
-}   /* impl PartialEq for Hue */
+}   /* run_learning() */
 
 
-/// Smallest movable cube fragment.
-#[derive(Eq, PartialEq, Copy, Clone)]
-struct Brick
+/// A practice solve as logged by `run_training()`.
+struct SolveRecord
 {
-    curLoc: Loc,
-    curHue: Hue
+    timestamp: u64,     // Seconds since the Unix epoch.
+    size:      Coord,
+    scramble:  String,
+    moves:     usize,   // Moves in the scramble, 0 if it reads in no notation.
+    seconds:   f64
 
-}   /* Brick */
+}   /* SolveRecord */
 
-impl Brick
-{
-    /// Brick constructor.
-    fn new (x: Coord, y: Coord, z: Coord)
-    -> Brick
-    {
-        Brick {
-            curLoc: Loc { x, y, z },
-            curHue: Hue {
-                xpos: Huename::RD, xneg: Huename::OR,
-                ypos: Huename::WT, yneg: Huename::YL,
-                zpos: Huename::GN, zneg: Huename::BL
-            }
-        }
 
-    } /* ::new() */
+/// Reads the solves logged in the given file, skipping malformed lines.
+fn read_solve_log (path: &str)
+-> Vec<SolveRecord>
+{
+    solve_records_of_text(&exit_on_error_at(fs::read_to_string(path), path))
 
-}   /* impl Brick */
+}   /* read_solve_log() */
 
 
-/// Rotates a brick counter-clockwise by 90° about the cube's X axis.
-fn brick_rotated_x_pos (brick: &Brick, axmax: Coord)
--> Brick
+/// Returns the solves of the lines of a solve log, skipping malformed ones.
+/// `train` logs each scramble as it printed it, with axes and layers or in
+/// Singmaster notation, so its moves are counted in whichever reads it.
+fn solve_records_of_text (text: &str)
+-> Vec<SolveRecord>
 {
-    let srcLoc = &brick.curLoc;
-    let srcHue = &brick.curHue;
-    Brick {
-        curLoc: Loc {
-            x: srcLoc.x,
-            y: axmax - srcLoc.z,
-            z: srcLoc.y
-        },
-        curHue: Hue {
-            xpos: srcHue.xpos,
-            xneg: srcHue.xneg,
-            ypos: srcHue.zneg,
-            yneg: srcHue.zpos,
-            zpos: srcHue.ypos,
-            zneg: srcHue.yneg
+    text.lines().filter_map(|line| {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() != 4
+        {
+            return None;
         }
-    }
+        let size = fields[1].parse::<Coord>().ok().filter(|&size| 0 < size && size < 11)?;
+        let moves = movevec_of_string(fields[2], size - 1).or_else(|_| movevec_of_singmaster(fields[2], size - 1))
+                                                          .map_or(0, |moves| moves.len());
+        Some(SolveRecord {
+            timestamp: fields[0].parse().ok()?,
+            size,
+            scramble:  fields[2].to_string(),
+            moves,
+            seconds:   fields[3].parse().ok()?
+        })
+    }).collect()
 
-}   /* brick_rotated_x_pos() */
+}   /* solve_records_of_text() */
 
 
-/// Rotates a brick clockwise by 90° about the cube's X axis.
-fn brick_rotated_x_neg (brick: &Brick, axmax: Coord)
--> Brick
+/// Returns the Unix time at the start of the given ‘YYYY-MM-DD’ date.
+fn unix_time_of_date (date: &str)
+-> Option<u64>
 {
-    let srcLoc = &brick.curLoc;
-    let srcHue = &brick.curHue;
-    Brick {
-        curLoc: Loc {
-            x: srcLoc.x,
-            y: srcLoc.z,
-            z: axmax - srcLoc.y
-        },
-        curHue: Hue {
-            xpos: srcHue.xpos,
-            xneg: srcHue.xneg,
-            ypos: srcHue.zpos,
-            yneg: srcHue.zneg,
-            zpos: srcHue.yneg,
-            zneg: srcHue.ypos
-        }
+    let parts: Vec<i64> = date.split('-').map(|part| part.parse().ok()).collect::<Option<_>>()?;
+    if parts.len() != 3 || parts[1] < 1 || 12 < parts[1] || parts[2] < 1 || 31 < parts[2]
+    {
+        return None;
     }
 
-}   /* brick_rotated_x_neg() */
+    // Days since 1970-01-01 in the proleptic Gregorian calendar,
+    // counting years from March so that leap days come last.
+    let (year, month, day) = (parts[0] - (parts[1] <= 2) as i64, parts[1], parts[2]);
+    let era = year.div_euclid(400);
+    let yearOfEra = year - era * 400;
+    let dayOfYear = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let dayOfEra = yearOfEra * 365 + yearOfEra / 4 - yearOfEra / 100 + dayOfYear;
+    let days = era * 146097 + dayOfEra - 719468;
+
+    u64::try_from(days * 86400).ok()
+
+}   /* unix_time_of_date() */
 
 
-/// Rotates a brick counter-clockwise by 90° about the cube's Y axis.
-fn brick_rotated_y_pos (brick: &Brick, axmax: Coord)
--> Brick
+/// Returns the given values as a line of block characters whose heights
+/// span the range from the smallest to the largest value.
+fn sparkline (values: &[f64])
+-> String
 {
-    let srcLoc = &brick.curLoc;
-    let srcHue = &brick.curHue;
-    Brick {
-        curLoc: Loc {
-            x: srcLoc.z,
-            y: srcLoc.y,
-            z: axmax - srcLoc.x
-        },
-        curHue: Hue {
-            xpos: srcHue.zpos,
-            xneg: srcHue.zneg,
-            ypos: srcHue.ypos,
-            yneg: srcHue.yneg,
-            zpos: srcHue.xneg,
-            zneg: srcHue.xpos
-        }
-    }
+    static BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+    let least = values.iter().cloned().fold(f64::INFINITY, f64::min);
+    let most  = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let range = most - least;
 
-}   /* brick_rotated_y_pos() */
+    values.iter().map(|&value| {
+        if range > 0.0 { BLOCKS[((value - least) / range * 7.0).round() as usize] } else { BLOCKS[3] }
+    }).collect()
 
+}   /* sparkline() */
 
-/// Rotates a brick clockwise by 90° about the cube's Y axis.
-fn brick_rotated_y_neg (brick: &Brick, axmax: Coord)
--> Brick
+
+/// Returns the solves logged in the given file that match the given cube
+/// size and lie within the given range of Unix times.
+fn read_solve_log_filtered (path: &str, size: Option<Coord>, since: u64, until: u64)
+-> Vec<SolveRecord>
 {
-    let srcLoc = &brick.curLoc;
-    let srcHue = &brick.curHue;
-    Brick {
-        curLoc: Loc {
-            x: axmax - srcLoc.z,
-            y: srcLoc.y,
-            z: srcLoc.x
-        },
-        curHue: Hue {
-            xpos: srcHue.zneg,
-            xneg: srcHue.zpos,
-            ypos: srcHue.ypos,
-            yneg: srcHue.yneg,
-            zpos: srcHue.xpos,
-            zneg: srcHue.xneg
-        }
-    }
+    read_solve_log(path).into_iter()
+                        .filter(|rec| size.is_none_or(|size| rec.size == size))
+                        .filter(|rec| since <= rec.timestamp && rec.timestamp < until)
+                        .collect()
 
-}   /* brick_rotated_y_neg() */
+}   /* read_solve_log_filtered() */
 
 
-/// Rotates a brick counter-clockwise by 90° about the cube's Z axis.
-fn brick_rotated_z_pos (brick: &Brick, axmax: Coord)
--> Brick
+/// Summarizes the given solves and graphs their solve times and scramble
+/// lengths in logged order.
+fn run_stats (records: &[SolveRecord])
 {
-    let srcLoc = &brick.curLoc;
-    let srcHue = &brick.curHue;
-    Brick {
-        curLoc: Loc {
-            x: axmax - srcLoc.y,
-            y: srcLoc.x,
-            z: srcLoc.z
-        },
-        curHue: Hue {
-            xpos: srcHue.yneg,
-            xneg: srcHue.ypos,
-            ypos: srcHue.xpos,
-            yneg: srcHue.xneg,
-            zpos: srcHue.zpos,
-            zneg: srcHue.zneg
-        }
+    if records.is_empty()
+    {
+        println!("No solves.");
+        return;
     }
 
-}   /* brick_rotated_z_pos() */
+    let times: Vec<f64> = records.iter().map(|rec| rec.seconds).collect();
+    let moves: Vec<f64> = records.iter().map(|rec| rec.moves as f64).collect();
+    let best = times.iter().cloned().fold(f64::INFINITY, f64::min);
+    let worst = times.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let mean = times.iter().sum::<f64>() / times.len() as f64;
+
+    println!("Solves: {}  Best: {:.2} s  Mean: {:.2} s  Worst: {:.2} s",
+             records.len(), best, mean, worst);
+    println!("Times:  {}", sparkline(&times));
+    println!("Moves:  {}", sparkline(&moves));
 
+}   /* run_stats() */
 
-/// Rotates a brick clockwise by 90° about the cube's Z axis.
-fn brick_rotated_z_neg (brick: &Brick, axmax: Coord)
--> Brick
+
+/// Runs the experiment of `cubus randomwalk`: tabulates how far random walks
+/// stray from the ordered state, step by step, as the mean number and share
+/// of misplaced stickers.  The share levels off once the walks have mixed.
+fn run_random_walk (size: Coord, steps: usize, walks: usize, rng: &mut Rng)
 {
-    let srcLoc = &brick.curLoc;
-    let srcHue = &brick.curHue;
-    Brick {
-        curLoc: Loc {
-            x: srcLoc.y,
-            y: axmax - srcLoc.x,
-            z: srcLoc.z
-        },
-        curHue: Hue {
-            xpos: srcHue.ypos,
-            xneg: srcHue.yneg,
-            ypos: srcHue.xneg,
-            yneg: srcHue.xpos,
-            zpos: srcHue.zpos,
-            zneg: srcHue.zneg
-        }
+    let distances = random_walk_distances(size, steps, walks, rng);
+    let stickerNum = 6.0 * size as f64 * size as f64;
+
+    println!("Step   Misplaced   Share");
+    for (step, distance) in distances.iter().enumerate()
+    {
+        println!("{:>4}  {:>10.2}  {:>5.1}%", step, distance, 100.0 * distance / stickerNum);
     }
+    println!("Walks: {}  Mixing: {}", walks, sparkline(&distances));
 
-}   /* brick_rotated_z_neg() */
+}   /* run_random_walk() */
 
 
-/// Performs the indicated move on the given Brick vector
-/// and returns a new vector in the resulting state.
-fn brickvec_move (bricks: &[Brick], axdir: Axis, axval: Coord, axmax: Coord)
--> Vec<Brick>
-{
-    // A function that returns a fixed coordinate component of a Loc.
-    let selFun: fn (&Loc) -> Coord =
-    match axdir
-    {
-        'X' | 'x' =>  get_x,
-        'Y' | 'y' =>  get_y,
-        'Z' | 'z' =>  get_z,
-        _         =>  panic!("Invalid axis designator {}", axdir)
-    };
+/*  ––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––  *
+ *
+ *      Scripting
+ */
 
-    // A function that rotates a brick ±90° at a time around a fixed cube axis.
-    let rotFun: fn (&Brick, Coord) -> Brick =
-    match axdir
-    {
-        'X' =>  brick_rotated_x_pos,
-        'x' =>  brick_rotated_x_neg,
-        'Y' =>  brick_rotated_y_pos,
-        'y' =>  brick_rotated_y_neg,
-        'Z' =>  brick_rotated_z_pos,
-        'z' =>  brick_rotated_z_neg,
-        _   =>  panic!("Invalid axis designator {}", axdir)
-    };
 
-    let mut newBricks: Vec<Brick> = Vec::with_capacity(bricks.len());
-    for brick in bricks.iter()
+/// Runs the Rhai script in the given file.  Scripts can create cubes with
+/// `cube(N)`, move them with `c.apply(Moves)` or `c.moved(Moves)`, compose
+/// them with `*`, compare them with `==` and `c.changed(other)`, test them
+/// with `c.solved`, print them as a net or draw them with `c.draw()`, and
+/// search with `find(src, dst, maxLen)`.
+#[cfg(feature = "rhai")]
+fn run_script (path: &str)
+{
+    use rhai::{Array, Dynamic, Engine, EvalAltResult};
+
+    let mut engine = Engine::new();
+    engine.register_type_with_name::<Cube>("Cube")
+          .register_fn("cube", |size: i64| -> Result<Cube, Box<EvalAltResult>> {
+              if !(1 ..= 10).contains(&size)
+              {
+                  return Err(format!("Invalid cube size {}", size).into());
+              }
+              Ok(Cube::new(size as Coord))
+          })
+          .register_get("size", |cube: &mut Cube| cube.size as i64)
+          .register_get("solved", |cube: &mut Cube| cube.is_solved())
+          .register_fn("to_string", |cube: &mut Cube| cube.to_string())
+          .register_fn("apply", |cube: &mut Cube, moves: &str| -> Result<(), Box<EvalAltResult>> {
+              Ok(movevec_of_string(moves, cube.size - 1).and_then(|moves| cube.apply_moves(&moves))
+                                                        .map_err(|error| error.to_string())?)
+          })
+          .register_fn("moved", |cube: &mut Cube, moves: &str| -> Result<Cube, Box<EvalAltResult>> {
+              Ok(movevec_of_string(moves, cube.size - 1).and_then(|moves| cube.copy_with_moves(&moves))
+                                                        .map_err(|error| error.to_string())?)
+          })
+          .register_fn("changed", |cube: &mut Cube, other: Cube| {
+              // The number of bricks whose location or orientation differs.
+              cube.bricks.iter().zip(other.bricks.iter())
+                             .filter(|(lhs, rhs)| lhs != rhs)
+                             .count() as i64
+          })
+          .register_fn("draw", |cube: &mut Cube| -> Result<(), Box<EvalAltResult>> {
+              Ok(draw_cube(cube, 1, 2, RenderStyle::detect()).map_err(|error| error.to_string())?)
+          })
+          .register_fn("*", |lhs: Cube, rhs: Cube| -> Result<Cube, Box<EvalAltResult>> {
+              Ok((lhs * rhs).map_err(|error| error.to_string())?)
+          })
+          .register_fn("==", |lhs: Cube, rhs: Cube| lhs == rhs)
+          .register_fn("!=", |lhs: Cube, rhs: Cube| lhs != rhs)
+          .register_fn("find", |src: Cube, dst: Cube, maxLen: i64| -> Result<Array, Box<EvalAltResult>> {
+              let (found, _) = find_moves(maxLen.max(0) as usize, &src, &dst, None).map_err(|error| error.to_string())?;
+              Ok(found.into_iter().map(Dynamic::from).collect())
+          });
+
+    if let Err(error) = engine.run_file(path.into())
     {
-        if selFun(&brick.curLoc) == axval
-        {
-            // Bricks in the affected layer are rotated.
-            newBricks.push(rotFun(brick, axmax));
-        }
-        else
-        {
-            // Unaffected bricks are just copied.
-            newBricks.push(brick.clone());
-        }
+        writeln!(io::stderr(), "{}: {}", path, error);
+        process::exit(1);
     }
 
-    newBricks
-
-}   /* brickvec_move() */
+}   /* run_script() */
 
 
-/// Casts a move's identity as an integer, for fast equality tests.
-fn ident_of_move (axdir: Axis, axval: Coord)
--> u16
+/// Stands in for the script runner when cubus is built without Rhai.
+#[cfg(not(feature = "rhai"))]
+fn run_script (_path: &str)
 {
-    (((axdir as u16) & 0x00FF) << 8) | (axval as u16)
+    writeln!(io::stderr(), "This cubus was built without script support; rebuild with --features rhai.");
+    process::exit(1);
 
-}   /* ident_of_move() */
+}   /* run_script() */
 
 
-/// A move on a cube, which is the rotation of a layer of bricks
-/// around the selected cube axis by 90° at a time.  Affected bricks
-/// are identified by their coordinate value on the rotation axis.
-#[derive(Eq, PartialEq, Copy, Clone)]
-struct Move
+/// Writes the practice sheet of practice_sheet_pdf() to the given file.
+#[cfg(feature = "pdf")]
+fn write_sheet (path: &str, cube: &Cube, scramble: &str, solution: &[Move], scheme: &ColorScheme,
+                format: &dyn Fn(&[Move]) -> String)
+-> Result<(), CubusError>
 {
-    axdir:  Axis,
-    axval:  Coord,
-    ident:  u16
+    fs::write(path, practice_sheet_pdf(cube, scramble, solution, scheme, format)?)?;
 
-}   /* Move */
+    Ok(())
 
+}   /* write_sheet() */
 
-/// Returns a vector of Moves that were parsed from the given string.
-fn movevec_of_string (string: &str, axmax: Coord)
--> Vec<Move>
-{
-    let maxChr = ('0' as u8 + axmax) as char;
 
-    let mut moves: Vec<Move> = vec![];
+/// Stands in for the sheet writer when cubus is built without PDF support.
+#[cfg(not(feature = "pdf"))]
+fn write_sheet (_path: &str, _cube: &Cube, _scramble: &str, _solution: &[Move], _scheme: &ColorScheme,
+                _format: &dyn Fn(&[Move]) -> String)
+-> Result<(), CubusError>
+{
+    writeln!(io::stderr(), "This cubus was built without PDF support; rebuild with --features pdf.");
+    process::exit(1);
 
-    let mut count: u8 = 1;
-    let mut axdir: Axis = '_';
-    let mut expectsAxis = true;
-    let mut isInComment = false;
-    for chr in string.chars()
-    {
-        if isInComment
-        {
-            // Ignore until end.
-            if chr == '\n'
-            {
-                isInComment = false;
-            }
-        }
-        else
-        if expectsAxis
-        {
-            if chr == 'X' || chr == 'x'
-            || chr == 'Y' || chr == 'y'
-            || chr == 'Z' || chr == 'z'
-            {
-                // Consume move axis.
-                axdir = chr;
+}   /* write_sheet() */
 
-                expectsAxis = false;
-            }
-            else
-            if '2' <= chr && chr <= '9'
-            {
-                // A prefixed digit acts as a repeat count.
-                count = (chr as u8 - '0' as u8) % 4u8;
-            }
-            else
-            if chr == '#'
-            {
-                isInComment = true;
-            }
-        }
-        else
-        {
-            // Expecting a coordinate digit.
-            if '0' <= chr && chr <= maxChr
-            {
-                let axval = (chr as u8 - '0' as u8) as Coord;
 
-                let newMove = Move { axdir, axval, ident: 0 };
-                while count != 0
-                {
-                    moves.push(newMove.clone());
-                    count -= 1;
-                }
+/// Writes the given solves in csTimer's export format, one session per
+/// cube size.  cubus records no penalties, so every solve counts as OK.
+fn write_cstimer (out: &mut dyn Write, records: &[SolveRecord])
+-> io::Result<()>
+{
+    let mut sizes: Vec<Coord> = records.iter().map(|rec| rec.size).collect();
+    sizes.sort();
+    sizes.dedup();
 
-                expectsAxis = true;
-                count = 1;
-            }
-            else
-            {
-                panic!("Invalid coordinate value {}", chr);
-            }
-        }
+    let mut sessionData: Vec<String> = vec![];
+    write!(out, "{{")?;
+    for (ind, &size) in sizes.iter().enumerate()
+    {
+        let solves: Vec<String> =
+            records.iter().filter(|rec| rec.size == size)
+                          .map(|rec| format!("[[0,{}],{},\"\",{}]",
+                                             (rec.seconds * 1000.0).round() as u64,
+                                             json_string(&rec.scramble), rec.timestamp))
+                          .collect();
+        write!(out, "\"session{}\":[{}],", ind + 1, solves.join(","))?;
+        sessionData.push(format!("\"{}\":{{\"name\":\"cubus {}x{}x{}\",\"opt\":{{}},\"rank\":{}}}",
+                                 ind + 1, size, size, size, ind + 1));
     }
+    writeln!(out, "\"properties\":{{\"sessionN\":{},\"sessionData\":{}}}}}",
+             sizes.len(), json_string(&format!("{{{}}}", sessionData.join(","))))?;
 
-    moves
+    Ok(())
 
-}   /* movevec_of_string() */
+}   /* write_cstimer() */
 
 
-/// A Rubik's cube with a given edge length.
-#[derive(Eq, PartialEq, Clone)]
-struct Cube
+/// Layout of printed move sequences.
+struct SeqFormat
 {
-    size:   Coord,
-    bricks: Vec<Brick>
+    columns: usize,     // Sequences per line.
+    groupBy: usize,     // Moves per space-separated group, or 0.
+    sep:     String,    // Separator between sequences on a line.
+    brackets: bool,     // Write conjugates and commutators in brackets.
+    repeats: bool,      // Write repeated blocks as powers.
+    metrics: bool,      // Follow sequences by their lengths in each metric.
+    singmaster: bool    // Write face letters instead of axes and layers.
 
-}   /* Cube */
+}   /* SeqFormat */
 
-impl Cube
+impl SeqFormat
 {
-    /// Cube constructor.
-    fn new (size: Coord)
-    -> Cube
+    /// Returns a sequence string with a space after every `groupBy` moves.
+    fn grouped (&self, seqStr: &str)
+    -> String
     {
-        assert!(0 < size && size < 11);
-
-        let axmax = size - 1;
-        let mut bricks: Vec<Brick> = vec![];
+        if self.groupBy == 0
+        {
+            return seqStr.to_string();
+        }
 
-        for z in 0 .. size
+        let mut string = String::with_capacity(seqStr.len() * 2);
+        let mut moveNum = 0;
+        for chr in seqStr.chars()
         {
-            for y in 0 .. size
+            // Each move begins with its axis letter.
+            if chr.is_ascii_alphabetic()
             {
-                for x in 0 .. size
+                if moveNum != 0 && moveNum % self.groupBy == 0
                 {
-                    // We're only interested in bricks that partake in the cube's surface.
-                    if x == 0 || x == axmax
-                    || y == 0 || y == axmax
-                    || z == 0 || z == axmax
-                    {
-                        bricks.push(Brick::new(x, y, z));
-                    }
+                    string.push(' ');
                 }
+                moveNum += 1;
             }
+            string.push(chr);
         }
 
-        Cube {
-            size,
-            bricks
-        }
+        string
 
-    } /* ::new() */
+    }   /* .grouped() */
 
-    /// Manipulates the receiving Cube instance according to the given Move
-    /// sequence and returns a new Cube instance in the resulting state.
-    fn copy_with_moves (&self, moves: &[Move])
-    -> Cube
+    /// Returns the given moves, of a cube with the given maximum
+    /// coordinate, as a string in this format.
+    fn moves_string (&self, moves: &[Move], axmax: Coord)
+    -> String
     {
-        let size  = self.size;
-        let axmax = size - 1;
-
-        let mut bricks = self.bricks.clone();
-        for mov in moves.iter()
+        match (self.brackets, self.repeats, self.singmaster)
         {
-            bricks = brickvec_move(&bricks, mov.axdir, mov.axval, axmax);
+            (true, _, true)         =>  bracketed_singmaster_string_of_movevec(moves, axmax),
+            (true, _, false)        =>  bracketed_string_of_movevec(moves),
+            (false, true, true)     =>  repeated_singmaster_string_of_movevec(moves, axmax),
+            (false, true, false)    =>  repeated_string_of_movevec(moves),
+            (false, false, true)    =>  singmaster_string_of_movevec(moves, axmax),
+            (false, false, false)   =>  self.grouped(&string_of_movevec(moves))
         }
 
-        Cube {
-            size,
-            bricks
-        }
+    }   /* .moves_string() */
 
-    } /* .copy_with_moves() */
+    /// Returns the lengths of the given moves in each metric, set off to
+    /// follow them, if the format asks for them, or else nothing.
+    fn metrics_string (&self, moves: &[Move], axmax: Coord)
+    -> String
+    {
+        if self.metrics { format!("  ({})", MoveMetrics::of_movevec(moves, axmax + 1).as_string()) }
+        else            { String::new() }
 
-}   /* impl Cube */
+    }   /* .metrics_string() */
 
+    /// Prints the given sequences of a cube with the given maximum
+    /// coordinate, `columns` per line.
+    fn print (&self, seqStrs: &[String], axmax: Coord)
+    {
+        for (ind, seqStr) in seqStrs.iter().enumerate()
+        {
+            if ind % self.columns != 0
+            {
+                print!("{}", self.sep);
+            }
 
-/*  ––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––  *
- *
- *      Move Finding
- */
+            match seqStr.parse::<MoveSequence>()
+            {
+                Ok(seq) =>  print!("{}{}", self.moves_string(&seq.moves, axmax), self.metrics_string(&seq.moves, axmax)),
+                Err(_)  =>  print!("{}", self.grouped(seqStr))
+            }
 
-/// A memory structure storing which layers have been moved.
-struct Layers
-{
-    xpos: Vec<bool>,
-    xneg: Vec<bool>,
-    ypos: Vec<bool>,
-    yneg: Vec<bool>,
-    zpos: Vec<bool>,
-    zneg: Vec<bool>
-
-}   /* Layers */
-
-// Vec allocation helper.
-fn vec_of_size<T: Clone> (size: usize, value: T)
--> Vec<T>
-{
-    let mut vec: Vec<T> = vec![];
-    vec.resize(size, value);
+            if (ind + 1) % self.columns == 0
+            {
+                println!();
+            }
+        }
+        if seqStrs.len() % self.columns != 0
+        {
+            println!();
+        }
+
+    }   /* .print() */
 
-    vec
+}   /* impl SeqFormat */
 
-}   /* vec_of_size<T>() */
 
-impl Layers
+/// Returns the value of a successful result, or else reports the error on
+/// stderr and exits.
+fn exit_on_error<T> (result: Result<T, CubusError>)
+-> T
 {
-    /// Layers constructor.
-    fn new (size: Coord)
-    -> Layers
-    {
-        let size: usize = size as usize;
-        Layers {
-            xpos: vec_of_size(size, false),
-            xneg: vec_of_size(size, false),
-            ypos: vec_of_size(size, false),
-            yneg: vec_of_size(size, false),
-            zpos: vec_of_size(size, false),
-            zneg: vec_of_size(size, false)
-        }
-
-    } /* ::new() */
-
-    /// Sets the flag for the layer identified by axis and coordinate value.
-    fn set_flag (&mut self, axdir: Axis, axval: Coord)
-    {
-        let axflags = match axdir
-        {
-            'X' =>  &mut self.xpos,
-            'x' =>  &mut self.xneg,
-            'Y' =>  &mut self.ypos,
-            'y' =>  &mut self.yneg,
-            'Z' =>  &mut self.zpos,
-            'z' =>  &mut self.zneg,
-            _   =>  panic!()
-        };
+    match result
+    {
+        Ok(value)   =>  value,
+        Err(error)  =>
+        {
+            writeln!(io::stderr(), "cubus: {}", error);
+            process::exit(1);
+        }
+    }
 
-        axflags[axval as usize] = true;
+}   /* exit_on_error() */
 
-    } /* .set_flag() */
 
-    /// Tests the flag for the layer identified by axis and coordinate value.
-    fn has_flag (&self, axdir: Axis, axval: Coord)
-    -> bool
+/// Like exit_on_error(), for work on the file or directory at `path`,
+/// which the report names.
+fn exit_on_error_at<T, E: Into<CubusError>> (result: Result<T, E>, path: &str)
+-> T
+{
+    match result
     {
-        let axflags = match axdir
+        Ok(value)   =>  value,
+        Err(error)  =>
         {
-            'X' =>  &self.xpos,
-            'x' =>  &self.xneg,
-            'Y' =>  &self.ypos,
-            'y' =>  &self.yneg,
-            'Z' =>  &self.zpos,
-            'z' =>  &self.zneg,
-            _   =>  panic!()
-        };
-
-        axflags[axval as usize]
-
-    } /* .has_flag() */
+            writeln!(io::stderr(), "cubus: {}: {}", path, error.into());
+            process::exit(1);
+        }
+    }
 
-}   /* impl Layers */
+}   /* exit_on_error_at() */
 
 
-fn brickvec_eq (lhs: &[Brick], rhs: &[Brick])
--> bool
+/// Tells on stderr if a search was stopped by its time limit.
+fn report_cancelled (cancel: Option<&CancelToken>)
 {
-    let len = lhs.len();
-    if rhs.len() != len
+    if cancel.is_some_and(|token| token.is_cancelled())
     {
-        return false;
+        writeln!(io::stderr(), "Search stopped at the time limit; results are incomplete.");
     }
 
-    for ind in 0 .. len
+}   /* report_cancelled() */
+
+
+/// Opens the pattern databases of `solve --optimal` in the given file, by
+/// default in the user's cache directory, working them out and writing
+/// them there first if the file is missing.
+fn open_databases (path: Option<&str>)
+-> PatternDatabases
+{
+    let path = match path
     {
-        if lhs[ind] != rhs[ind]
+        Some(path)  =>  path.to_string(),
+        None        =>  match (env::var("XDG_CACHE_HOME"), env::var("HOME"))
         {
-            return false;
+            (Ok(dir), _)    =>  format!("{}/cubus/korf.pdb", dir),
+            (_, Ok(home))   =>  format!("{}/.cache/cubus/korf.pdb", home),
+            _               =>  "korf.pdb".to_string()
         }
-    }
+    };
 
-    true
+    match PatternDatabases::open(&path)
+    {
+        Err(CubusError::Io(error)) if error.kind() == io::ErrorKind::NotFound
+                    =>  {
+            writeln!(io::stderr(), "Working out the pattern databases {} once, which takes a few minutes ...", path);
+            let databases = PatternDatabases::generate();
+            exit_on_error(databases.write_to_file(&path));
+            databases
+        },
+        result      =>  exit_on_error(result)
+    }
 
-}   /* brickvec_eq() */
+}   /* open_databases() */
 
 
-/// An experimental move sequence in reverse, so the most recent moves are easily accessible.
-struct Trail
+/// Prints where the bricks with the given sticker colors are, and which
+/// way each of their colors faces, in the given color scheme.
+fn print_pieces (cube: &Cube, colors: &[Huename], scheme: &ColorScheme)
 {
-    steps: Vec<Move>
-
-}   /* Trail */
+    let axmax = cube.size - 1;
+    let letters: String = colors.iter().map(|&name| scheme.paint(name).letter()).collect();
 
-impl Trail
-{
-    /// Trail constructor.
-    fn new ()
-    -> Trail
+    let pieces = cube.find_piece(colors);
+    if pieces.is_empty()
     {
-        Trail { steps: vec![] }
-
-    }   /* ::new() */
-
-    /// Returns a sequel with the given next move in front.
-    fn proceed (&self, axdir: Axis, axval: Coord, ident: u16)
-    -> Trail
+        println!("No brick shows the colors {}.", letters);
+    }
+    for brick in pieces.iter()
     {
-        let mut sequel: Vec<Move> = Vec::with_capacity(self.steps.len() + 1);
+        let (loc, hue) = (&brick.curLoc, &brick.curHue);
+        let sides = [(loc.x == axmax, hue.xpos, "right"), (loc.x == 0, hue.xneg, "left"),
+                     (loc.y == axmax, hue.ypos, "up"),    (loc.y == 0, hue.yneg, "down"),
+                     (loc.z == axmax, hue.zpos, "front"), (loc.z == 0, hue.zneg, "back")];
+        let facing: Vec<String> = sides.iter()
+                                       .filter(|&&(isOut, _, _)| isOut)
+                                       .map(|&(_, name, side)| format!("{} {}", scheme.paint(name).letter(), side))
+                                       .collect();
+        println!("{} at x={} y={} z={}: {}", letters, loc.x, loc.y, loc.z, facing.join(", "));
+    }
 
-        sequel.push(Move { axdir, axval, ident });
-        sequel.extend(self.steps.iter().cloned());
+}   /* print_pieces() */
 
-        Trail { steps: sequel }
 
-    }   /* .proceed() */
+/// Tabulates how many of the given sequences have each length, with a bar
+/// each, and counts the different moves that they end with.
+fn print_length_histogram (seqStrs: &[String])
+{
+    let seqs: Vec<MoveSequence> = seqStrs.iter().filter_map(|seqStr| seqStr.parse().ok()).collect();
+    if seqs.is_empty()
+    {
+        return;
+    }
 
-    /// Returns the cube brick configuration produced by this Trail.
-    fn transform (&self, bricks: &Vec<Brick>, axmax: Coord)
-    -> Vec<Brick>
+    let mut counts: Vec<usize> = vec![];
+    let mut lastMoves: Vec<Move> = vec![];
+    for seq in seqs.iter()
     {
-        let mut bricks = bricks.clone();
-        for mov in self.steps.iter().rev()
+        if counts.len() <= seq.len()
         {
-            bricks = brickvec_move(&bricks, mov.axdir, mov.axval, axmax);
+            counts.resize(seq.len() + 1, 0);
         }
-
-        bricks
-
-    }   /* .transform() */
-
-    /// Returns the Trail's string representation.
-    fn as_string (&self)
-    -> String
-    {
-        let mut string = String::with_capacity(2 * self.steps.len());
-        for mov in self.steps.iter().rev()
+        counts[seq.len()] += 1;
+        if let Some(&last) = seq.moves.last()
         {
-            string = string + &format!("{}{}", mov.axdir, mov.axval);
+            if !lastMoves.contains(&last)
+            {
+                lastMoves.push(last);
+            }
         }
+    }
 
-        string
-
-    }   /* .as_string() */
+    let most = counts.iter().cloned().max().unwrap_or(0).max(1);
+    println!("Length   Sequences");
+    for (len, &count) in counts.iter().enumerate().filter(|&(_, &count)| count != 0)
+    {
+        println!("{:>6}  {:>10}  {}", len, count, "█".repeat((count * 40).div_ceil(most)));
+    }
+    println!("Last moves: {} different", lastMoves.len());
 
-}   /* impl Trail */
+}   /* print_length_histogram() */
 
 
-/// Returns the given axis with its rotational sense inverted.
-fn invert_axis (axdir: Axis)
--> Axis
+/// Returns a sequence of cube moves, as printed by string_of_movevec(), in
+/// the notation of the given axis convention.
+fn notation_string (seqStr: &str, axes: &AxisConvention, axmax: Coord)
+-> String
 {
-    // Flips uppercase <-> lowercase.
-    ((axdir as u8) ^ 0x20) as Axis
+    match movevec_of_string(seqStr, axmax)
+    {
+        Ok(moves)   =>  string_of_movevec(&axes.to_notation(&moves, axmax)),
+        Err(_)      =>  seqStr.to_string()
+    }
 
-}   /* invert_axis() */
+}   /* notation_string() */
 
 
-/// Finds all move sequences, no longer than maxLen, that transform the
-/// srcCube into the dstCube.
-fn find_moves (maxLen: usize, srcCube: &Cube, dstCube: &Cube)
--> (Vec<String>, u64)
+/// Returns the cube moves of a string in Singmaster notation, or else in
+/// the notation of the given axis convention, after warning on stderr of
+/// moves that are likely mistakes.
+fn moves_of_string (string: &str, axes: &AxisConvention, isSingmaster: bool, axmax: Coord)
+-> Result<Vec<Move>, CubusError>
 {
-    let cubeSize = srcCube.size;
-    if dstCube.size != cubeSize
+    if isSingmaster
     {
-        panic!("Cubes are of different size");
+        return movevec_of_singmaster(string, axmax);
     }
 
-    let axmax = cubeSize - 1;
+    let (moves, lints) = movevec_of_string_with_lints(string, axmax)?;
+    for lint in lints
+    {
+        writeln!(io::stderr(), "Warning: {}", lint);
+    }
 
-    let mut dblMovs = Layers::new(cubeSize);
-    let mut lastLen = 0;
+    Ok(axes.to_cube(&moves, axmax))
 
-    let mut trailQ: VecDeque<Trail> = VecDeque::new();
-    trailQ.push_back(Trail::new());
+}   /* moves_of_string() */
 
-    let mut seqStrs: Vec<String> = vec![];
-    let mut moveNum: u64 = 0;
 
-    // Process available trails.
-    while trailQ.len() != 0
+/// Returns cube moves as a string in Singmaster notation, or else in the
+/// notation of the given axis convention.
+fn string_of_moves (moves: &[Move], axes: &AxisConvention, isSingmaster: bool, axmax: Coord)
+-> String
+{
+    if isSingmaster
     {
-        let trail = trailQ.pop_front().unwrap();
-        let bricks = trail.transform(&srcCube.bricks, axmax);
+        return singmaster_string_of_movevec(moves, axmax);
+    }
 
-        // Does the trail's move sequence produce the target state?
-        if brickvec_eq(&bricks, &dstCube.bricks)
-        {
-            // Collect successful target match and don't continue the trail.
-            seqStrs.push(trail.as_string());
-        }
-        else
-        {
-            // Explore possible continuations of the trail's move sequence.
-            let movStack: &[Move] = &trail.steps;
-            let trailLen = movStack.len();
-            if trailLen < maxLen
-            {
-                let mut negdir: Axis  = '_';
-                let mut axval1: Coord = 0x0F;
-                let mut ident1: u16   = 0x00;
-                let mut ident2: u16   = 0x00;
-                if trailLen > 0
-                {
-                    if trailLen > 1
-                    {
-                        ident2 = movStack[1].ident;
-                    }
+    string_of_movevec(&axes.to_notation(moves, axmax))
 
-                    if trailLen > lastLen
-                    {
-                        dblMovs = Layers::new(cubeSize);
-                        lastLen = trailLen;
-                    }
+}   /* string_of_moves() */
 
-                    let move1 = &movStack[0];
-                    negdir = invert_axis(move1.axdir);
-                    axval1 = move1.axval;
-                    ident1 = move1.ident;
-                }
 
-                // Systematically explore layer movements.
-                for axdirRef in ['X', 'x', 'Y', 'y', 'Z', 'z'].iter()
-                {
-                    let axdir = *axdirRef;
+/// Returns a sequence of cube moves, as printed by string_of_movevec(),
+/// without the whole-cube turns that it starts or ends with.
+fn turns_trimmed_string (seqStr: &str, size: Coord)
+-> String
+{
+    match movevec_of_string(seqStr, size - 1)
+    {
+        Ok(moves)   =>  string_of_movevec(&movevec_turns_trimmed(&moves, size)),
+        Err(_)      =>  seqStr.to_string()
+    }
 
-                    for axval in 0 .. cubeSize
-                    {
-                        // Don't rotate a layer in the opposite direction of its previous move.
-                        if trailLen > 0
-                        && axval == axval1
-                        && axdir == negdir
-                        {
-                            continue;
-                        }
+}   /* turns_trimmed_string() */
 
-                        let ident = ident_of_move(axdir, axval);
 
-                        // Don't rotate a layer in the same direction thrice.
-                        if trailLen > 1
-                        && ident == ident1
-                        && ident == ident2
-                        {
-                            continue;
-                        }
+/// Reports the time spent in each program phase on stderr.
+fn print_timings (timings: &[(&str, Duration)])
+{
+    let mut total = Duration::new(0, 0);
+    for &(phase, elapsed) in timings.iter()
+    {
+        writeln!(io::stderr(), "{:>8}: {:>12.3} ms", phase, elapsed.as_secs_f64() * 1000.0);
+        total += elapsed;
+    }
+    writeln!(io::stderr(), "{:>8}: {:>12.3} ms", "total", total.as_secs_f64() * 1000.0);
 
-                        // Is the candidate move a duplicate of the most recent move in this trail?
-                        let isDbl = (trailLen > 0 && ident == ident1);
+}   /* print_timings() */
 
-                        // Don't do a double move if the opposite double has been done.
-                        if isDbl && dblMovs.has_flag(negdir, axval)
-                        {
-                            continue;
-                        }
 
-                        if trailLen >= axmax as usize
-                        {
-                            // Check if all layers rotate identically.  This would be equivalent
-                            // to a rotation of the cube as a whole.  Such a transformation is too
-                            // trivial to be used as a basis for meaningful alternative moves.
-                            let mut sameDir: bool = true;
-                            for ind in 0 .. axmax as usize
-                            {
-                                if movStack[ind].axdir != axdir
-                                {
-                                    sameDir = false;
-                                    break
-                                }
-                            }
-                            if sameDir
-                            {
-                                let mut usedVal: Vec<bool> = vec_of_size(cubeSize as usize, false);
-                                usedVal[axval as usize] = true;
-                                for ind in 0 .. axmax as usize
-                                {
-                                    usedVal[movStack[ind].axval as usize] = true
-                                }
-
-                                let mut usedAll = true;
-                                for ind in 0 .. cubeSize as usize
-                                {
-                                    if ! usedVal[ind]
-                                    {
-                                        usedAll = false;
-                                        break
-                                    }
-                                }
-                                if usedAll
-                                {
-                                    // Skip cube rotation.
-                                    continue
-                                }
-                            }
-                        }
+#[inline(never)]
+unsafe
+fn usage ()
+{
+    let msg =
+"Usage:  cubus [Options] N Moves
+        cubus script run File
+        cubus [--log=F] [--hooks=D] [--seed=S] [--case=C] train N [Length]
+        cubus [--heuristic=H] diff N HEX1 HEX2 [Length]
+        cubus [--size=N] [--since=D] [--until=D] stats [F]
+        cubus [--size=N] [--since=D] [--until=D] export [F]
+        cubus [--seed=S] randomwalk N Steps [Walks]
+        cubus assert N --moves=M --expect-facelets=F
+        cubus commutator N [A,B]
+        cubus [--max-time=S] effect Cycles [Length]
+        cubus [--seed=S] drill Case [Count]
+        cubus [--max-time=S] [--heuristic=H] depth N State [Length]
+        cubus trace-compare N MovesA MovesB
+        cubus [--seed=S] learn
+        cubus [--max-time=S] [--optimal | --beginner | --roux | --zz | --phases=F] solve State [Length]
+        cubus [--max-time=S] [--cast=F] [--fps=R] morph StateA StateB [Length]
+        cubus join N Moves1 Moves2 ...
+        cubus algs list [Set] | show Name | search Text
+        cubus [--mask=M] algs match State
+        cubus [--size=N] [--format=F] sheet File
 
-                        // Perform new exploratory move.
-                        let ntrail = trail.proceed(axdir, axval, ident);
+Depicts a Rubik's cube of edge length ‘N’, after applying the given
+Moves to an ordered state, as a character graphic in the terminal.
+The second form runs a Rhai script that experiments with cubes.
+The third form shows random scrambles of ‘Length’ moves, by default
+10 per layer beyond the first, times how long you take to solve each
+on a real cube, and appends scrambles and times to the log file ‘F’.
+The fourth form searches for a shortest sequence of at most ‘Length’
+moves, by default 6, that turns the packed state HEX1 into HEX2, or
+tells why no sequence can.
+The fifth form graphs the solve times and scramble lengths logged by
+‘train’ in the file ‘F’, by default that of the --profile, optionally
+only for cube size ‘N’ and for the dates ‘D’ (as YYYY-MM-DD) from
+--since up to and including --until.
+The sixth form prints the same solves in csTimer's JSON export format.
+The seventh form performs ‘Walks’ random walks of ‘Steps’ moves, by
+default 100, and tabulates the mean number of misplaced stickers after
+each step, which shows how many moves a scramble needs to mix the cube.
+The eighth form applies the moves ‘M’ to an ordered state and fails
+with exit status 1 unless the stickers then read ‘F’.  ‘F’ holds the
+6·N² color letters of the faces Up, Right, Front, Down, Left and Back,
+each row by row as seen from outside, with Back above Up, Front above
+Down and Up above the others.  Whitespace in ‘F’ is ignored.
+The ninth form depicts side by side what the moves ‘A’, the moves ‘B’
+and their commutator A B A⁻¹ B⁻¹ do to an ordered state, with the
+bricks that they leave in place drawn faint.
+The tenth form searches for a shortest sequence of at most ‘Length’
+face moves, by default 8, that moves the corners and edges of a 3x3x3
+cube round the ‘Cycles’ and leaves all others in place and unturned,
+e.g. ‘(UFR UBL DFR)’ for a 3-cycle of corners.  ‘Cycles’ are written
+as --cycles prints them, each optionally followed by + or - for the
+clockwise or counterclockwise twist of its corners or ' for the flip
+of its edges in all; how single pieces of a cycle face is left open.
+The eleventh form prints ‘Count’ setups, by default 12, of the 3x3x3
+case ‘Case’ of the bundled algorithms, e.g. ‘T-perm’: the inverse of
+its algorithm between random turns of the U layer.
+The twelfth form tells how many moves the ‘State’, a packed state as
+--packed prints it or else Moves, is from an ordered state, searching
+by IDA* up to ‘Length’ moves, by default 20.  If the search passes
+‘Length’ or the time limit first, it tells the least distance it could
+not rule out, and for Moves that their own number is the most.
+The thirteenth form plays ‘MovesA’ and ‘MovesB’ side by side from an
+ordered state and tabulates, move by move, how many stickers their
+states differ in.  It depicts both states after the first move where
+they differ, with the bricks as they were before it drawn faint, e.g.
+to find where an algorithm was copied wrong.
+The fourteenth form teaches the layer-by-layer method on a scrambled
+3x3x3 cube: it explains each step, turns the cube by the moves you type
+in Singmaster notation, tells what they reached or undid, and on ‘hint’
+highlights the pieces of the step.  ‘hint move’, ‘hint step’ and
+‘hint all’ reveal the next move, the rest of the step or the whole
+solution; the moves revealed stay on screen until you have made them,
+and moves off them let ‘hint’ start over from there.  Moves typed
+after ‘undo’ branch off as in a chess program's analysis board: ‘tree’
+lists the lines tried, ‘goto N’ returns to one, and ‘save F’ and
+‘load F’ keep the whole tree in the file ‘F’.
+The fifteenth form solves the 3x3x3 ‘State’, a packed state as --packed
+prints it, facelets as for --facelets, or else Moves, by Kociemba's
+two-phase algorithm.  It keeps searching for shorter solutions until
+one has at most ‘Length’ face turns, by default 22, which takes some
+milliseconds, or until the time limit, and prints the shortest found.
+With --optimal it searches for a shortest solution of at most ‘Length’
+face turns, by default 20, by Korf's IDA* with pattern databases.
+With --beginner it solves the way beginners learn, layer by layer, and
+prints the moves of each step with a line that explains it, and with
+--roux it solves by the Roux method's blocks, corners and last edges,
+and with --zz by the ZZ method, after telling which edges it orients.
+With --phases=F it solves by the phases of a method of your own that
+the JSON file ‘F’ defines, see --phases.
+The sixteenth form animates in the terminal, or with --cast records, a
+3x3x3 cube turning from ‘StateA’ into ‘StateB’, given as for ‘solve’,
+by one possible path of face turns that the two-phase algorithm finds
+within ‘Length’, by default 22, with rotations where centers differ.
+The seventeenth form joins the parts ‘Moves1’, ‘Moves2’ and so on of a
+solution, e.g. the phases of a multi-step method, cancelling the moves
+where each meets the next, and tells how many moves that saves.
+The eighteenth form lists the algorithms that cubus bundles, those of
+the ‘Set’ OLL, PLL, F2L or parity, shows the one named ‘Name’, e.g.
+‘T-perm’ or ‘OLL-27’, or lists those whose set, name, moves or short
+description contain ‘Text’.  ‘algs match’ lists the bundled algorithms
+that solve the stickers of the --mask on the 3x3x3 ‘State’, given as
+for ‘solve’, with the turns of the U layer they need before and after,
+shortest first.
+The nineteenth form prints a sheet of the cases that the algorithms in
+the JSON ‘File’ solve, by default for N = 3: each case's last layer as
+--top shows it, with only the stickers of the top color where that face
+is not yet all of it, and its algorithm beside.  ‘File’ holds an object
+of algorithms by name or an array of objects with a ‘name’ and an ‘alg’
+member, algorithms in Singmaster notation.
+
+0 < N < 11.  A negative ‘N’ additionally searches for all move
+sequences that are not longer than Moves and have the same result.
+
+Options:
+  --axes=A      Read and print moves in the axis convention ‘A’, which
+                names the cube's axes that the notation's X, Y and Z stand
+                for, each preceded by - if it points the other way, e.g.
+                X-ZY for a Z axis that points up.  The default XYZ has X
+                point right, Y up and Z to the front.
+  --beginner    With ‘solve’, solve layer by layer in named steps: the
+                cross, the first and second layers, and the last layer
+                by its four algorithms, each explained in a line.
+  --brackets    Print Moves, and found sequences, with the stretches that
+                are conjugates A B A⁻¹ or commutators A B A⁻¹ B⁻¹ written
+                as [A: B] or [A, B] instead of --group-by.
+  --case=C      Have ‘train’ scramble only the 3x3x3 case ‘C’, with
+                the setups of ‘drill’, for practice on that case.
+  --cast=F      Write an asciinema recording to the file F that plays
+                the moves, or those that ‘morph’ finds, one per second.
+  --columns=C   Print found sequences ‘C’ per line (default 4).
+  --cycles      Print the permutation that Moves make as cycles of
+                corners, edges and centers, with + or - for a clockwise or
+                counterclockwise corner twist and ' for an edge flip.
+  --databases=F With ‘solve --optimal’, keep the pattern databases, some
+                90 MB that take a few minutes to work out the first time,
+                in the file F instead of in $XDG_CACHE_HOME/cubus/korf.pdb
+                or ~/.cache/cubus/korf.pdb.  With the ‘mmap’ feature they
+                are mapped into memory rather than read.
+  --facelets=F  Apply Moves to the cube whose stickers read ‘F’, in the
+                facelet letters U, R, F, D, L, B of Kociemba-style solvers,
+                face by face in that order, instead of to an ordered state.
+  --find=C      Print where the brick with the sticker colors ‘C’ is after
+                Moves, e.g. WG for the white-green edge, and which way
+                each of its colors faces.
+  --format=F    Print the ‘sheet’ as plain text, ‘txt’ (the default), or
+                as an HTML page, ‘html’, with the cases in a grid.
+  --fps=R       With --cast or ‘morph’, play ‘R’ frames per second
+                (default 1, at most 60), which show each move turning.
+  --frames-dir=D
+                Write the cube's picture before and after each move to
+                the files D/frame-000.txt, D/frame-001.txt, and so on.
+  --group-by=G  Separate every ‘G’ moves of printed sequences by a space.
+  --heuristic=H With --optimal, estimate remaining moves by H, which is
+                ‘stickers’ (misplaced stickers, the default), ‘zero’, or
+                for N = 3 ‘databases’, the pattern databases of ‘solve
+                --optimal’, opened or worked out as for that.
+  --highlight=P Draw the pieces ‘P’ at full brightness wherever Moves
+                take them, and all others faint.  ‘P’ lists pieces by the
+                faces of their home places, e.g. UFR,UF.
+  --histogram   With a negative N, tabulate how many found sequences have
+                each length, and count the different moves they end with.
+  --hooks=D     With ‘train’, run the programs D/scramble as each
+                scramble is shown, D/solve as each solve is timed, and
+                D/best for a time below any logged for the size, if they
+                exist, by default those in the --profile's directory
+                hooks.  They find the size, scramble, packed state, time
+                and previous best in the environment variables
+                CUBUS_SIZE, CUBUS_SCRAMBLE, CUBUS_STATE, CUBUS_TIME and
+                CUBUS_PREVIOUS_BEST, and the event in CUBUS_EVENT.
+  --lock=L      Lock the layers that the moves ‘L’ turn, e.g. Y0X1,
+                in either direction.  Moves and searches may not turn
+                them, and the picture marks them with a padlock.
+  --mask=M      With ‘algs match’, the stickers to solve: pieces named
+                as for --highlight and the regions F2L, the layers below
+                the top, OLL, those and the top face, and LL, the whole
+                cube (the default), separated by commas.
+  --max-time=S  Stop searching after ‘S’ seconds and print what has been
+                found so far.
+  --metrics     Follow Moves, and found sequences, by their lengths in
+                the half-turn, quarter-turn, slice-turn and execution
+                metrics (HTM, QTM, STM, ETM) of speedcubers.  Moves about
+                one axis in a row count as the fewest block turns they
+                net.
+  --niss        Read the moves in parentheses of Moves as moves on the
+                inverse, as in fewest-moves solving, and print the final
+                sequence, e.g. R U D' F' for R U (F D).
+  --optimal     With a negative ‘N’, search for one shortest sequence
+                only, by IDA*, which needs little memory.  With ‘solve’,
+                search for a shortest solution, which for most cubes
+                takes minutes or more.
+  --pattern=P   Apply Moves to the pattern ‘P’ instead of to an ordered
+                state: ‘checkerboard’, or for N = 3 also ‘superflip’ or
+                ‘cube-in-cube’.
+  --profile=P   Keep the settings and solves of ‘P’ apart from those of
+                others who share the machine.  The options in the file
+                $XDG_DATA_HOME/cubus/profiles/P/options, or else in
+                ~/.local/share/cubus/profiles/P/options, apply before
+                those given, and ‘train’ logs to solves.log there, which
+                ‘stats’ and ‘export’ read if ‘F’ is left out.
+  --prefix=P    With a negative ‘N’, only search for sequences that begin
+                with the moves P, continuing from the state they reach.
+  --packed      Print the resulting state in its packed hexadecimal
+                encoding.
+  --pdf=F       Write a printable practice sheet to the file F (with the
+                ‘pdf’ feature): Moves as the scramble, the cube it leaves,
+                and the --solution split into the phases of a layer-by-
+                layer solve on a 3x3x3 cube, each beside the cube after
+                it, or ruled lines to write a solution on.
+  --phases=F    With ‘solve’, solve by the phases in the JSON file F,
+                an array of objects with a ‘name’, a ‘goal’ as for
+                --mask, the ‘moves’ it may make in Singmaster notation,
+                each also made the other way, and optionally a ‘note’
+                and the ‘depth’ it searches to, by default 6.
+  --qr          Print Moves, or with --packed the packed state, as a QR
+                code in block characters, e.g. to carry a scramble over
+                to a phone.  The light modules are drawn, for a terminal
+                with a dark background.
+  --qr-light    With --qr, draw the dark modules instead, for a terminal
+                with a light background.
+  --qr-png=F    Write the QR code of --qr to the file F as a PNG image.
+  --progress    For N = 3, report which layer-by-layer milestones the
+                cube has reached: crosses, F2L slots, OLL, solved.  With
+                the first two layers complete and the last on top, also
+                name the bundled OLL or PLL algorithm that comes next.
+  --repeats     Print Moves, and found sequences, with stretches that
+                repeat a block of moves written as its power, e.g.
+                (X0Y1)^6, or (R U)6 with --singmaster.
+  --roux        With ‘solve’, solve by the Roux method in named steps:
+                two 1x2x3 blocks on the left and right, the top corners
+                by two algorithms, and the last six edges by M and U
+                turns, each explained in a line.
+  --scheme=S    Paint the faces in the color scheme ‘western’ (the
+                default), ‘japanese’, or six color letters for Up,
+                Right, Front, Down, Left and Back, e.g. WRGYOB.
+  --seed=S      Draw the random moves of ‘train’ and ‘randomwalk’ from
+                the seed ‘S’, so that equal seeds repeat them.
+  --singmaster  Read and print moves in the Singmaster notation of
+                speedcubers, e.g. R U2 F', instead of axes and layers:
+                R, L, U, D, F, B turn a face clockwise, 2R and the like
+                the layer that far in, Rw or r the two outer layers, 3Rw
+                and the like as many, 2-3Rw and the like the layers
+                between, M, E, S the middle layer of an odd cube like
+                L, D, F, and x, y, z the whole cube like R, U, F;
+                ' turns the other way and 2 twice.  --axes does not apply.
+  --sep=S       Separate sequences on a line by ‘S’ (default a tab).
+  --simplify    Merge consecutive turns of the same layer in Moves.
+  --state=HEX   Apply Moves to the packed state HEX, as printed by
+                --packed, instead of to an ordered state.
+  --top         Also print the top layer as seen from above, with the
+                side stickers of its bricks around it.
+  --trim        With --optimal or diff, drop whole-cube turns from the
+                start and end of the shortest sequence, rewriting the moves
+                after a leading turn to play from the original orientation.
+                The cube then ends up as before, but may face elsewhere.
+                (The exhaustive search never turns the whole cube.)
+  --turn-counts Print how often Moves turn each layer either way.
+  --zz          With ‘solve’, tell which edges are misoriented, then
+                solve by the ZZ method in named steps: the EOLine that
+                orients them and places DF and DB, the blocks on the left
+                and right without rotations, and the last layer by its
+                algorithms, each explained in a line.
+  --solution=S  With --pdf, the moves ‘S’ that solve the scrambled cube.
+  --style=S     Render colors as ‘truecolor’, ‘256’, ‘16’ or ‘ascii’
+                letters, instead of what COLORTERM and TERM suggest.
+  --budget=B    Draw the cube, also in ‘train’, in at most ‘B’ bytes of
+                terminal output where it can, which keeps huge cubes
+                quick to redraw: as the net of its faces at one character
+                cell per sticker if the full picture takes more, and as
+                counts of each face's colors if that still does.  A
+                10x10x10 cube takes some 75000 bytes in full and 22000 as
+                a net.
+  --timings     Report the time spent parsing, moving, rendering and
+                searching on stderr.
 
-                        // Attempt to continue this move sequence.
-                        trailQ.push_back(ntrail);
+‘Moves’ is a sequence of character pairs «axis»«coord» where «axis»
+is one of X, Y, Z, x, y, z, denoting the rotation axis and direction.
+Uppercase means +90° (counter-clockwise) and lowercase means -90°
+(clockwise) rotation of a brick layer around the named «axis», where
+the axis transfixes the center of the cube.  The rotated bricks are
+addressed by «coord», which is a single decimal digit in the range
+0 ≤ «coord» < N.  A move rotates all bricks whose coordinate value
+along «axis» is «coord» in the direction that is indicated by the
+uppercase/lowercase feature of «axis».  A «coord» value of 0 denotes
+the leftmost / bottommost / hindmost cube layer.  A «coord» of *
+rotates the whole cube, e.g. Y*, which counts as a single move.
 
-                        if isDbl
-                        {
-                            // Register any double moves.
-                            dblMovs.set_flag(axdir, axval);
-                        }
+A decimal digit before a move repeats it as often.  Moves may be
+grouped in parentheses.  A group followed by ^«n» is
+repeated «n» times, e.g. (X0 Y1)^6.  [A, B] stands for the commutator
+A B A' B' and [A: B] for the conjugate A B A', where A' undoes A;
+they nest, e.g. [X0: [Y2, Z0]].\n";
 
-                        // Count the exploratory moves actually performed.
-                        moveNum += 1;
-                    }
-                }
-            }
-        }
-    }
+    write!(io::stderr(), "{}", msg);
+    process::exit(1);
+
+    // NoReturn //
+
+}   /* usage() */
 
-    (seqStrs, moveNum)
 
-}   /* find_moves() */
+/// Returns the directory of the given profile, in the user's data
+/// directory, or shows the usage for a name that is no plain word.
+fn profile_dir (name: &str)
+-> String
+{
+    if name.is_empty() || !name.chars().all(|chr| chr.is_ascii_alphanumeric() || chr == '-' || chr == '_')
+    {
+        unsafe { usage(); }
+    }
 
+    match (env::var("XDG_DATA_HOME"), env::var("HOME"))
+    {
+        (Ok(dir), _)    =>  format!("{}/cubus/profiles/{}", dir, name),
+        (_, Ok(home))   =>  format!("{}/.local/share/cubus/profiles/{}", home, name),
+        _               =>  format!("cubus-profiles/{}", name)
+    }
 
-/*  ––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––––  *
- *
- *      Output Functions
- */
+}   /* profile_dir() */
 
 
-/// Returns a stream that writes output to the terminal.
-fn tty_out ()
--> File
+/// Returns the options that the file `options` in a profile directory
+/// holds, separated by whitespace, with # starting a comment to the end
+/// of its line; none if there is no such file.
+fn profile_options (dir: &str)
+-> Vec<String>
 {
-    match OpenOptions::new().create(true).write(true).open("/dev/tty")
+    let path = format!("{}/options", dir);
+    let text = match fs::read_to_string(&path)
     {
-        Ok(stream)  =>  stream,
-        Err(error)  =>  panic!("{}", error)
+        Ok(text)    =>  text,
+        Err(error) if error.kind() == io::ErrorKind::NotFound
+                    =>  String::new(),
+        Err(error)  =>  exit_on_error_at(Err(error), &path)
+    };
+
+    let options: Vec<String> = text.lines().flat_map(|line| line.split('#').next().unwrap().split_whitespace())
+                                   .map(str::to_string).collect();
+    if let Some(stray) = options.iter().find(|opt| !opt.starts_with("--") || opt.starts_with("--profile="))
+    {
+        writeln!(io::stderr(), "cubus: {}: {} is no option that a profile can set", path, stray);
+        process::exit(1);
     }
 
-}   /* tty_out() */
+    options
 
+}   /* profile_options() */
 
-/// Saves the VT100 cursor position.
-fn tty_save ()
+
+/**
+ *  Global entry point
+ */
+fn main ()
 {
-    write!(tty_out(), "\x1B7");
+    // Options are recognized by their leading double dash.
+    let (optArgs, posArgs): (Vec<String>, Vec<String>) =
+        env::args().skip(1).partition(|arg| arg.starts_with("--"));
 
-}   /* tty_save() */
+    // A profile's options come first, so that those given here override them.
+    let profileDir = optArgs.iter().rev().find_map(|opt| opt.strip_prefix("--profile=")).map(profile_dir);
+    let optArgs = match &profileDir
+    {
+        Some(dir)   =>  [profile_options(dir), optArgs].concat(),
+        None        =>  optArgs
+    };
+    let profileLog = profileDir.as_ref().map(|dir| format!("{}/solves.log", dir));
+
+    let mut doTimings = false;
+    let mut doHistogram = false;
+    let mut doPacked = false;
+    let mut doNiss = false;
+    let mut doQr = false;
+    let mut doQrLight = false;
+    let mut doCycles = false;
+    let mut doOptimal = false;
+    let mut doBeginner = false;
+    let mut doRoux = false;
+    let mut doZz = false;
+    let mut doProgress = false;
+    let mut doSimplify = false;
+    let mut doTopView = false;
+    let mut doTrim = false;
+    let mut doTurnCounts = false;
+    let mut seqFormat = SeqFormat { columns: 4, groupBy: 0, sep: "\t".to_string(), brackets: false, repeats: false,
+                                    metrics: false, singmaster: false };
+    let mut heuristic: &dyn Heuristic = &MisplacedStickers;
+    let mut doDatabaseHeuristic = false;
+    let mut argState: Option<&str> = None;
+    let mut argFaceletState: Option<&str> = None;
+    let mut argPrefix: Option<&str> = None;
+    let mut argFramesDir: Option<&str> = None;
+    let mut argCast: Option<&str> = None;
+    let mut argPdf: Option<&str> = None;
+    let mut argQrPng: Option<&str> = None;
+    let mut argSolution: Option<&str> = None;
+    let mut argFps: u32 = 1;
+    let mut argLog: Option<&str> = None;
+    let mut argDatabases: Option<&str> = None;
+    let mut argHooks: Option<&str> = None;
+    let mut argSeed: Option<u64> = None;
+    let mut argSize: Option<Coord> = None;
+    let mut argSince: u64 = 0;
+    let mut argUntil: u64 = u64::MAX;
+    let mut argMaxTime: Option<Duration> = None;
+    let mut argBudget: usize = usize::MAX;
+    let mut argLock: Option<&str> = None;
+    let mut argFind: Option<Vec<Huename>> = None;
+    let mut argHighlight: Option<&str> = None;
+    let mut argPattern: Option<&str> = None;
+    let mut argCase: Option<&str> = None;
+    let mut argMoves: Option<&str> = None;
+    let mut argFacelets: Option<&str> = None;
+    let mut argFormat: &str = "txt";
+    let mut argMask: &str = "LL";
+    let mut argPhases: Option<&str> = None;
+    let mut style = RenderStyle::detect();
+    let mut scheme = ColorScheme::western();
+    let mut axes = AxisConvention::native();
+    for opt in optArgs.iter()
+    {
+        match opt.as_str()
+        {
+            "--timings" =>  doTimings = true,
+            "--histogram"
+                        =>  doHistogram = true,
+            "--packed"  =>  doPacked = true,
+            "--niss"    =>  doNiss = true,
+            "--qr"      =>  doQr = true,
+            "--qr-light"
+                        =>  doQrLight = true,
+            "--cycles"  =>  doCycles = true,
+            "--optimal" =>  doOptimal = true,
+            "--beginner"
+                        =>  doBeginner = true,
+            "--roux"    =>  doRoux = true,
+            "--zz"      =>  doZz = true,
+            "--brackets"
+                        =>  seqFormat.brackets = true,
+            "--repeats" =>  seqFormat.repeats = true,
+            "--metrics" =>  seqFormat.metrics = true,
+            "--singmaster"
+                        =>  seqFormat.singmaster = true,
+            "--simplify"
+                        =>  doSimplify = true,
+            "--progress"
+                        =>  doProgress = true,
+            "--top"     =>  doTopView = true,
+            "--trim"    =>  doTrim = true,
+            "--turn-counts"
+                        =>  doTurnCounts = true,
+            "--heuristic=zero"
+                        =>  heuristic = &ZeroHeuristic,
+            "--heuristic=stickers"
+                        =>  heuristic = &MisplacedStickers,
+            "--heuristic=databases"
+                        =>  doDatabaseHeuristic = true,
+            _ if opt.starts_with("--state=")
+                        =>  argState = Some(&opt["--state=".len() ..]),
+            _ if opt.starts_with("--facelets=")
+                        =>  argFaceletState = Some(&opt["--facelets=".len() ..]),
+            _ if opt.starts_with("--prefix=")
+                        =>  argPrefix = Some(&opt["--prefix=".len() ..]),
+            _ if opt.starts_with("--frames-dir=")
+                        =>  argFramesDir = Some(&opt["--frames-dir=".len() ..]),
+            _ if opt.starts_with("--columns=")
+                        =>  match opt["--columns=".len() ..].parse::<usize>()
+                            {
+                                Ok(value) if value > 0
+                                        =>  seqFormat.columns = value,
+                                _       =>  unsafe { usage(); }
+                            },
+            _ if opt.starts_with("--group-by=")
+                        =>  match opt["--group-by=".len() ..].parse::<usize>()
+                            {
+                                Ok(value)   =>  seqFormat.groupBy = value,
+                                Err(_)      =>  unsafe { usage(); }
+                            },
+            _ if opt.starts_with("--sep=")
+                        =>  seqFormat.sep = opt["--sep=".len() ..].to_string(),
+            _ if opt.starts_with("--log=")
+                        =>  argLog = Some(&opt["--log=".len() ..]),
+            _ if opt.starts_with("--profile=")
+                        =>  (),
+            _ if opt.starts_with("--databases=")
+                        =>  argDatabases = Some(&opt["--databases=".len() ..]),
+            _ if opt.starts_with("--hooks=")
+                        =>  argHooks = Some(&opt["--hooks=".len() ..]),
+            _ if opt.starts_with("--seed=")
+                        =>  match opt["--seed=".len() ..].parse::<u64>()
+                            {
+                                Ok(value)   =>  argSeed = Some(value),
+                                Err(_)      =>  unsafe { usage(); }
+                            },
+            _ if opt.starts_with("--size=")
+                        =>  match opt["--size=".len() ..].parse::<Coord>()
+                            {
+                                Ok(value) if 0 < value && value < 11
+                                        =>  argSize = Some(value),
+                                _       =>  unsafe { usage(); }
+                            },
+            _ if opt.starts_with("--since=")
+                        =>  match unix_time_of_date(&opt["--since=".len() ..])
+                            {
+                                Some(time)  =>  argSince = time,
+                                None        =>  unsafe { usage(); }
+                            },
+            _ if opt.starts_with("--until=")
+                        =>  match unix_time_of_date(&opt["--until=".len() ..])
+                            {
+                                Some(time)  =>  argUntil = time + 86400,
+                                None        =>  unsafe { usage(); }
+                            },
+            _ if opt.starts_with("--max-time=")
+                        =>  match opt["--max-time=".len() ..].parse::<f64>()
+                            {
+                                Ok(value) if value >= 0.0 && value.is_finite()
+                                        =>  argMaxTime = Some(Duration::from_secs_f64(value)),
+                                _       =>  unsafe { usage(); }
+                            },
+            _ if opt.starts_with("--budget=")
+                        =>  match opt["--budget=".len() ..].parse::<usize>()
+                            {
+                                Ok(value)   =>  argBudget = value,
+                                Err(_)      =>  unsafe { usage(); }
+                            },
+            _ if opt.starts_with("--style=")
+                        =>  match RenderStyle::of_name(&opt["--style=".len() ..])
+                            {
+                                Some(value) =>  style = value,
+                                None        =>  unsafe { usage(); }
+                            },
+            _ if opt.starts_with("--axes=")
+                        =>  match AxisConvention::of_name(&opt["--axes=".len() ..])
+                            {
+                                Some(value) =>  axes = value,
+                                None        =>  unsafe { usage(); }
+                            },
+            _ if opt.starts_with("--scheme=")
+                        =>  match ColorScheme::of_name(&opt["--scheme=".len() ..])
+                            {
+                                Some(value) =>  scheme = value,
+                                None        =>  unsafe { usage(); }
+                            },
+            _ if opt.starts_with("--find=")
+                        =>  match opt["--find=".len() ..].chars().map(Huename::of_letter).collect::<Option<Vec<Huename>>>()
+                            {
+                                Some(value) if !value.is_empty()
+                                            =>  argFind = Some(value),
+                                _           =>  unsafe { usage(); }
+                            },
+            _ if opt.starts_with("--pattern=")
+                        =>  argPattern = Some(&opt["--pattern=".len() ..]),
+            _ if opt.starts_with("--case=")
+                        =>  argCase = Some(&opt["--case=".len() ..]),
+            _ if opt.starts_with("--highlight=")
+                        =>  argHighlight = Some(&opt["--highlight=".len() ..]),
+            _ if opt.starts_with("--lock=")
+                        =>  argLock = Some(&opt["--lock=".len() ..]),
+            _ if opt.starts_with("--cast=")
+                        =>  argCast = Some(&opt["--cast=".len() ..]),
+            _ if opt.starts_with("--pdf=")
+                        =>  argPdf = Some(&opt["--pdf=".len() ..]),
+            _ if opt.starts_with("--qr-png=")
+                        =>  argQrPng = Some(&opt["--qr-png=".len() ..]),
+            _ if opt.starts_with("--solution=")
+                        =>  argSolution = Some(&opt["--solution=".len() ..]),
+            _ if opt.starts_with("--fps=")
+                        =>  match opt["--fps=".len() ..].parse::<u32>()
+                            {
+                                Ok(value) if 0 < value && value <= 60
+                                        =>  argFps = value,
+                                _       =>  unsafe { usage(); }
+                            },
+            _ if opt.starts_with("--moves=")
+                        =>  argMoves = Some(&opt["--moves=".len() ..]),
+            _ if opt.starts_with("--expect-facelets=")
+                        =>  argFacelets = Some(&opt["--expect-facelets=".len() ..]),
+            _ if opt.starts_with("--mask=")
+                        =>  argMask = &opt["--mask=".len() ..],
+            _ if opt.starts_with("--phases=")
+                        =>  argPhases = Some(&opt["--phases=".len() ..]),
+            _ if opt.starts_with("--format=")
+                        =>  match &opt["--format=".len() ..]
+                            {
+                                value @ ("txt" | "html")
+                                        =>  argFormat = value,
+                                _       =>  unsafe { usage(); }
+                            },
+            _           =>  unsafe { usage(); }
+        }
+    }
 
+    if posArgs.is_empty()
+    {
+        unsafe { usage(); }
+    }
 
-/// Restores the VT100 cursor position.
-fn tty_load ()
-{
-    write!(tty_out(), "\x1B8");
+    let mut rng = argSeed.map_or_else(Rng::from_clock, Rng::new);
 
-}   /* tty_load() */
+    // Singmaster notation names the faces, whichever way the axes lie.
+    if seqFormat.singmaster
+    {
+        axes = AxisConvention::native();
+    }
 
+    let databaseHeuristic = doDatabaseHeuristic.then(|| open_databases(argDatabases));
+    if let Some(databases) = &databaseHeuristic
+    {
+        heuristic = databases;
+    }
 
-/// Writes output to the terminal at the given position.
-fn tty_put_at (row: i16, col: i16, text: &str)
-{
-    write!(tty_out(), "\x1B[{};{}f{}", row, col, text);
+    if posArgs[0] == "script"
+    {
+        if posArgs.len() != 3 || posArgs[1] != "run"
+        {
+            unsafe { usage(); }
+        }
 
-}   /* tty_put_at() */
+        run_script(&posArgs[2]);
+        return;
+    }
 
+    if posArgs[0] == "train"
+    {
+        let size = match posArgs.get(1).map(|arg| arg.parse::<u8>())
+        {
+            Some(Ok(value)) if 0 < value && value < 11
+                        =>  value,
+            _           =>  unsafe { usage(); 0 }
+        };
+        let length = match posArgs.get(2).map(|arg| arg.parse::<usize>())
+        {
+            Some(Ok(value)) =>  value,
+            Some(Err(_))    =>  unsafe { usage(); 0 },
+            None            =>  (10 * (size as usize - 1)).max(5)
+        };
 
-/// Draws a single cube brick to the terminal as a character graphic.
-fn draw_brick (brick: &Brick, axmax: Coord, row: i16, col: i16)
-{
-    // The Unicode “FULL BLOCK” character as a string.
-    static FULL1: &'static str = "█";
-    static FULL2: &'static str = "██";
-    static FULL3: &'static str = "███";
-    static FULL9: &'static str = "█████████";
+        if argCase.is_some() && size != 3
+        {
+            unsafe { usage(); }
+        }
 
-    fn put (tty: &mut File, row: i16, col: i16, attr: &str, text: &str)
+        if let (None, Some(dir)) = (argLog, &profileDir)
+        {
+            exit_on_error_at(fs::create_dir_all(dir), dir);
+        }
+        let profileHooks = profileDir.as_ref().map(|dir| format!("{}/hooks", dir));
+        run_training(size, length, argCase, argLog.or(profileLog.as_deref()), argHooks.or(profileHooks.as_deref()),
+                     style, argBudget, &scheme, &axes, seqFormat.singmaster, &mut rng);
+        return;
+    }
+
+    if posArgs[0] == "learn"
     {
-        write!(tty, "\x1B7\x1B[{};{}f{}{}\x1B8", row, col, attr, text);
+        if posArgs.len() != 1
+        {
+            unsafe { usage(); }
+        }
+        run_learning(style, &scheme, &mut rng);
+        return;
     }
 
-    let axmax = axmax  as i16;
+    if posArgs[0] == "drill"
+    {
+        if posArgs.len() < 2 || 3 < posArgs.len()
+        {
+            unsafe { usage(); }
+        }
+        let count = match posArgs.get(2).map(|arg| arg.parse::<usize>())
+        {
+            Some(Ok(value)) =>  value,
+            Some(Err(_))    =>  unsafe { usage(); 0 },
+            None            =>  12
+        };
 
-    let brickLoc = &brick.curLoc;
-    let brickHue = &brick.curHue;
+        for _ in 0 .. count
+        {
+            let setup = exit_on_error(drill_movevec(&posArgs[1], &mut rng));
+            println!("{}", string_of_moves(&setup, &axes, seqFormat.singmaster, 2));
+        }
+        return;
+    }
 
-    let posX = brickLoc.x as i16;
-    let posY = brickLoc.y as i16;
-    let posZ = brickLoc.z as i16;
+    if posArgs[0] == "sheet"
+    {
+        if posArgs.len() != 2
+        {
+            unsafe { usage(); }
+        }
 
-    let bRow = -4 * posY +  2 * posZ + 4 * axmax + row + 1;
-    let bCol =  9 * posX + -3 * posZ + 3 * axmax + col + 1;
+        let path = &posArgs[1];
+        let collection = exit_on_error_at(alg_collection_of_json(&exit_on_error_at(fs::read_to_string(path), path)), path);
+        let size = argSize.unwrap_or(3);
+        for (name, algorithm) in collection.iter()
+        {
+            exit_on_error_at(movevec_of_singmaster(algorithm, size - 1), &format!("{}: {}", path, name));
+        }
+        let sheet = match argFormat
+        {
+            "html"  =>  alg_sheet_html(&collection, size, &scheme),
+            _       =>  alg_sheet_text(&collection, size, &scheme)
+        };
+        print!("{}", exit_on_error_at(sheet, path));
+        return;
+    }
 
-    let tty = &mut tty_out();
+    if posArgs[0] == "randomwalk"
+    {
+        let size = match posArgs.get(1).map(|arg| arg.parse::<u8>())
+        {
+            Some(Ok(value)) if 0 < value && value < 11
+                        =>  value,
+            _           =>  unsafe { usage(); 0 }
+        };
+        let counts: Vec<usize> = posArgs[2 ..].iter().map(|arg| match arg.parse::<usize>()
+        {
+            Ok(value) if value > 0
+                    =>  value,
+            _       =>  unsafe { usage(); 0 }
+        }).collect();
+        if counts.is_empty() || 2 < counts.len()
+        {
+            unsafe { usage(); }
+        }
+
+        run_random_walk(size, counts[0], counts.get(1).cloned().unwrap_or(100), &mut rng);
+        return;
+    }
 
-    if posZ == axmax
+    // The log of `stats` and `export`, by default the profile's.
+    let logOf = |posArgs: &[String]| match (posArgs.len(), &profileLog)
     {
-        let attr = brickHue.zpos.vt100_attrs();
-        put(tty, bRow + 2, bCol +  0, attr, FULL9);
-        put(tty, bRow + 3, bCol +  0, attr, FULL9);
-        put(tty, bRow + 4, bCol +  0, attr, FULL9);
-        put(tty, bRow + 5, bCol +  0, attr, FULL9);
+        (2, _)          =>  posArgs[1].clone(),
+        (1, Some(path)) =>  path.clone(),
+        _               =>  unsafe { usage(); String::new() }
+    };
+
+    if posArgs[0] == "stats"
+    {
+        run_stats(&read_solve_log_filtered(&logOf(&posArgs), argSize, argSince, argUntil));
+        return;
     }
 
-    if posY == axmax
+    if posArgs[0] == "export"
     {
-        let attr = brickHue.ypos.vt100_attrs();
-        put(tty, bRow + 0, bCol +  2, attr, FULL9);
-        put(tty, bRow + 1, bCol +  1, attr, FULL9);
+        let records = read_solve_log_filtered(&logOf(&posArgs), argSize, argSince, argUntil);
+        exit_on_error(write_cstimer(&mut io::stdout(), &records).map_err(CubusError::from));
+        return;
     }
 
-    if posX == axmax
+    if posArgs[0] == "commutator"
     {
-        let attr = brickHue.xpos.vt100_attrs();
-        put(tty, bRow + 0, bCol + 11, attr, FULL1);
-        put(tty, bRow + 1, bCol + 10, attr, FULL2);
-        put(tty, bRow + 2, bCol +  9, attr, FULL3);
-        put(tty, bRow + 3, bCol +  9, attr, FULL3);
-        put(tty, bRow + 4, bCol +  9, attr, FULL2);
-        put(tty, bRow + 5, bCol +  9, attr, FULL1);
+        let size = match posArgs.get(1).map(|arg| arg.parse::<u8>())
+        {
+            Some(Ok(value)) if 0 < value && value < 11 && posArgs.len() == 3
+                        =>  value,
+            _           =>  unsafe { usage(); 0 }
+        };
+        let (lhsStr, rhsStr) = match posArgs[2].trim().strip_prefix('[')
+                                                     .and_then(|arg| arg.strip_suffix(']'))
+                                                     .and_then(|arg| arg.split_once(','))
+        {
+            Some(parts) =>  parts,
+            None        =>  unsafe { usage(); ("", "") }
+        };
+
+        let lhs = exit_on_error(moves_of_string(lhsStr, &axes, seqFormat.singmaster, size - 1));
+        let rhs = exit_on_error(moves_of_string(rhsStr, &axes, seqFormat.singmaster, size - 1));
+        let start = Cube::new(size);
+        let cubes = [
+            (format!("A = {}", string_of_moves(&lhs, &axes, seqFormat.singmaster, size - 1)), exit_on_error(start.copy_with_moves(&lhs))),
+            (format!("B = {}", string_of_moves(&rhs, &axes, seqFormat.singmaster, size - 1)), exit_on_error(start.copy_with_moves(&rhs))),
+            ("[A, B]".to_string(), exit_on_error(start.copy_with_moves(&movevec_commutator(&lhs, &rhs))))
+        ];
+        let cubes: Vec<(&str, Cube)> = cubes.iter().map(|(title, cube)| (title.as_str(), scheme.recolored(cube))).collect();
+        let titled: Vec<(&str, &Cube)> = cubes.iter().map(|(title, cube)| (*title, cube)).collect();
+        exit_on_error(draw_cube_row(&titled, &scheme.recolored(&start), 1, 2, style));
+        return;
     }
 
-}   /* draw_brick() */
+    if posArgs[0] == "join"
+    {
+        let size = match posArgs.get(1).map(|arg| arg.parse::<u8>())
+        {
+            Some(Ok(value)) if 0 < value && value < 11 && posArgs.len() >= 4
+                        =>  value,
+            _           =>  unsafe { usage(); 0 }
+        };
+        let parts: Vec<Vec<Move>> = posArgs[2 ..].iter()
+            .map(|arg| exit_on_error(moves_of_string(arg, &axes, seqFormat.singmaster, size - 1))).collect();
+        let (joined, savings) = movevec_joined(&parts);
 
+        println!("{}", string_of_moves(&joined, &axes, seqFormat.singmaster, size - 1));
+        for (seam, saved) in savings.iter().enumerate()
+        {
+            match saved
+            {
+                0   =>  println!("Parts {} and {}: nothing cancels", seam + 1, seam + 2),
+                _   =>  println!("Parts {} and {}: {} quarter turn{} cancel{}", seam + 1, seam + 2, saved,
+                                 if *saved != 1 {"s"} else {""}, if *saved != 1 {""} else {"s"})
+            }
+        }
+        let naive = parts.iter().map(|part| MoveMetrics::of_movevec(part, size).htm).sum::<usize>();
+        let htm = MoveMetrics::of_movevec(&joined, size).htm;
+        println!("Saved {} of {} moves (HTM), {} left", naive - htm, naive, htm);
+        return;
+    }
 
-fn draw_cube (cube: &Cube, row: i16, col: i16)
-{
-    let size    = cube.size;
-    let axmax = size - 1;
-//  let boxW    = (3 + 4) * size as i16;
-    let boxH    = (2 + 4) * size as i16;
+    if posArgs[0] == "assert"
+    {
+        let size = match posArgs.get(1).map(|arg| arg.parse::<u8>())
+        {
+            Some(Ok(value)) if 0 < value && value < 11 && posArgs.len() == 2
+                        =>  value,
+            _           =>  unsafe { usage(); 0 }
+        };
+        let (moveStr, expected) = match (argMoves, argFacelets)
+        {
+            (Some(moveStr), Some(facelets))
+                        =>  (moveStr, facelets.split_whitespace().collect::<String>()),
+            _           =>  unsafe { usage(); ("", String::new()) }
+        };
 
-    // «Clear Screen» «Reset Attributes»
-    tty_put_at(boxH + row + 2, 0, "\x1B[2J\x1B[0m");
+        let moves = exit_on_error(moves_of_string(moveStr, &axes, seqFormat.singmaster, size - 1));
+        let cube = scheme.recolored(&exit_on_error(Cube::new(size).copy_with_moves(&moves)));
+        let facelets = cube.facelets();
+        if facelets != expected
+        {
+            writeln!(io::stderr(), "cubus: {} gives the facelets\n  {}\ninstead of\n  {}\n\n{}",
+                     moveStr, facelets, expected, cube);
+            process::exit(1);
+        }
+        return;
+    }
 
-    tty_save();
-    for brick in cube.bricks.iter()
+    if posArgs[0] == "effect"
     {
-        if brick.curLoc.x == axmax
-        || brick.curLoc.y == axmax
-        || brick.curLoc.z == axmax
+        if posArgs.len() < 2 || 3 < posArgs.len()
+        {
+            unsafe { usage(); }
+        }
+        let length = match posArgs.get(2).map(|arg| arg.parse::<usize>())
+        {
+            Some(Ok(value)) =>  value,
+            Some(Err(_))    =>  unsafe { usage(); 0 },
+            None            =>  8
+        };
+        let cycles = exit_on_error(cyclevec_of_string(&posArgs[1]));
+
+        let cancel = argMaxTime.map(cancel_after);
+        let (found, moveNum) = exit_on_error(find_moves_with_cycles(length, &cycles, cancel.as_ref()));
+        report_cancelled(cancel.as_ref());
+        println!("{} sequence from {} exploratory move{}:",
+                 if found.is_some() {"Shortest"} else {"No"},
+                 moveNum, if moveNum != 1 {"s"} else {""});
+        if let Some(seqStr) = found
         {
-            draw_brick(brick, axmax, row, col);
+            seqFormat.print(&[notation_string(&seqStr, &axes, 2)], 2);
         }
+        return;
     }
-    tty_load();
 
-}   /* draw_cube() */
+    if posArgs[0] == "depth"
+    {
+        if posArgs.len() < 3 || 4 < posArgs.len()
+        {
+            unsafe { usage(); }
+        }
+        let size = match posArgs[1].parse::<u8>()
+        {
+            Ok(value) if 0 < value && value < 11
+                        =>  value,
+            _           =>  unsafe { usage(); 0 }
+        };
+        let length = match posArgs.get(3).map(|arg| arg.parse::<usize>())
+        {
+            Some(Ok(value)) =>  value,
+            Some(Err(_))    =>  unsafe { usage(); 0 },
+            None            =>  20
+        };
 
+        // A packed state, or else moves whose count bounds the distance.
+        let (cube, movesLen) = match bytes_of_hex(&posArgs[2]).and_then(|packed| Cube::from_packed(size, &packed))
+        {
+            Some(cube)  =>  (cube, None),
+            None        =>  {
+                let moves = movevec_simplified(&exit_on_error(moves_of_string(&posArgs[2], &axes, seqFormat.singmaster, size - 1)));
+                let movesLen = moves.iter().map(|mov| if mov.is_rotation() { size as usize } else { 1 }).sum::<usize>();
+                (exit_on_error(Cube::new(size).copy_with_moves(&moves)), Some(movesLen))
+            }
+        };
 
-#[inline(never)]
-unsafe
-fn usage ()
-{
-    let msg =
-"Usage:  cubus N Moves
+        let cancel = argMaxTime.map(cancel_after);
+        let (distance, isExact) = exit_on_error(solve_distance(&cube, length, heuristic, cancel.as_ref()));
+        report_cancelled(cancel.as_ref());
+        match (isExact || movesLen == Some(distance), movesLen)
+        {
+            (true, _)   =>  println!("Distance from ordered: {} move{}", distance, if distance != 1 {"s"} else {""}),
+            (false, Some(most)) if distance <= most
+                        =>  println!("Distance from ordered: at least {}, at most {} moves", distance, most),
+            (false, _)  =>  println!("Distance from ordered: at least {} moves", distance)
+        }
+        return;
+    }
 
-Depicts a Rubik's cube of edge length ‘N’, after applying the given
-Moves to an ordered state, as a character graphic in the terminal.
+    // A 3x3x3 state given as packed, or else as facelets, 54 of the letters
+    // U, R, F, D, L, B, or else as moves.
+    let cube_of_state = |arg: &str| {
+        let letters: Vec<char> = arg.chars().filter(|chr| !chr.is_whitespace()).collect();
+        match bytes_of_hex(arg).and_then(|packed| Cube::from_packed(3, &packed))
+        {
+            Some(cube)  =>  cube,
+            None if letters.len() == 54 && letters.iter().all(|&chr| "URFDLB".contains(chr))
+                        =>  exit_on_error(Cube::from_facelet_string(arg)),
+            None        =>  exit_on_error(Cube::new(3).copy_with_moves(&exit_on_error(moves_of_string(arg, &axes, seqFormat.singmaster, 2))))
+        }
+    };
 
-0 < N < 11.
+    if posArgs[0] == "algs"
+    {
+        let algorithms: Vec<&Algorithm> = match (posArgs.get(1).map(String::as_str), posArgs.len())
+        {
+            (Some("list"), 2)   =>  ALGORITHMS.iter().collect(),
+            (Some("list"), 3)   =>  ALGORITHMS.iter().filter(|algorithm| algorithm.set.eq_ignore_ascii_case(&posArgs[2])).collect(),
+            (Some("show"), 3)   =>  vec![exit_on_error(algorithm_named(&posArgs[2]))],
+            (Some("search"), 3) =>  algorithms_matching(&posArgs[2]),
+            (Some("match"), 3)  =>  {
+                let mask = exit_on_error(sticker_mask(argMask, 3));
+                for (algorithm, moves) in algorithms_solving(&cube_of_state(&posArgs[2]), &mask)
+                {
+                    println!("{:<8}{:<12}{}", algorithm.set, algorithm.name, singmaster_string_of_movevec(&moves, 2));
+                }
+                return;
+            },
+            _                   =>  unsafe { usage(); vec![] }
+        };
 
-‘Moves’ is a sequence of character pairs «axis»«coord» where «axis»
-is one of X, Y, Z, x, y, z, denoting the rotation axis and direction.
-Uppercase means +90° (counter-clockwise) and lowercase means -90°
-(clockwise) rotation of a brick layer around the named «axis», where
-the axis transfixes the center of the cube.  The rotated bricks are
-addressed by «coord», which is a single decimal digit in the range
-0 ≤ «coord» < N.  A move rotates all bricks whose coordinate value
-along «axis» is «coord» in the direction that is indicated by the
-uppercase/lowercase feature of «axis».  A «coord» value of 0 denotes
-the leftmost / bottommost / hindmost cube layer.\n";
+        for algorithm in algorithms.iter()
+        {
+            if posArgs[1] == "show"
+            {
+                println!("{} ({} for {}x{}x{}): {}", algorithm.name, algorithm.set,
+                         algorithm.size, algorithm.size, algorithm.size, algorithm.note);
+                println!("{}", algorithm.moves);
+            }
+            else
+            {
+                println!("{:<8}{:<12}{}", algorithm.set, algorithm.name, algorithm.moves);
+            }
+        }
+        return;
+    }
 
-    write!(io::stderr(), "{}", msg);
-    process::exit(1);
+    if posArgs[0] == "solve"
+    {
+        if posArgs.len() < 2 || 3 < posArgs.len()
+        {
+            unsafe { usage(); }
+        }
+        let length = match posArgs.get(2).map(|arg| arg.parse::<usize>())
+        {
+            Some(Ok(value)) =>  value,
+            Some(Err(_))    =>  unsafe { usage(); 0 },
+            None            =>  if doOptimal {20} else {22}
+        };
 
-    // NoReturn //
+        let cube = cube_of_state(&posArgs[1]);
+        let mut timings: Vec<(&str, Duration)> = vec![];
 
-}   /* usage() */
+        if doZz
+        {
+            let misoriented = exit_on_error(misoriented_edges(&cube));
+            match misoriented.len()
+            {
+                0   =>  println!("All edges oriented.\n"),
+                _   =>  println!("{} edges misoriented: {}\n", misoriented.len(), misoriented.join(" "))
+            }
+        }
 
+        if doBeginner || doRoux || doZz || argPhases.is_some()
+        {
+            // Roux counts slice turns as one move.
+            let count_of = |moves: &[Move]| {
+                let metrics = MoveMetrics::of_movevec(moves, 3);
+                if doRoux { metrics.stm } else { metrics.htm }
+            };
+            let started = Instant::now();
+            let (method, steps) = if let Some(path) = argPhases
+                                  {
+                                      let phases = exit_on_error_at(fs::read_to_string(path).map_err(CubusError::from)
+                                                                    .and_then(|text| phases_of_json(&text, 3)), path);
+                                      (path, solve_phases(&cube, &phases))
+                                  }
+                                  else if doRoux    { ("Roux", solve_roux(&cube)) }
+                                  else if doZz      { ("ZZ", solve_zz(&cube)) }
+                                  else              { ("Layer by layer", solve_layer_by_layer(&cube)) };
+            let steps = exit_on_error(steps);
+            timings.push(("search", started.elapsed()));
+
+            // Moves where one step meets the next may cancel.
+            let parts: Vec<Vec<Move>> = steps.iter().map(|step| step.moves.clone()).collect();
+            let (joined, _) = movevec_joined(&parts);
+            let total = count_of(&joined);
+            let cancelled = parts.iter().map(|moves| count_of(moves)).sum::<usize>() - total;
+            match cancelled
+            {
+                0   =>  println!("{} in {} moves:", method, total),
+                _   =>  println!("{} in {} moves, with {} cancelled where steps meet:", method, total, cancelled)
+            }
+            for step in steps
+            {
+                let count = count_of(&step.moves);
+                println!("\n{} ({} move{}): {}", step.name, count, if count != 1 {"s"} else {""},
+                         if step.moves.is_empty() {"done already".to_string()}
+                         else {string_of_moves(&step.moves, &axes, seqFormat.singmaster, 2)});
+                if !step.note.is_empty()
+                {
+                    println!("  {}", step.note);
+                }
+            }
+            if cancelled != 0
+            {
+                println!("\nJoined: {}", string_of_moves(&joined, &axes, seqFormat.singmaster, 2));
+            }
+            if doTimings
+            {
+                print_timings(&timings);
+            }
+            return;
+        }
 
-/**
- *  Global entry point
- */
-fn main ()
-{
-    let argc = env::args().count();
-    if argc < 2
+        let started = Instant::now();
+        let databases = doOptimal.then(|| open_databases(argDatabases));
+        if doOptimal
+        {
+            timings.push(("tables", started.elapsed()));
+        }
+
+        let started = Instant::now();
+        let cancel = argMaxTime.map(cancel_after);
+        let (found, moveNum) = match &databases
+        {
+            Some(databases) =>  exit_on_error(solve_optimal(&cube, databases, length, cancel.as_ref())),
+            None            =>  exit_on_error(solve_two_phase(&cube, length, cancel.as_ref()))
+        };
+        timings.push(("search", started.elapsed()));
+        report_cancelled(cancel.as_ref());
+        println!("{} solution from {} exploratory move{}:",
+                 match (&found, doOptimal)
+                 {
+                     (None, _)          =>  "No",
+                     (Some(_), true)    =>  "Optimal",
+                     (Some(_), false)   =>  "Two-phase"
+                 },
+                 moveNum, if moveNum != 1 {"s"} else {""});
+        if let Some(moves) = found
+        {
+            seqFormat.print(&[notation_string(&string_of_movevec(&moves), &axes, 2)], 2);
+        }
+        if doTimings
+        {
+            print_timings(&timings);
+        }
+        return;
+    }
+
+    if posArgs[0] == "morph"
     {
-        unsafe { usage(); }
+        if posArgs.len() < 3 || 4 < posArgs.len()
+        {
+            unsafe { usage(); }
+        }
+        let length = match posArgs.get(3).map(|arg| arg.parse::<usize>())
+        {
+            Some(Ok(value)) =>  value,
+            Some(Err(_))    =>  unsafe { usage(); 0 },
+            None            =>  22
+        };
+        let (from, to) = (cube_of_state(&posArgs[1]), cube_of_state(&posArgs[2]));
+
+        let cancel = argMaxTime.map(cancel_after);
+        let (found, _) = exit_on_error(solve_between(&from, &to, length, cancel.as_ref()));
+        report_cancelled(cancel.as_ref());
+        let Some(moves) = found
+        else
+        {
+            println!("No path found.");
+            process::exit(1);
+        };
+
+        let caption = "One possible path:";
+        match argCast
+        {
+            Some(path)  =>  exit_on_error_at(write_cast_captioned(path, &scheme.recolored(&from), &moves, caption, style, argFps), path),
+            None        =>  exit_on_error(play_moves(&scheme.recolored(&from), &moves, caption, style, argFps))
+        }
+        println!("{} {}", caption, string_of_moves(&moves, &axes, seqFormat.singmaster, 2));
+        return;
     }
 
-    let mut size = match env::args().nth(1).unwrap().parse::<i8>()
+    if posArgs[0] == "trace-compare"
     {
-        Ok(value) => value,
-        Err(_)    => 0
-    };
+        let size = match posArgs.get(1).map(|arg| arg.parse::<u8>())
+        {
+            Some(Ok(value)) if 0 < value && value < 11 && posArgs.len() == 4
+                        =>  value,
+            _           =>  unsafe { usage(); 0 }
+        };
+        let lhs = exit_on_error(moves_of_string(&posArgs[2], &axes, seqFormat.singmaster, size - 1));
+        let rhs = exit_on_error(moves_of_string(&posArgs[3], &axes, seqFormat.singmaster, size - 1));
+        let start = Cube::new(size);
+        let distances = exit_on_error(trace_distances(&start, &lhs, &rhs));
+
+        // The states after the first move that sets them apart, and before.
+        let diverged = distances.iter().position(|&distance| distance != 0);
+        if let Some(step) = diverged
+        {
+            let before = exit_on_error(start.copy_with_moves(&lhs[.. (step - 1).min(lhs.len())]));
+            let states = [(&lhs, "A"), (&rhs, "B")].map(|(moves, name)| {
+                let moves = &moves[.. step.min(moves.len())];
+                (format!("{} after {}", name, moves.len()), scheme.recolored(&exit_on_error(start.copy_with_moves(moves))))
+            });
+            let titled: Vec<(&str, &Cube)> = states.iter().map(|(title, cube)| (title.as_str(), cube)).collect();
+            exit_on_error(draw_cube_row(&titled, &scheme.recolored(&before), 1, 2, style));
+        }
+
+        let moveAt = |moves: &[Move], step: usize| {
+            moves.get(step.wrapping_sub(1)).map_or(String::new(), |mov| string_of_moves(&[*mov], &axes, seqFormat.singmaster, size - 1))
+        };
+        println!("Step  A      B      Stickers apart");
+        for (step, distance) in distances.iter().enumerate()
+        {
+            println!("{:>4}  {:<5}  {:<5}  {:>5}{}", step, moveAt(&lhs, step), moveAt(&rhs, step), distance,
+                     if diverged == Some(step) {"  first difference"} else {""});
+        }
+        if diverged.is_none()
+        {
+            println!("The sequences pass through the same states.");
+        }
+        return;
+    }
+
+    if posArgs[0] == "diff"
+    {
+        if posArgs.len() < 4 || 5 < posArgs.len()
+        {
+            unsafe { usage(); }
+        }
+        let size = match posArgs[1].parse::<u8>()
+        {
+            Ok(value) if 0 < value && value < 11
+                        =>  value,
+            _           =>  unsafe { usage(); 0 }
+        };
+        let length = match posArgs.get(4).map(|arg| arg.parse::<usize>())
+        {
+            Some(Ok(value)) =>  value,
+            Some(Err(_))    =>  unsafe { usage(); 0 },
+            None            =>  6
+        };
+        let cubes: Vec<Cube> = posArgs[2 ..= 3].iter().map(|hex|
+            exit_on_error(bytes_of_hex(hex).and_then(|packed| Cube::from_packed(size, &packed))
+                                           .ok_or_else(|| CubusError::InvalidPacked(hex.to_string())))).collect();
+
+        if let (Some(from), Some(to)) = (cubes[0].invariants(), cubes[1].invariants())
+        {
+            let faults = invariant_faults(&from, &to);
+            if !faults.is_empty()
+            {
+                println!("No sequence connects the states:");
+                for fault in faults.iter()
+                {
+                    println!("  {}", fault);
+                }
+                process::exit(1);
+            }
+        }
+
+        let cancel = argMaxTime.map(cancel_after);
+        let (found, moveNum) = exit_on_error(find_moves_ida(length, &cubes[0], &cubes[1], heuristic, cancel.as_ref()));
+        report_cancelled(cancel.as_ref());
+        println!("{} sequence from {} exploratory move{}:",
+                 if found.is_some() {"Shortest"} else {"No"},
+                 moveNum, if moveNum != 1 {"s"} else {""});
+        if let Some(mut seqStr) = found
+        {
+            if doTrim
+            {
+                seqStr = turns_trimmed_string(&seqStr, size);
+            }
+            seqFormat.print(&[notation_string(&seqStr, &axes, size - 1)], size - 1);
+        }
+        return;
+    }
+
+    let mut size = posArgs[0].parse::<i8>().unwrap_or(0);
 
     let mut doFindMoves = false;
     if size < 0
@@ -1051,53 +2238,244 @@ fn main ()
     }
     let argCubeSize = size as u8;
 
-    if argCubeSize < 1 || 10 < argCubeSize
+    if !(1 ..= 10).contains(&argCubeSize)
     {
         unsafe { usage(); }
     }
 
-    let argMoveStr = if argc > 2 {env::args().skip(2).collect::<Vec<String>>().join("\n")} else {"".to_string()};
+    let mut timings: Vec<(&str, Duration)> = vec![];
 
-    let argMoveVec = movevec_of_string(&argMoveStr, argCubeSize - 1);
+    let argMoveStr = posArgs[1 ..].join("\n");
 
-    let srcCube = Cube::new(argCubeSize);
-    let dstCube = srcCube.copy_with_moves(&argMoveVec);
-    draw_cube(&dstCube, 1, 2);
+    let started = Instant::now();
+    let parse = |string: &str| moves_of_string(string, &axes, seqFormat.singmaster, argCubeSize - 1);
+    let mut argMoveVec = exit_on_error(if doNiss { movevec_of_niss(&argMoveStr, &parse) } else { parse(&argMoveStr) });
+    if doSimplify
+    {
+        argMoveVec = movevec_simplified(&argMoveVec);
+    }
+    timings.push(("parse", started.elapsed()));
 
-    println!("{}", argMoveStr);
+    let started = Instant::now();
+    let mut srcCube = match argState
+    {
+        Some(hex) =>  exit_on_error(bytes_of_hex(hex).and_then(|packed| Cube::from_packed(argCubeSize, &packed))
+                                                      .ok_or_else(|| CubusError::InvalidPacked(hex.to_string()))),
+        None      =>  match argFaceletState
+        {
+            Some(facelets)
+                      =>  {
+                let cube = exit_on_error(Cube::from_facelet_string(facelets));
+                if cube.size != argCubeSize
+                {
+                    exit_on_error(Err(CubusError::SizeMismatch(argCubeSize, cube.size)))
+                }
+                cube
+            },
+            None      =>  Cube::new(argCubeSize)
+        }
+    };
+    if let Some(name) = argPattern
+    {
+        exit_on_error(srcCube.apply_pattern(name));
+    }
+    for lock in exit_on_error(moves_of_string(argLock.unwrap_or(""), &axes, seqFormat.singmaster, argCubeSize - 1)).iter()
+    {
+        srcCube.lock_layer(lock.axdir, lock.axval);
+    }
+    if let Some(invs) = srcCube.invariants()
+    {
+        for fault in invariant_faults(&[0, 0, 0], &invs).iter()
+        {
+            writeln!(io::stderr(), "Warning: unsolvable state: {}", fault);
+        }
+    }
+    let dstCube = exit_on_error(srcCube.copy_with_moves(&argMoveVec));
+    timings.push(("moves", started.elapsed()));
 
-    let maxLen = argMoveVec.len();
-    if doFindMoves && maxLen != 0
+    let started = Instant::now();
+    match argHighlight
     {
-        let (foundVec, moveNum) = find_moves(maxLen, &srcCube, &dstCube);
-        let foundNum = foundVec.len();
-        println!("{} sequence{} from {} exploratory move{}:",
-                 foundNum, if foundNum != 1 {"s"} else {""},
-                 moveNum, if moveNum != 1 {"s"} else {""});
+        Some(pieces)    =>  {
+            let pieces: Vec<&str> = pieces.split(',').collect();
+            exit_on_error(draw_cube_highlighted(&scheme.recolored(&dstCube), &pieces, 1, 2, style));
+        },
+        None            =>  {
+            exit_on_error(draw_cube_within(&scheme.recolored(&dstCube), 1, 2, style, argBudget));
+        }
+    }
+    timings.push(("render", started.elapsed()));
+
+    let metricsStr = seqFormat.metrics_string(&argMoveVec, argCubeSize - 1);
+    if seqFormat.brackets || seqFormat.repeats || seqFormat.groupBy != 0 || doSimplify || doNiss
+    {
+        println!("{}{}", seqFormat.moves_string(&axes.to_notation(&argMoveVec, argCubeSize - 1), argCubeSize - 1), metricsStr);
+    }
+    else
+    {
+        println!("{}{}", argMoveStr, metricsStr);
+    }
+
+    if let Some(dir) = argFramesDir
+    {
+        exit_on_error_at(write_frames(dir, &scheme.recolored(&srcCube), &argMoveVec, style), dir);
+    }
+
+    if let Some(path) = argCast
+    {
+        exit_on_error_at(write_cast(path, &scheme.recolored(&srcCube), &argMoveVec, style, argFps), path);
+    }
+
+    if let Some(path) = argPdf
+    {
+        let solution = exit_on_error(parse(argSolution.unwrap_or("")));
+        let format = |moves: &[Move]| seqFormat.moves_string(&axes.to_notation(moves, argCubeSize - 1), argCubeSize - 1);
+        let scramble = format(&argMoveVec);
+        exit_on_error_at(write_sheet(path, &dstCube, &scramble, &solution, &scheme, &format), path);
+    }
+
+    if doTurnCounts
+    {
+        print!("{}", turn_count_table(&argMoveVec, argCubeSize));
+    }
+
+    if doTopView
+    {
+        print!("{}", exit_on_error(top_view_string(&scheme.recolored(&dstCube))));
+    }
+
+    if let Some(colors) = argFind
+    {
+        let colors: Vec<Huename> = colors.into_iter().map(|name| scheme.unpaint(name)).collect();
+        print_pieces(&dstCube, &colors, &scheme);
+    }
 
-        let mut stepNum: u64 = 0;
-        for movStrRef in foundVec.iter()
+    if doProgress
+    {
+        match dstCube.solved_report()
         {
-            if stepNum % 4 != 0
-            {
-                print!("\t");
-            }
+            Some(report)    =>  println!("{}", report.as_string()),
+            None            =>  println!("Solve progress is only tracked on 3x3x3 cubes.")
+        }
+        if let Some((algorithm, before)) = dstCube.last_layer_case()
+        {
+            let mut moves = singmaster_string_of_movevec(&before, 2);
+            moves.push_str(if moves.is_empty() {""} else {" "});
+            println!("Next: {} ({}): {}{}", algorithm.name, algorithm.note, moves, algorithm.moves);
+        }
+    }
+
+    if doCycles
+    {
+        match dstCube.cycles()
+        {
+            Some(cycles)    =>  {
+                let cycles: Vec<String> = cycles.iter().map(|cycle| cycle.as_string()).collect();
+                println!("{}", if cycles.is_empty() {"No piece moves.".to_string()} else {cycles.join(" ")});
+            },
+            None            =>  println!("Cycles are only traced on 3x3x3 cubes.")
+        }
+    }
 
-            print!("{}", *movStrRef);
-            stepNum += 1;
+    if doPacked
+    {
+        println!("{}", hex_of_bytes(&dstCube.to_packed()));
+    }
+
+    if doQr || argQrPng.is_some()
+    {
+        let text = if doPacked { hex_of_bytes(&dstCube.to_packed()) } else { argMoveStr.clone() };
+        let modules = exit_on_error(qr_modules(text.as_bytes()));
+        if doQr
+        {
+            print!("{}", qr_string(&modules, doQrLight));
+        }
+        if let Some(path) = argQrPng
+        {
+            exit_on_error_at(fs::write(path, qr_png(&modules, 8)), path);
+        }
+    }
+
+    // Prefix moves count as executed already; the search continues after them.
+    let prefixVec = exit_on_error(moves_of_string(argPrefix.unwrap_or(""), &axes, seqFormat.singmaster, argCubeSize - 1));
+    let prefixStr = string_of_movevec(&axes.to_notation(&prefixVec, argCubeSize - 1));
+    let fromCube = exit_on_error(srcCube.copy_with_moves(&prefixVec));
+
+    let maxLen = argMoveVec.len();
+    let searchLen = maxLen.saturating_sub(prefixVec.len());
+    let cancel = argMaxTime.map(cancel_after);
+    if doFindMoves && doOptimal
+    {
+        let started = Instant::now();
+        let (found, moveNum) = exit_on_error(find_moves_ida(searchLen, &fromCube, &dstCube, heuristic, cancel.as_ref()));
+        timings.push(("search", started.elapsed()));
+        report_cancelled(cancel.as_ref());
 
-            if stepNum % 4 == 0
+        println!("{} sequence from {} exploratory move{}:",
+                 if found.is_some() {"Shortest"} else {"No"},
+                 moveNum, if moveNum != 1 {"s"} else {""});
+        if let Some(mut seqStr) = found
+        {
+            if doTrim
             {
-                print!("\n");
+                seqStr = turns_trimmed_string(&seqStr, argCubeSize);
             }
+            seqFormat.print(&[prefixStr + notation_string(&seqStr, &axes, argCubeSize - 1).as_str()], argCubeSize - 1);
+        }
+    }
+    else if doFindMoves && maxLen != 0
+    {
+        let started = Instant::now();
+        let (mut foundVec, moveNum) = exit_on_error(find_moves(searchLen, &fromCube, &dstCube, cancel.as_ref()));
+        timings.push(("search", started.elapsed()));
+        report_cancelled(cancel.as_ref());
+
+        for seqStr in foundVec.iter_mut()
+        {
+            *seqStr = prefixStr.clone() + notation_string(seqStr, &axes, argCubeSize - 1).as_str();
         }
-        if stepNum % 4 != 0
+
+        let foundNum = foundVec.len();
+        println!("{} sequence{} from {} exploratory move{}:",
+                 foundNum, if foundNum != 1 {"s"} else {""},
+                 moveNum, if moveNum != 1 {"s"} else {""});
+
+        seqFormat.print(&foundVec, argCubeSize - 1);
+        if doHistogram
         {
-            print!("\n");
+            print_length_histogram(&foundVec);
         }
     }
 
+    if doTimings
+    {
+        print_timings(&timings);
+    }
+
 }   /* main() */
 
 
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    /// Scrambles logged with axes and layers and in Singmaster notation
+    /// count their moves alike, and malformed lines are skipped.
+    #[test]
+    fn solve_log_counts_moves ()
+    {
+        let records = solve_records_of_text("1700000000\t3\tX2Y2Y2z0\t12.50\n\
+                                             1700000100\t3\tR U2 F' L\t11.25\n\
+                                             1700000200\t3\tR U2\n\
+                                             1700000300\t4\tL' 2U\t30.00\n");
+        let moves: Vec<usize> = records.iter().map(|rec| rec.moves).collect();
+        assert_eq!(moves, [4, 5, 2]);
+        assert_eq!(records[1].scramble, "R U2 F' L");
+
+    }   /* solve_log_counts_moves() */
+
+}   /* mod tests */
+
+
 /* ~ main.rs ~ */